@@ -0,0 +1,61 @@
+// Smoke test for the C API of the Rust game engine (game_engine/include/game_engine.h).
+// Runs without a window: it only drives the Rust World, movement, collision and physics code.
+// Every call is made outside assert(), so the test still runs them when NDEBUG is defined.
+
+#undef NDEBUG
+#include <assert.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include "game_engine.h"
+
+int main() {
+    GeWorld* world = ge_world_new(100.0f, 100.0f);
+    assert(world != NULL);
+
+    // A ball moving right hits the right border halfway through the step and bounces back
+    bool added = ge_world_add_sprite(world, "ball", 85.0f, 10.0f, 10.0f, 10.0f, 1.0f, 0.0f, 255, 255, 255);
+    assert(added);
+    size_t count = ge_world_sprite_count(world);
+    assert(count == 1);
+    GeCollision collision = ge_world_step(world, 10.0f);
+    assert(collision == GE_COLLISION_WITH_BORDER);
+
+    float x = 0.0f, y = 0.0f;
+    bool found = ge_world_get_sprite_pos(world, "ball", &x, &y);
+    assert(found);
+    assert(x == 85.0f && y == 10.0f);
+
+    // Two overlapping sprites collide with each other
+    bool moved = ge_world_set_sprite_pos(world, "ball", 40.0f, 40.0f);
+    assert(moved);
+    added = ge_world_add_sprite(world, "paddle", 45.0f, 45.0f, 10.0f, 10.0f, 0.0f, 0.0f, 255, 255, 255);
+    assert(added);
+    bool intersect = ge_world_sprites_intersect(world, "ball", "paddle");
+    assert(intersect);
+    collision = ge_world_collision(world, 0.0f);
+    assert(collision == GE_COLLISION_WITH_SPRITE);
+
+    // Gravity pulls a resting sprite down in fixed steps of 8 ms
+    GePhysics* physics = ge_physics_new(8.0f, 0.0f, 1.0f / 1024.0f);
+    assert(physics != NULL);
+    added = ge_world_add_sprite(world, "box", 10.0f, 10.0f, 10.0f, 10.0f, 0.0f, 0.0f, 255, 255, 255);
+    assert(added);
+    ge_physics_update(physics, world, 12.0f);
+    found = ge_world_get_sprite_pos(world, "box", &x, &y);
+    assert(found);
+    assert(x == 10.0f && y == 10.0625f);
+    ge_physics_free(physics);
+
+    // Unknown sprites and null pointers are rejected
+    moved = ge_world_set_sprite_pos(world, "ghost", 1.0f, 1.0f);
+    assert(!moved);
+    found = ge_world_get_sprite_pos(world, "ghost", &x, &y);
+    assert(!found);
+    count = ge_world_sprite_count(NULL);
+    assert(count == 0);
+
+    ge_world_free(world);
+
+    printf("C API smoke test passed\n");
+    return 0;
+}
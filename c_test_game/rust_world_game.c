@@ -0,0 +1,42 @@
+// Test game in C that uses the Rust engine layer through its C API:
+// the Rust World moves a ball and handles its collisions, the arrow keys move a paddle.
+
+#include "game_engine.h"
+
+#define GLFW_KEY_DOWN 264
+#define GLFW_KEY_UP 265
+
+int main() {
+    GeEngine* engine = ge_engine_create_window("C Rust World Game", 800, 600);
+    if (engine == NULL) {
+        return 1;
+    }
+
+    GeWorld* world = ge_world_new(800.0f, 600.0f);
+    ge_world_add_sprite(world, "paddle", 20.0f, 250.0f, 20.0f, 100.0f, 0.0f, 0.0f, 255, 255, 255);
+    ge_world_add_sprite(world, "ball", 400.0f, 300.0f, 20.0f, 20.0f, 4.0f, 3.0f, 255, 0, 0);
+
+    float paddle_x = 0.0f, paddle_y = 0.0f;
+
+    // Main loop
+    while (!ge_engine_should_close(engine)) {
+        ge_engine_begin_frame(engine);
+
+        ge_world_get_sprite_pos(world, "paddle", &paddle_x, &paddle_y);
+        if (ge_engine_key_pressed(engine, GLFW_KEY_UP)) {
+            ge_world_set_sprite_pos(world, "paddle", paddle_x, paddle_y - 5.0f);
+        }
+        if (ge_engine_key_pressed(engine, GLFW_KEY_DOWN)) {
+            ge_world_set_sprite_pos(world, "paddle", paddle_x, paddle_y + 5.0f);
+        }
+
+        ge_world_step(world, 1.0f);
+        ge_world_render(engine, world);
+
+        ge_engine_end_frame(engine);
+    }
+
+    ge_world_free(world);
+    ge_engine_free(engine);
+    return 0;
+}
@@ -1,19 +1,242 @@
-use cc;
+use std::env;
 
 fn main() {
-    println!("cargo:rerun-if-changed=../opengl_wrapper_lib/opengl_wrapper_lib.c");
-
-    cc::Build::new()
-        .file("../opengl_wrapper_lib/opengl_wrapper_lib.c")
-        .include("../opengl_wrapper_lib")
-        .shared_flag(true)
-        .compile("opengl_wrapper_lib");
-
-    println!("cargo:rustc-link-lib=dylib=opengl_wrapper_lib");
-    println!(
-        "cargo:rustc-link-search=native={}",
-        std::env::var("OUT_DIR").unwrap()
-    );
-    println!("cargo:rustc-link-lib=dylib=glfw");
-    println!("cargo:rustc-link-lib=dylib=GL");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // `backend` is set when any implementation of the C library API is compiled in.
+    println!("cargo::rustc-check-cfg=cfg(backend)");
+    if env::var_os("CARGO_FEATURE_GL_BACKEND").is_some()
+        || env::var_os("CARGO_FEATURE_HEADLESS").is_some()
+    {
+        println!("cargo::rustc-cfg=backend");
+    }
+
+    #[cfg(feature = "gl-backend")]
+    gl_backend::build();
+
+    generate_c_header();
+}
+
+/// Native part of the build: the C wrapper, GLFW, OpenGL and the generated bindings.
+#[cfg(feature = "gl-backend")]
+mod gl_backend {
+    use std::env;
+    use std::path::PathBuf;
+
+    const WRAPPER_DIR: &str = "../opengl_wrapper_lib";
+    const WRAPPER_HEADER: &str = "../opengl_wrapper_lib/opengl_wrapper_lib.h";
+    const WRAPPER_SOURCE: &str = "../opengl_wrapper_lib/opengl_wrapper_lib.c";
+    const WRAPPER_LIB: &str = "opengl_wrapper_lib";
+
+    pub fn build() {
+        println!("cargo:rerun-if-changed={WRAPPER_SOURCE}");
+        println!("cargo:rerun-if-changed={WRAPPER_HEADER}");
+
+        let glfw = find_glfw();
+        let gl = find_gl();
+
+        build_wrapper(&glfw.include_paths);
+        glfw.emit_link_flags();
+        gl.emit_link_flags();
+
+        generate_bindings(&glfw.include_paths);
+    }
+
+    /// A native library found on the system or built from source.
+    ///
+    /// The link flags are printed only after the wrapper itself, because static
+    /// archives must come before the libraries they depend on.
+    struct NativeLib {
+        include_paths: Vec<PathBuf>,
+        link_paths: Vec<PathBuf>,
+        /// Libraries in `kind=name` form, e.g. `dylib=glfw`.
+        libs: Vec<String>,
+    }
+
+    impl NativeLib {
+        fn emit_link_flags(&self) {
+            for path in &self.link_paths {
+                println!("cargo:rustc-link-search=native={}", path.display());
+            }
+            for lib in &self.libs {
+                println!("cargo:rustc-link-lib={lib}");
+            }
+        }
+    }
+
+    /// Discovers the system GLFW through pkg-config.
+    ///
+    /// # Panics
+    ///
+    /// Panics with installation hints if pkg-config does not know `glfw3` 3.3 or newer,
+    /// the first version with the gamepad functions.
+    #[cfg(not(feature = "vendored-glfw"))]
+    fn find_glfw() -> NativeLib {
+        match pkg_config::Config::new()
+            .atleast_version("3.3")
+            .cargo_metadata(false)
+            .probe("glfw3")
+        {
+            Ok(lib) => NativeLib {
+                include_paths: lib.include_paths,
+                link_paths: lib.link_paths,
+                libs: lib.libs.iter().map(|name| format!("dylib={name}")).collect(),
+            },
+            Err(err) => panic!(
+                "\n\nGLFW 3.3 or newer was not found through pkg-config:\n{err}\n\n\
+                 Install the GLFW development package (e.g. `libglfw3-dev` on Debian/Ubuntu, \
+                 `glfw-devel` on Fedora, `glfw` on Homebrew), point PKG_CONFIG_PATH at its \
+                 `glfw3.pc`, or build with `--features vendored-glfw`.\n"
+            ),
+        }
+    }
+
+    /// Builds GLFW as a static library from the sources in `GLFW_SRC_DIR`
+    /// (default: `vendor/glfw` in the workspace root).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the GLFW sources are missing.
+    #[cfg(feature = "vendored-glfw")]
+    fn find_glfw() -> NativeLib {
+        println!("cargo:rerun-if-env-changed=GLFW_SRC_DIR");
+        let src = env::var_os("GLFW_SRC_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("../vendor/glfw"));
+        if !src.join("CMakeLists.txt").exists() {
+            panic!(
+                "\n\nThe `vendored-glfw` feature needs the GLFW sources in {}.\n\
+                 Fetch them with `git clone --depth 1 --branch 3.4 https://github.com/glfw/glfw vendor/glfw` \
+                 or set GLFW_SRC_DIR.\n",
+                src.display()
+            );
+        }
+
+        let dst = cmake::Config::new(&src)
+            .define("BUILD_SHARED_LIBS", "OFF")
+            .define("GLFW_BUILD_EXAMPLES", "OFF")
+            .define("GLFW_BUILD_TESTS", "OFF")
+            .define("GLFW_BUILD_DOCS", "OFF")
+            .define("GLFW_INSTALL", "ON")
+            .define("CMAKE_INSTALL_LIBDIR", "lib")
+            .build();
+
+        NativeLib {
+            include_paths: vec![dst.join("include")],
+            link_paths: vec![dst.join("lib")],
+            libs: ["static=glfw3", "dylib=m", "dylib=dl", "dylib=pthread"]
+                .iter()
+                .map(|lib| lib.to_string())
+                .collect(),
+        }
+    }
+
+    /// Discovers OpenGL through pkg-config, falling back to plain `-lGL`.
+    fn find_gl() -> NativeLib {
+        match pkg_config::Config::new().cargo_metadata(false).probe("gl") {
+            Ok(lib) => NativeLib {
+                include_paths: lib.include_paths,
+                link_paths: lib.link_paths,
+                libs: lib.libs.iter().map(|name| format!("dylib={name}")).collect(),
+            },
+            Err(_) => NativeLib {
+                include_paths: Vec::new(),
+                link_paths: Vec::new(),
+                libs: vec![String::from("dylib=GL")],
+            },
+        }
+    }
+
+    /// Compiles `opengl_wrapper_lib.c`.
+    ///
+    /// With the `static-wrapper` feature the wrapper is archived and linked into the
+    /// Rust binary. Otherwise it becomes `libopengl_wrapper_lib.so` in `OUT_DIR`, which
+    /// `cargo run` and `cargo test` find, but a copied binary needs on its library path.
+    fn build_wrapper(glfw_includes: &[PathBuf]) {
+        let mut build = cc::Build::new();
+        build
+            .file(WRAPPER_SOURCE)
+            .include(WRAPPER_DIR)
+            .includes(glfw_includes);
+
+        if cfg!(feature = "static-wrapper") {
+            // `compile` prints the `static=` link flags itself.
+            build.compile(WRAPPER_LIB);
+            return;
+        }
+
+        build.pic(true);
+        let objects = build.compile_intermediates();
+        let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+        let library = out_dir.join(format!("lib{WRAPPER_LIB}.so"));
+
+        let status = build
+            .get_compiler()
+            .to_command()
+            .arg("-shared")
+            .arg("-o")
+            .arg(&library)
+            .args(&objects)
+            .status()
+            .expect("run the C compiler to link the wrapper");
+        assert!(status.success(), "linking {} failed", library.display());
+
+        println!("cargo:rustc-link-search=native={}", out_dir.display());
+        println!("cargo:rustc-link-lib=dylib={WRAPPER_LIB}");
+    }
+
+    /// Generates the raw Rust bindings for `opengl_wrapper_lib.h` into `OUT_DIR/bindings.rs`.
+    ///
+    /// Only the wrapper's own functions, the `Sprite` struct and the GLFW key, mouse
+    /// button and gamepad constants are generated; the GLFW window stays an opaque type.
+    fn generate_bindings(glfw_includes: &[PathBuf]) {
+        let bindings = bindgen::Builder::default()
+            .header(WRAPPER_HEADER)
+            .clang_args(
+                glfw_includes
+                    .iter()
+                    .map(|path| format!("-I{}", path.display())),
+            )
+            .rust_edition(bindgen::RustEdition::Edition2024)
+            .allowlist_function(
+                "create_game_window|create_sprite|free_sprite|render_sprite|update_sprite_position\
+                 |update_game_window|clear_screen|window_should_close|get_key|get_mouse_button\
+                 |get_cursor_pos|get_gamepad_button|get_gamepad_axis|get_window",
+            )
+            .allowlist_type("Sprite")
+            .allowlist_var("GLFW_PRESS|GLFW_KEY_.*|GLFW_MOUSE_BUTTON_.*|GLFW_GAMEPAD_.*")
+            .opaque_type("GLFWwindow")
+            .default_macro_constant_type(bindgen::MacroTypeVariation::Signed)
+            .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+            .generate()
+            .expect("generate bindings for opengl_wrapper_lib.h");
+
+        let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+        bindings
+            .write_to_file(out_path.join("bindings.rs"))
+            .expect("write bindings.rs");
+    }
 }
+
+/// Generates `include/game_engine.h` for the C API in `src/capi.rs`.
+#[cfg(feature = "capi")]
+fn generate_c_header() {
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("read cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("generate the C header for the capi feature")
+        .write_to_file(crate_dir.join("include").join("game_engine.h"));
+}
+
+#[cfg(not(feature = "capi"))]
+fn generate_c_header() {}
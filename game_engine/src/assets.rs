@@ -0,0 +1,522 @@
+//! Asset manager with typed handles, background loading and hot reloading.
+//!
+//! An [`AssetManager`] loads files below a root directory on a worker thread and
+//! hands out [`Handle`]s right away. A handle is reference counted: loading the
+//! same path as the same type again while a handle is alive returns the same
+//! asset, and the asset is freed with its last handle.
+//!
+//! ```no_run
+//! use game_engine::assets::AssetManager;
+//!
+//! let assets = AssetManager::new("assets");
+//! let level = assets.load::<String>("levels/1.json");
+//! // ... later, e.g. once per frame:
+//! if let Some(text) = level.get() {
+//!     println!("{} bytes", text.len());
+//! }
+//! ```
+//!
+//! Any type implementing [`Asset`] can be loaded: text and raw data files are
+//! built in, levels come with the `serde` feature, sounds and sound presets with
+//! the `audio` feature. The C library
+//! draws neither images nor text, so there are no texture or font types yet; they
+//! would plug in through the same trait.
+//!
+//! In debug builds the manager watches the loaded files and reloads an asset when
+//! its file changes, so [`Handle::version`] goes up and the game can react.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::thread::{self, JoinHandle};
+#[cfg(debug_assertions)]
+use std::{sync::atomic::AtomicBool, time::Duration, time::SystemTime};
+
+/// How often the hot reload watcher checks the files.
+#[cfg(debug_assertions)]
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Errors that can occur while loading an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetError {
+    /// The file could not be read.
+    Io(String),
+    /// The file content is not a valid asset of the requested type.
+    Parse(String),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::Io(msg) => write!(f, "could not read asset: {msg}"),
+            AssetError::Parse(msg) => write!(f, "could not parse asset: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+/// A type that can be loaded from a file by the [`AssetManager`].
+pub trait Asset: Send + Sync + Sized + 'static {
+    /// Creates the asset from the content of its file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err` with a description of the problem if the content is not valid.
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String>;
+}
+
+/// Raw data files.
+impl Asset for Vec<u8> {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        Ok(bytes)
+    }
+}
+
+/// UTF-8 text files, e.g. JSON data that is parsed by the game.
+impl Asset for String {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        String::from_utf8(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// WAV or OGG/Vorbis sound files.
+#[cfg(feature = "audio")]
+impl Asset for crate::audio::Sound {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        crate::audio::Sound::from_bytes(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// JSON sound effect presets.
+#[cfg(all(feature = "audio", feature = "serde"))]
+impl Asset for crate::audio::SfxPreset {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        let json = String::from_utf8(bytes).map_err(|err| err.to_string())?;
+        crate::audio::SfxPreset::from_json(&json).map_err(|err| err.to_string())
+    }
+}
+
+/// JSON level files; errors name line and column, the path is added by the manager.
+#[cfg(feature = "serde")]
+impl Asset for crate::level::Level {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        use crate::level::{Level, LevelError};
+        let json = String::from_utf8(bytes).map_err(|err| err.to_string())?;
+        Level::from_json(&json, "").map_err(|err| match err {
+            LevelError::Invalid {
+                line,
+                column,
+                message,
+                ..
+            } => format!("line {line}, column {column}: {message}"),
+            other => other.to_string(),
+        })
+    }
+}
+
+/// Load state of an asset, shared by all its handles.
+struct Slot<T> {
+    path: PathBuf,
+    state: Mutex<State<T>>,
+    loaded: Condvar,
+    /// Number of finished loads, successful or not.
+    version: AtomicU32,
+}
+
+struct State<T> {
+    value: Option<Arc<T>>,
+    error: Option<AssetError>,
+}
+
+impl<T: Asset> Slot<T> {
+    fn state(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Reads and parses the file. A failed reload keeps the previous value.
+    fn load(&self) {
+        let result = fs::read(&self.path)
+            .map_err(|err| AssetError::Io(format!("{}: {err}", self.path.display())))
+            .and_then(|bytes| {
+                T::from_bytes(bytes)
+                    .map_err(|err| AssetError::Parse(format!("{}: {err}", self.path.display())))
+            });
+
+        let mut state = self.state();
+        match result {
+            Ok(value) => {
+                state.value = Some(Arc::new(value));
+                state.error = None;
+            }
+            Err(err) => state.error = Some(err),
+        }
+        self.version.fetch_add(1, Ordering::AcqRel);
+        self.loaded.notify_all();
+    }
+}
+
+/// Typed, reference-counted handle to an asset loaded by an [`AssetManager`].
+pub struct Handle<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: Arc::clone(&self.slot),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("path", &self.slot.path)
+            .field("version", &self.slot.version.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+impl<T: Asset> Handle<T> {
+    /// Returns the full path of the asset file.
+    pub fn path(&self) -> &Path {
+        &self.slot.path
+    }
+
+    /// Returns the asset, or `None` while it is loading or if it failed to load.
+    ///
+    /// After a hot reload the new value is returned; earlier values stay valid.
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.slot.state().value.clone()
+    }
+
+    /// Returns the error of the last load, if it failed.
+    pub fn error(&self) -> Option<AssetError> {
+        self.slot.state().error.clone()
+    }
+
+    /// Returns `true` once the asset was loaded successfully.
+    pub fn is_loaded(&self) -> bool {
+        self.slot.state().value.is_some()
+    }
+
+    /// Returns the number of finished loads: 0 while loading, 1 after the first
+    /// load and one more after every hot reload, successful or not.
+    pub fn version(&self) -> u32 {
+        self.slot.version.load(Ordering::Acquire)
+    }
+
+    /// Blocks until the first load finished.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AssetError)` if the last load failed.
+    pub fn wait(&self) -> Result<Arc<T>, AssetError> {
+        let mut state = self.slot.state();
+        while self.version() == 0 {
+            state = self
+                .slot
+                .loaded
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        match (&state.value, &state.error) {
+            (_, Some(err)) => Err(err.clone()),
+            (Some(value), None) => Ok(Arc::clone(value)),
+            (None, None) => unreachable!("finished load without value or error"),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A loaded file checked for changes by the hot reload watcher.
+#[cfg(debug_assertions)]
+struct Watch {
+    path: PathBuf,
+    stamp: Option<Stamp>,
+    /// Returns the reload job, or `None` once all handles of the asset are gone.
+    reload: Box<dyn Fn() -> Option<Job> + Send>,
+}
+
+/// Modification time and length of a file. File systems with a coarse time
+/// resolution keep the time of quick successive writes, so the length is compared too.
+#[cfg(debug_assertions)]
+type Stamp = (SystemTime, u64);
+
+#[cfg(debug_assertions)]
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Hot reload watcher thread.
+#[cfg(debug_assertions)]
+struct Watcher {
+    watches: Arc<Mutex<Vec<Watch>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(debug_assertions)]
+impl Watcher {
+    fn start(jobs: Sender<Job>) -> Self {
+        let watches: Arc<Mutex<Vec<Watch>>> = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let watches = Arc::clone(&watches);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Acquire) {
+                    thread::park_timeout(WATCH_INTERVAL);
+                    let mut watches = watches.lock().unwrap_or_else(|err| err.into_inner());
+                    watches.retain_mut(|watch| {
+                        let now = stamp(&watch.path);
+                        if now == watch.stamp {
+                            return true;
+                        }
+                        watch.stamp = now;
+                        match (watch.reload)() {
+                            Some(job) => jobs.send(job).is_ok(),
+                            None => false,
+                        }
+                    });
+                }
+            })
+        };
+        Self {
+            watches,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    fn watch<T: Asset>(&self, slot: &Arc<Slot<T>>) {
+        let weak = Arc::downgrade(slot);
+        let watch = Watch {
+            path: slot.path.clone(),
+            stamp: stamp(&slot.path),
+            reload: Box::new(move || {
+                let slot = weak.upgrade()?;
+                Some(Box::new(move || slot.load()) as Job)
+            }),
+        };
+        self.watches
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(watch);
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Loads assets on a worker thread and caches them by path and type.
+pub struct AssetManager {
+    root: PathBuf,
+    /// Weak references to the loaded slots, so assets are freed with their last handle.
+    cache: Mutex<HashMap<(TypeId, PathBuf), Weak<dyn Any + Send + Sync>>>,
+    jobs: Option<Sender<Job>>,
+    worker: Option<JoinHandle<()>>,
+    #[cfg(debug_assertions)]
+    watcher: Option<Watcher>,
+}
+
+impl AssetManager {
+    /// Creates a manager for the assets below `root` and starts its worker thread.
+    ///
+    /// In debug builds a second thread watches the loaded files for hot reloading.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let worker = thread::spawn(move || {
+            for job in queue {
+                job();
+            }
+        });
+        Self {
+            root: root.into(),
+            cache: Mutex::default(),
+            #[cfg(debug_assertions)]
+            watcher: Some(Watcher::start(jobs.clone())),
+            jobs: Some(jobs),
+            worker: Some(worker),
+        }
+    }
+
+    /// Returns the directory asset paths are relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Starts loading the asset at `path` (relative to the root) in the background.
+    ///
+    /// If a handle to the same path and type is still alive, no file is read and
+    /// a handle to the existing asset is returned.
+    pub fn load<T: Asset>(&self, path: impl AsRef<Path>) -> Handle<T> {
+        let path = self.root.join(path);
+        let key = (TypeId::of::<T>(), path.clone());
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Some(slot) = cache.get(&key).and_then(Weak::upgrade)
+            && let Ok(slot) = slot.downcast::<Slot<T>>()
+        {
+            return Handle { slot };
+        }
+
+        let slot = Arc::new(Slot {
+            path,
+            state: Mutex::new(State {
+                value: None,
+                error: None,
+            }),
+            loaded: Condvar::new(),
+            version: AtomicU32::new(0),
+        });
+        cache.retain(|_, slot| slot.strong_count() > 0);
+        let weak: Weak<dyn Any + Send + Sync> = Arc::downgrade(&slot) as Weak<Slot<T>>;
+        cache.insert(key, weak);
+
+        #[cfg(debug_assertions)]
+        if let Some(watcher) = &self.watcher {
+            watcher.watch(&slot);
+        }
+        let job_slot = Arc::clone(&slot);
+        self.send(Box::new(move || job_slot.load()));
+        Handle { slot }
+    }
+
+    /// Returns the number of assets that still have a handle.
+    pub fn len(&self) -> usize {
+        let cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        cache
+            .values()
+            .filter(|slot| slot.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn send(&self, job: Job) {
+        if let Some(jobs) = &self.jobs {
+            // The worker only stops when the manager is dropped.
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+impl Drop for AssetManager {
+    /// Stops the watcher, lets the worker finish the queued loads and joins it.
+    fn drop(&mut self) {
+        // The watcher holds a sender too, the worker only stops once it is gone.
+        #[cfg(debug_assertions)]
+        drop(self.watcher.take());
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for the asset files of one test.
+    fn asset_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("game_engine_assets_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Verify that assets load in the background and report errors.
+    #[test]
+    fn test_load() {
+        let dir = asset_dir("load");
+        fs::write(dir.join("level.txt"), "hello").unwrap();
+        fs::write(dir.join("broken.txt"), [0xff, 0xfe]).unwrap();
+        let assets = AssetManager::new(&dir);
+
+        let text = assets.load::<String>("level.txt");
+        assert_eq!(*text.wait().unwrap(), "hello");
+        assert_eq!(text.version(), 1);
+        assert!(text.is_loaded());
+
+        let broken = assets.load::<String>("broken.txt");
+        assert!(matches!(broken.wait(), Err(AssetError::Parse(_))));
+        let missing = assets.load::<String>("missing.txt");
+        assert!(matches!(missing.wait(), Err(AssetError::Io(_))));
+        assert!(missing.get().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Verify that repeated loads share one asset, per type, while a handle is alive.
+    #[test]
+    fn test_deduplication() {
+        let dir = asset_dir("dedup");
+        fs::write(dir.join("data.bin"), "abc").unwrap();
+        let assets = AssetManager::new(&dir);
+
+        let first = assets.load::<String>("data.bin");
+        let second = assets.load::<String>("data.bin");
+        let bytes = assets.load::<Vec<u8>>("data.bin");
+        assert!(Arc::ptr_eq(&first.wait().unwrap(), &second.wait().unwrap()));
+        assert_eq!(*bytes.wait().unwrap(), b"abc");
+        assert_eq!(assets.len(), 2);
+
+        drop((first, second));
+        assert_eq!(assets.len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Verify that a changed file is reloaded and a broken change keeps the old value.
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_hot_reload() {
+        let dir = asset_dir("reload");
+        let path = dir.join("value.txt");
+        fs::write(&path, "one").unwrap();
+        let assets = AssetManager::new(&dir);
+        let value = assets.load::<String>("value.txt");
+        value.wait().unwrap();
+
+        let wait_for_version = |version| {
+            for _ in 0..100 {
+                if value.version() >= version {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            panic!("asset was not reloaded");
+        };
+
+        // Every write changes the length, so it is seen even where the modification
+        // time stays the same.
+        fs::write(&path, "two, longer").unwrap();
+        wait_for_version(2);
+        assert_eq!(*value.get().unwrap(), "two, longer");
+
+        fs::write(&path, [0xff]).unwrap();
+        wait_for_version(3);
+        assert_eq!(*value.get().unwrap(), "two, longer");
+        assert!(matches!(value.error(), Some(AssetError::Parse(_))));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
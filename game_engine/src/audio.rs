@@ -0,0 +1,470 @@
+//! Audio output, enabled by the `audio` feature.
+//!
+//! [`Audio`] owns the output and a software mixer. Sounds are decoded once
+//! into a [`Sound`] (WAV or OGG/Vorbis, generated tones or [`SfxPreset`] synth
+//! effects) and can then be played on one of the [`Bus`]es any number of times,
+//! also overlapping:
+//!
+//! ```no_run
+//! use game_engine::audio::{Audio, Bus, Sound};
+//! use std::time::Duration;
+//!
+//! let audio = Audio::new();
+//! let music = audio.play_looping(&Sound::load("music.ogg").unwrap(), Bus::Music);
+//! audio.play(&Sound::tone(220.0, Duration::from_millis(150), 0.2), Bus::Sfx);
+//! audio.set_bus_volume(Bus::Music, 0.5);
+//! audio.fade_out(music, Duration::from_secs(2));
+//! ```
+//!
+//! Without an output device [`Audio::new`] falls back to a null output that keeps
+//! the mixer running in real time but discards the samples. [`Audio::offline`]
+//! renders only on [`Audio::advance`] and records the result, so tests can check
+//! what was played or write it to a WAV file.
+//!
+//! Background [`Music`] is streamed from its file while it plays, with an intro,
+//! a loop region and crossfades between tracks. Stingers can wait for the next
+//! beat or bar of the music.
+//!
+//! Sounds played with [`Audio::play_at`] have a world position. They are panned and
+//! attenuated relative to the [`Listener`], usually the screen or camera center.
+
+mod mixer;
+mod music;
+mod sound;
+mod spatial;
+mod synth;
+mod wav;
+
+pub use mixer::{Bus, SoundHandle};
+pub use music::{Music, Quantize, Tempo};
+pub use sound::Sound;
+pub use spatial::Listener;
+pub use synth::{Envelope, SfxPreset, Waveform};
+
+use crate::sprite::Pos;
+use log::warn;
+use mixer::Mixer;
+use music::MusicStream;
+use rodio::{OutputStream, Source};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Sample rate of the mixer in Hz. Sounds are converted to it when loaded.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// The mixer always works in stereo.
+pub const CHANNELS: u16 = 2;
+
+/// Number of sounds that can play at the same time.
+pub const MAX_VOICES: usize = 32;
+
+/// Frames mixed per lock of the mixer in the output thread.
+const BLOCK_FRAMES: usize = 512;
+
+/// Errors of the audio module.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AudioError {
+    /// A sound file could not be read or written.
+    Io(String),
+    /// A sound file has an unsupported format or is broken.
+    Decode(String),
+    /// The audio output device could not be opened.
+    Device(String),
+    /// A sound effect preset could not be parsed.
+    Preset(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::Io(msg) => write!(f, "sound file error: {msg}"),
+            AudioError::Decode(msg) => write!(f, "could not decode sound: {msg}"),
+            AudioError::Device(msg) => write!(f, "could not open audio device: {msg}"),
+            AudioError::Preset(msg) => write!(f, "invalid sound effect preset: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+/// Where the mixed samples go.
+enum Output {
+    /// The sound card; rodio pulls blocks from the mixer in its own thread.
+    Device { _stream: OutputStream },
+    /// No device: the mixer follows the wall clock and the samples are dropped.
+    Null(Mutex<Instant>),
+    /// Rendered by [`Audio::advance`] and recorded.
+    Offline(Mutex<Vec<f32>>),
+}
+
+/// Audio output with a multi-voice mixer and the buses [`Bus::Sfx`], [`Bus::Music`]
+/// and [`Bus::Ui`].
+///
+/// All methods take `&self`; the mixer is shared with the output thread.
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    output: Output,
+}
+
+impl Audio {
+    /// Opens the default output device, or falls back to [`Audio::null`] with a
+    /// warning if there is none (headless servers, containers, SSH sessions).
+    pub fn new() -> Self {
+        Audio::open_device().unwrap_or_else(|err| {
+            warn!("{err}; continuing without sound");
+            Audio::null()
+        })
+    }
+
+    /// Opens the default output device and starts the mixer.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Device)` if the default output device cannot be opened.
+    pub fn open_device() -> Result<Self, AudioError> {
+        let stream = rodio::OutputStreamBuilder::open_default_stream()
+            .map_err(|err| AudioError::Device(err.to_string()))?;
+
+        let mixer = Arc::new(Mutex::new(Mixer::new(MAX_VOICES)));
+        stream.mixer().add(MixerSource {
+            mixer: Arc::clone(&mixer),
+            buffer: vec![0.0; BLOCK_FRAMES * CHANNELS as usize],
+            pos: BLOCK_FRAMES * CHANNELS as usize,
+        });
+
+        Ok(Self {
+            mixer,
+            output: Output::Device { _stream: stream },
+        })
+    }
+
+    /// Creates audio without output. Sounds play (and end) in real time, silently.
+    pub fn null() -> Self {
+        Self::with_output(Output::Null(Mutex::new(Instant::now())))
+    }
+
+    /// Creates audio that is only rendered by [`Audio::advance`], for tests.
+    pub fn offline() -> Self {
+        Self::with_output(Output::Offline(Mutex::new(Vec::new())))
+    }
+
+    fn with_output(output: Output) -> Self {
+        Self {
+            mixer: Arc::new(Mutex::new(Mixer::new(MAX_VOICES))),
+            output,
+        }
+    }
+
+    /// Returns `true` if the sound goes to an output device.
+    pub fn has_device(&self) -> bool {
+        matches!(self.output, Output::Device { .. })
+    }
+
+    /// Renders the next `duration` of an offline output into its recording.
+    ///
+    /// The other outputs advance on their own, so this does nothing for them.
+    pub fn advance(&self, duration: Duration) {
+        if let Output::Offline(recording) = &self.output {
+            let mut recording = lock(recording);
+            render_frames(&mut lock(&self.mixer), frames(duration), |block| {
+                recording.extend_from_slice(block)
+            });
+        }
+    }
+
+    /// Returns the interleaved stereo samples rendered by an offline output so far.
+    ///
+    /// Always empty for the other outputs.
+    pub fn recording(&self) -> Vec<f32> {
+        match &self.output {
+            Output::Offline(recording) => lock(recording).clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Writes [`Audio::recording`] to a 16 bit stereo WAV file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Io)` if the file cannot be written.
+    pub fn write_wav(&self, path: impl AsRef<Path>) -> Result<(), AudioError> {
+        let path = path.as_ref();
+        std::fs::write(path, wav::encode(&self.recording(), CHANNELS, SAMPLE_RATE))
+            .map_err(|err| AudioError::Io(format!("{}: {err}", path.display())))
+    }
+
+    /// Locks the mixer. A null output first catches up with the wall clock.
+    fn mixer(&self) -> MutexGuard<'_, Mixer> {
+        let mut mixer = lock(&self.mixer);
+        if let Output::Null(last) = &self.output {
+            let mut last = lock(last);
+            let elapsed = frames(last.elapsed());
+            if elapsed > 0 {
+                render_frames(&mut mixer, elapsed, |_| ());
+                *last = Instant::now();
+            }
+        }
+        mixer
+    }
+
+    /// Plays `sound` once on `bus`.
+    ///
+    /// # Returns
+    ///
+    /// A handle to stop or fade the sound while it plays.
+    pub fn play(&self, sound: &Sound, bus: Bus) -> SoundHandle {
+        self.mixer().play(sound, bus, false, None)
+    }
+
+    /// Plays `sound` on `bus` in a loop until it is stopped.
+    pub fn play_looping(&self, sound: &Sound, bus: Bus) -> SoundHandle {
+        self.mixer().play(sound, bus, true, None)
+    }
+
+    /// Plays `sound` once on `bus` from a world position.
+    ///
+    /// Pan and volume follow the position relative to the [`Listener`].
+    pub fn play_at(&self, sound: &Sound, bus: Bus, position: Pos) -> SoundHandle {
+        self.mixer().play(sound, bus, false, Some(position))
+    }
+
+    /// Plays `sound` on `bus` in a loop from a world position.
+    pub fn play_looping_at(&self, sound: &Sound, bus: Bus, position: Pos) -> SoundHandle {
+        self.mixer().play(sound, bus, true, Some(position))
+    }
+
+    /// Moves a playing sound, e.g. to follow a sprite.
+    pub fn set_position(&self, handle: SoundHandle, position: Pos) {
+        self.mixer().set_position(handle, position);
+    }
+
+    /// Sets the point sounds are heard from, e.g. when the camera moves.
+    pub fn set_listener(&self, listener: Listener) {
+        self.mixer().set_listener(listener);
+    }
+
+    /// Returns the current listener.
+    pub fn listener(&self) -> Listener {
+        self.mixer().listener()
+    }
+
+    /// Starts streaming a music track on [`Bus::Music`], crossfading from the
+    /// current track over `crossfade`.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Io)` if the file cannot be opened.
+    /// * `Err(AudioError::Decode)` if its format is not supported.
+    pub fn play_music(
+        &self,
+        music: &Music,
+        crossfade: Duration,
+    ) -> Result<SoundHandle, AudioError> {
+        let stream = MusicStream::open(music)?;
+        Ok(self.mixer().play_music(stream, frames(crossfade)))
+    }
+
+    /// Fades out the current music track over `fade` and stops it.
+    pub fn stop_music(&self, fade: Duration) {
+        self.mixer().stop_music(frames(fade));
+    }
+
+    /// Plays a short musical phrase on [`Bus::Music`], synchronized to the beat
+    /// or bar of the current track if it has a [`Tempo`].
+    pub fn play_stinger(&self, sound: &Sound, quantize: Quantize) -> SoundHandle {
+        self.mixer().play_stinger(sound, quantize)
+    }
+
+    /// Stops a sound immediately. Handles of finished sounds are ignored.
+    pub fn stop(&self, handle: SoundHandle) {
+        self.mixer().stop(handle);
+    }
+
+    /// Fades a sound out over `duration` and stops it.
+    pub fn fade_out(&self, handle: SoundHandle, duration: Duration) {
+        self.mixer().fade(handle, 0.0, frames(duration), true);
+    }
+
+    /// Changes the volume of a playing sound smoothly over `duration`.
+    pub fn fade_to(&self, handle: SoundHandle, volume: f32, duration: Duration) {
+        self.mixer().fade(handle, volume, frames(duration), false);
+    }
+
+    /// Sets the volume of a playing sound (1.0 is unchanged).
+    pub fn set_volume(&self, handle: SoundHandle, volume: f32) {
+        self.mixer().set_volume(handle, volume);
+    }
+
+    /// Returns `true` while the sound is playing.
+    pub fn is_playing(&self, handle: SoundHandle) -> bool {
+        self.mixer().is_playing(handle)
+    }
+
+    /// Returns the number of sounds currently playing.
+    pub fn active_voices(&self) -> usize {
+        self.mixer().active_voices()
+    }
+
+    /// Sets the volume of a bus (1.0 is unchanged).
+    pub fn set_bus_volume(&self, bus: Bus, volume: f32) {
+        self.mixer().set_bus_volume(bus, volume);
+    }
+
+    /// Returns the volume of a bus.
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.mixer().bus_volume(bus)
+    }
+
+    /// Mutes or unmutes a bus. The bus volume is kept.
+    pub fn set_bus_muted(&self, bus: Bus, muted: bool) {
+        self.mixer().set_bus_muted(bus, muted);
+    }
+
+    /// Returns `true` if the bus is muted.
+    pub fn is_bus_muted(&self, bus: Bus) -> bool {
+        self.mixer().is_bus_muted(bus)
+    }
+}
+
+/// Locks a mutex, ignoring poisoning; the mixer state stays usable after a panic.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Renders `frames` frames in blocks and passes each block to `sink`.
+fn render_frames(mixer: &mut Mixer, frames: usize, mut sink: impl FnMut(&[f32])) {
+    let mut buffer = [0.0; BLOCK_FRAMES * CHANNELS as usize];
+    let mut remaining = frames;
+    while remaining > 0 {
+        let block = &mut buffer[..remaining.min(BLOCK_FRAMES) * CHANNELS as usize];
+        mixer.render(block);
+        sink(block);
+        remaining -= block.len() / CHANNELS as usize;
+    }
+}
+
+/// Converts a duration to a number of mixer frames.
+fn frames(duration: Duration) -> usize {
+    (duration.as_secs_f64() * SAMPLE_RATE as f64).round() as usize
+}
+
+/// Endless rodio source that pulls blocks from the shared mixer.
+struct MixerSource {
+    mixer: Arc<Mutex<Mixer>>,
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Iterator for MixerSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.pos == self.buffer.len() {
+            lock(&self.mixer).render(&mut self.buffer);
+            self.pos = 0;
+        }
+        let sample = self.buffer[self.pos];
+        self.pos += 1;
+        Some(sample)
+    }
+}
+
+impl Source for MixerSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        CHANNELS
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the offline output records exactly what the mixer played.
+    #[test]
+    fn test_offline_recording() {
+        let audio = Audio::offline();
+        let sound = Sound::tone(441.0, Duration::from_millis(10), 0.5);
+        let handle = audio.play(&sound, Bus::Sfx);
+
+        audio.advance(Duration::from_millis(5));
+        assert!(audio.is_playing(handle));
+        audio.advance(Duration::from_millis(15));
+        assert!(!audio.is_playing(handle));
+
+        let recording = audio.recording();
+        let rendered = frames(Duration::from_millis(5)) + frames(Duration::from_millis(15));
+        assert_eq!(recording.len(), rendered * 2);
+        assert_eq!(recording[2 * 25], sound.frame(25).0);
+        assert!(recording[2 * 441..].iter().all(|&sample| sample == 0.0));
+        assert!(!audio.has_device());
+    }
+
+    /// Verify that a muted bus leaves the recording silent.
+    #[test]
+    fn test_offline_muted_bus_is_silent() {
+        let audio = Audio::offline();
+        audio.set_bus_muted(Bus::Music, true);
+        audio.play_looping(
+            &Sound::tone(220.0, Duration::from_millis(10), 1.0),
+            Bus::Music,
+        );
+
+        audio.advance(Duration::from_millis(30));
+        assert!(audio.recording().iter().all(|&sample| sample == 0.0));
+    }
+
+    /// Verify that the recording written as WAV decodes to the same sound.
+    #[test]
+    fn test_write_wav_round_trip() {
+        let audio = Audio::offline();
+        audio.play(&Sound::tone(441.0, Duration::from_millis(20), 0.5), Bus::Ui);
+        audio.advance(Duration::from_millis(20));
+
+        let path =
+            std::env::temp_dir().join(format!("game_engine_audio_{}.wav", std::process::id()));
+        audio.write_wav(&path).unwrap();
+        let sound = Sound::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sound.frames(), 882);
+        let recording = audio.recording();
+        for frame in [10, 100, 500] {
+            assert!((sound.frame(frame).0 - recording[2 * frame]).abs() < 0.001);
+        }
+    }
+
+    /// Verify that sounds on the null output still end in real time.
+    #[test]
+    fn test_null_output_follows_clock() {
+        let audio = Audio::null();
+        let handle = audio.play(
+            &Sound::tone(440.0, Duration::from_millis(10), 0.2),
+            Bus::Sfx,
+        );
+        assert!(audio.is_playing(handle));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!audio.is_playing(handle));
+        assert!(audio.recording().is_empty());
+    }
+}
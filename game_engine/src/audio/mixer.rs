@@ -0,0 +1,552 @@
+//! Software mixer behind [`super::Audio`].
+//!
+//! Every playing sound or music track is a voice with its own volume, optional fade
+//! and optional world position. Voices are panned and attenuated relative to the [`Listener`] and
+//! summed per bus gain into one interleaved stereo stream at [`super::SAMPLE_RATE`].
+//! The mixer knows nothing about the output device, so it is tested directly.
+
+use super::music::{self, MusicStream, Quantize};
+use super::sound::Sound;
+use super::spatial::Listener;
+use crate::sprite::Pos;
+
+/// Mixer bus a sound is played on. Each bus has its own volume and mute switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bus {
+    /// Game sound effects.
+    Sfx,
+    /// Background music.
+    Music,
+    /// Menu and interface sounds.
+    Ui,
+}
+
+impl Bus {
+    /// All buses, in index order.
+    pub const ALL: [Bus; 3] = [Bus::Sfx, Bus::Music, Bus::Ui];
+
+    fn index(self) -> usize {
+        match self {
+            Bus::Sfx => 0,
+            Bus::Music => 1,
+            Bus::Ui => 2,
+        }
+    }
+}
+
+/// Identifies a playing sound. Stays valid (but inert) after the sound ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SoundHandle(u64);
+
+#[derive(Debug, Clone, Copy)]
+struct BusState {
+    volume: f32,
+    muted: bool,
+}
+
+impl BusState {
+    fn gain(&self) -> f32 {
+        if self.muted { 0.0 } else { self.volume }
+    }
+}
+
+/// Linear volume ramp of a voice.
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    from: f32,
+    to: f32,
+    frames: usize,
+    elapsed: usize,
+    /// Stop the voice once the ramp is done (fade out).
+    stop_at_end: bool,
+}
+
+/// Where the samples of a voice come from.
+enum VoiceSource {
+    /// A decoded sound; `frame` is the next frame to play.
+    Buffer {
+        sound: Sound,
+        frame: usize,
+        looping: bool,
+    },
+    /// A music track decoded while it plays.
+    Stream(Box<MusicStream>),
+}
+
+impl VoiceSource {
+    fn next_frame(&mut self) -> Option<(f32, f32)> {
+        match self {
+            VoiceSource::Buffer {
+                sound,
+                frame,
+                looping,
+            } => {
+                if *frame >= sound.frames() {
+                    if !*looping || sound.frames() == 0 {
+                        return None;
+                    }
+                    *frame = 0;
+                }
+                *frame += 1;
+                Some(sound.frame(*frame - 1))
+            }
+            VoiceSource::Stream(stream) => stream.next_frame(),
+        }
+    }
+
+    fn is_looping(&self) -> bool {
+        match self {
+            VoiceSource::Buffer { looping, .. } => *looping,
+            VoiceSource::Stream(_) => true,
+        }
+    }
+}
+
+struct Voice {
+    handle: SoundHandle,
+    source: VoiceSource,
+    bus: Bus,
+    /// Frames of silence before the voice starts, for quantized stingers.
+    delay: usize,
+    /// Volume of the last frame played, which a running fade updates every frame.
+    volume: f32,
+    fade: Option<Fade>,
+    /// World position for pan and attenuation; `None` plays centered.
+    position: Option<Pos>,
+    finished: bool,
+}
+
+impl Voice {
+    /// Returns the volume for the current frame and advances the fade by one frame.
+    fn next_volume(&mut self) -> f32 {
+        let Some(fade) = &mut self.fade else {
+            return self.volume;
+        };
+        if fade.elapsed >= fade.frames {
+            self.volume = fade.to;
+            self.finished |= fade.stop_at_end;
+            self.fade = None;
+            return self.volume;
+        }
+        self.volume = fade.from + (fade.to - fade.from) * fade.elapsed as f32 / fade.frames as f32;
+        fade.elapsed += 1;
+        self.volume
+    }
+}
+
+/// Multi-voice mixer with one gain stage per [`Bus`].
+pub(crate) struct Mixer {
+    buses: [BusState; 3],
+    voices: Vec<Voice>,
+    max_voices: usize,
+    next_id: u64,
+    listener: Listener,
+    /// The current music track, see [`Mixer::play_music`].
+    music: Option<SoundHandle>,
+}
+
+impl Mixer {
+    /// Creates a mixer that plays at most `max_voices` sounds at once.
+    pub fn new(max_voices: usize) -> Self {
+        Self {
+            buses: [BusState {
+                volume: 1.0,
+                muted: false,
+            }; 3],
+            voices: Vec::new(),
+            max_voices: max_voices.max(1),
+            next_id: 0,
+            listener: Listener::default(),
+            music: None,
+        }
+    }
+
+    /// Starts a new voice for `sound` on `bus`, optionally at a world position.
+    ///
+    /// When all voices are busy the oldest one-shot voice is replaced, or the oldest
+    /// looping voice if only loops are playing.
+    pub fn play(
+        &mut self,
+        sound: &Sound,
+        bus: Bus,
+        looping: bool,
+        position: Option<Pos>,
+    ) -> SoundHandle {
+        let source = VoiceSource::Buffer {
+            sound: sound.clone(),
+            frame: 0,
+            looping,
+        };
+        self.start(source, bus, position, 0)
+    }
+
+    fn start(
+        &mut self,
+        source: VoiceSource,
+        bus: Bus,
+        position: Option<Pos>,
+        delay: usize,
+    ) -> SoundHandle {
+        if self.voices.len() >= self.max_voices {
+            let victim = self
+                .voices
+                .iter()
+                .position(|voice| !voice.source.is_looping())
+                .unwrap_or(0);
+            self.voices.remove(victim);
+        }
+
+        let handle = SoundHandle(self.next_id);
+        self.next_id += 1;
+        self.voices.push(Voice {
+            handle,
+            source,
+            bus,
+            delay,
+            volume: 1.0,
+            fade: None,
+            position,
+            finished: false,
+        });
+        handle
+    }
+
+    /// Starts a music track on [`Bus::Music`], crossfading from the current one.
+    ///
+    /// With `fade_frames` of 0 the current track stops and the new one starts at
+    /// full volume.
+    pub fn play_music(&mut self, stream: MusicStream, fade_frames: usize) -> SoundHandle {
+        self.stop_music(fade_frames);
+        let handle = self.start(VoiceSource::Stream(Box::new(stream)), Bus::Music, None, 0);
+        if fade_frames > 0 {
+            self.set_volume(handle, 0.0);
+            self.fade(handle, 1.0, fade_frames, false);
+        }
+        self.music = Some(handle);
+        handle
+    }
+
+    /// Fades out the current music track over `fade_frames` frames, or stops it
+    /// right away for 0.
+    pub fn stop_music(&mut self, fade_frames: usize) {
+        if let Some(current) = self.music.take() {
+            if fade_frames > 0 {
+                self.fade(current, 0.0, fade_frames, true);
+            } else {
+                self.stop(current);
+            }
+        }
+    }
+
+    /// Plays `sound` on [`Bus::Music`] at the next beat or bar of the current track.
+    ///
+    /// Starts right away if no music with a tempo is playing.
+    pub fn play_stinger(&mut self, sound: &Sound, quantize: Quantize) -> SoundHandle {
+        let delay = self
+            .music
+            .and_then(|handle| self.voices.iter().find(|voice| voice.handle == handle))
+            .and_then(|voice| match &voice.source {
+                VoiceSource::Stream(stream) => stream
+                    .tempo()
+                    .map(|tempo| music::frames_until(stream.position(), tempo, quantize)),
+                VoiceSource::Buffer { .. } => None,
+            })
+            .unwrap_or(0);
+        let source = VoiceSource::Buffer {
+            sound: sound.clone(),
+            frame: 0,
+            looping: false,
+        };
+        self.start(source, Bus::Music, None, delay)
+    }
+
+    fn voice_mut(&mut self, handle: SoundHandle) -> Option<&mut Voice> {
+        self.voices.iter_mut().find(|voice| voice.handle == handle)
+    }
+
+    /// Stops a voice immediately. Unknown handles are ignored.
+    pub fn stop(&mut self, handle: SoundHandle) {
+        self.voices.retain(|voice| voice.handle != handle);
+        if self.music == Some(handle) {
+            self.music = None;
+        }
+    }
+
+    /// Sets the volume of a voice, cancelling a running fade.
+    pub fn set_volume(&mut self, handle: SoundHandle, volume: f32) {
+        if let Some(voice) = self.voice_mut(handle) {
+            voice.volume = volume.max(0.0);
+            voice.fade = None;
+        }
+    }
+
+    /// Ramps the volume of a voice to `volume` over `frames` frames.
+    ///
+    /// The ramp starts at the current volume, so a fade that interrupts a running one
+    /// continues from where that one got to. With `stop_at_end` the voice is stopped
+    /// when the ramp is done.
+    pub fn fade(&mut self, handle: SoundHandle, volume: f32, frames: usize, stop_at_end: bool) {
+        if let Some(voice) = self.voice_mut(handle) {
+            voice.fade = Some(Fade {
+                from: voice.volume,
+                to: volume.max(0.0),
+                frames,
+                elapsed: 0,
+                stop_at_end,
+            });
+        }
+    }
+
+    /// Moves a positional voice, or makes a centered voice positional.
+    pub fn set_position(&mut self, handle: SoundHandle, position: Pos) {
+        if let Some(voice) = self.voice_mut(handle) {
+            voice.position = Some(position);
+        }
+    }
+
+    pub fn set_listener(&mut self, listener: Listener) {
+        self.listener = listener;
+    }
+
+    pub fn listener(&self) -> Listener {
+        self.listener
+    }
+
+    /// Returns `true` while the voice is playing.
+    pub fn is_playing(&self, handle: SoundHandle) -> bool {
+        self.voices.iter().any(|voice| voice.handle == handle)
+    }
+
+    /// Returns the number of voices currently playing.
+    pub fn active_voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.buses[bus.index()].volume = volume.max(0.0);
+    }
+
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.buses[bus.index()].volume
+    }
+
+    pub fn set_bus_muted(&mut self, bus: Bus, muted: bool) {
+        self.buses[bus.index()].muted = muted;
+    }
+
+    pub fn is_bus_muted(&self, bus: Bus) -> bool {
+        self.buses[bus.index()].muted
+    }
+
+    /// Mixes the next `out.len() / 2` stereo frames into `out` (interleaved L/R).
+    ///
+    /// Finished voices are removed afterwards.
+    pub fn render(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+
+        for voice in &mut self.voices {
+            let bus_gain = self.buses[voice.bus.index()].gain();
+            let (left_gain, right_gain) = voice
+                .position
+                .map_or((1.0, 1.0), |position| self.listener.gains(position));
+
+            for frame in out.chunks_exact_mut(2) {
+                if voice.delay > 0 {
+                    voice.delay -= 1;
+                    continue;
+                }
+                let gain = bus_gain * voice.next_volume();
+                if voice.finished {
+                    break;
+                }
+                let Some((left, right)) = voice.source.next_frame() else {
+                    voice.finished = true;
+                    break;
+                };
+                frame[0] += left * gain * left_gain;
+                frame[1] += right * gain * right_gain;
+            }
+        }
+
+        self.voices.retain(|voice| !voice.finished);
+        if self.music.is_some_and(|handle| !self.is_playing(handle)) {
+            self.music = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{Music, Tempo};
+
+    /// A stereo sound with `frames` frames of constant value.
+    fn constant(value: f32, frames: usize) -> Sound {
+        Sound::from_stereo_frames(vec![value; frames * 2])
+    }
+
+    /// Verify that simultaneous voices are summed and one-shots end on their own.
+    #[test]
+    fn test_voices_are_mixed() {
+        let mut mixer = Mixer::new(8);
+        let long = mixer.play(&constant(0.25, 4), Bus::Sfx, false, None);
+        let short = mixer.play(&constant(0.5, 2), Bus::Ui, false, None);
+
+        let mut out = [0.0; 8];
+        mixer.render(&mut out);
+        assert_eq!(out, [0.75, 0.75, 0.75, 0.75, 0.25, 0.25, 0.25, 0.25]);
+        assert!(!mixer.is_playing(short));
+
+        mixer.render(&mut out);
+        assert_eq!(out, [0.0; 8]);
+        assert!(!mixer.is_playing(long));
+    }
+
+    /// Verify that bus volume and mute only affect voices on that bus.
+    #[test]
+    fn test_bus_volume_and_mute() {
+        let mut mixer = Mixer::new(8);
+        mixer.play(&constant(1.0, 4), Bus::Sfx, true, None);
+        mixer.play(&constant(1.0, 4), Bus::Music, true, None);
+
+        mixer.set_bus_volume(Bus::Music, 0.5);
+        let mut out = [0.0; 2];
+        mixer.render(&mut out);
+        assert_eq!(out, [1.5, 1.5]);
+
+        mixer.set_bus_muted(Bus::Sfx, true);
+        mixer.render(&mut out);
+        assert_eq!(out, [0.5, 0.5]);
+        assert!(mixer.is_bus_muted(Bus::Sfx));
+        assert_eq!(mixer.bus_volume(Bus::Music), 0.5);
+    }
+
+    /// Verify that looping voices restart until they are stopped.
+    #[test]
+    fn test_looping_and_stop() {
+        let mut mixer = Mixer::new(8);
+        let sound = Sound::from_stereo_frames(vec![0.1, 0.1, 0.2, 0.2]);
+        let handle = mixer.play(&sound, Bus::Music, true, None);
+
+        let mut out = [0.0; 10];
+        mixer.render(&mut out);
+        assert_eq!(out, [0.1, 0.1, 0.2, 0.2, 0.1, 0.1, 0.2, 0.2, 0.1, 0.1]);
+        assert!(mixer.is_playing(handle));
+
+        mixer.stop(handle);
+        assert!(!mixer.is_playing(handle));
+    }
+
+    /// Verify that a fade out ramps the volume down and then stops the voice.
+    #[test]
+    fn test_fade_out_stops_voice() {
+        let mut mixer = Mixer::new(8);
+        let handle = mixer.play(&constant(1.0, 100), Bus::Sfx, false, None);
+        mixer.fade(handle, 0.0, 4, true);
+
+        let mut out = [0.0; 12];
+        mixer.render(&mut out);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert_eq!(left, [1.0, 0.75, 0.5, 0.25, 0.0, 0.0]);
+        assert!(!mixer.is_playing(handle));
+    }
+
+    /// Verify that a fade interrupting another one starts from the volume reached.
+    #[test]
+    fn test_fade_twice() {
+        let mut mixer = Mixer::new(8);
+        let handle = mixer.play(&constant(1.0, 100), Bus::Sfx, false, None);
+        mixer.fade(handle, 0.0, 4, false);
+
+        let mut out = [0.0; 6];
+        mixer.render(&mut out);
+        mixer.fade(handle, 1.0, 2, false);
+        mixer.render(&mut out);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert_eq!(left, [0.5, 0.75, 1.0]);
+    }
+
+    /// Verify that the oldest one-shot is replaced when all voices are busy.
+    #[test]
+    fn test_voice_stealing_keeps_loops() {
+        let mut mixer = Mixer::new(2);
+        let music = mixer.play(&constant(1.0, 4), Bus::Music, true, None);
+        let first = mixer.play(&constant(1.0, 4), Bus::Sfx, false, None);
+        let second = mixer.play(&constant(1.0, 4), Bus::Sfx, false, None);
+
+        assert_eq!(mixer.active_voices(), 2);
+        assert!(mixer.is_playing(music));
+        assert!(!mixer.is_playing(first));
+        assert!(mixer.is_playing(second));
+    }
+
+    /// Verify that a new music track fades in while the old one fades out and stops.
+    #[test]
+    fn test_music_crossfade() {
+        let first_path = music::tests::ramp_wav("crossfade_a", 100);
+        let second_path = music::tests::ramp_wav("crossfade_b", 100);
+        let mut mixer = Mixer::new(8);
+        let first = mixer.play_music(MusicStream::open(&Music::new(&first_path)).unwrap(), 0);
+
+        let second = mixer.play_music(MusicStream::open(&Music::new(&second_path)).unwrap(), 4);
+        assert_eq!(mixer.active_voices(), 2);
+
+        let mut out = [0.0; 12];
+        mixer.render(&mut out);
+        assert!(!mixer.is_playing(first));
+        assert!(mixer.is_playing(second));
+        assert_eq!(mixer.active_voices(), 1);
+
+        mixer.stop_music(0);
+        assert!(!mixer.is_playing(second));
+        std::fs::remove_file(first_path).unwrap();
+        std::fs::remove_file(second_path).unwrap();
+    }
+
+    /// Verify that stingers wait for the next beat of the music.
+    #[test]
+    fn test_stinger_on_beat() {
+        let path = music::tests::ramp_wav("stinger", 1000);
+        let mut mixer = Mixer::new(8);
+        mixer.set_bus_muted(Bus::Sfx, true);
+        // 10 frames per beat
+        let music = Music {
+            tempo: Some(Tempo {
+                bpm: 60.0 * crate::audio::SAMPLE_RATE as f32 / 10.0,
+                beats_per_bar: 4,
+            }),
+            ..Music::new(&path)
+        };
+        let track = mixer.play_music(MusicStream::open(&music).unwrap(), 0);
+        let mut out = [0.0; 6];
+        mixer.render(&mut out);
+        mixer.set_volume(track, 0.0);
+
+        mixer.play_stinger(&constant(1.0, 2), Quantize::Beat);
+        let mut out = [0.0; 20];
+        mixer.render(&mut out);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert_eq!(left, [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Verify that positional voices are panned relative to the listener.
+    #[test]
+    fn test_positional_voice() {
+        let mut mixer = Mixer::new(8);
+        mixer.set_listener(Listener::centered(crate::sprite::Size {
+            width: 200.0,
+            height: 100.0,
+        }));
+        let right = Pos { x: 200.0, y: 50.0 };
+        let handle = mixer.play(&constant(1.0, 4), Bus::Sfx, false, Some(right));
+
+        let mut out = [0.0; 2];
+        mixer.render(&mut out);
+        assert_eq!(out, [0.0, 1.0]);
+
+        mixer.set_position(handle, Pos { x: 150.0, y: 50.0 });
+        mixer.render(&mut out);
+        assert_eq!(out, [0.5, 1.0]);
+    }
+}
@@ -0,0 +1,247 @@
+//! Streamed background music.
+//!
+//! Music files are decoded while they play instead of being loaded into memory
+//! like a [`super::Sound`]. A track has an intro that plays once and a loop region
+//! that repeats, and an optional tempo that stingers are synchronized to.
+
+use super::{AudioError, CHANNELS, SAMPLE_RATE};
+use rodio::Source;
+use rodio::source::UniformSourceIterator;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Tempo of a music track, used to start stingers on a beat or bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo {
+    pub bpm: f32,
+    pub beats_per_bar: u32,
+}
+
+/// Where a stinger starts relative to the playing music.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantize {
+    /// Right away.
+    Immediate,
+    /// On the next beat of the music.
+    Beat,
+    /// On the next bar of the music.
+    Bar,
+}
+
+/// A music track: a WAV or OGG/Vorbis file with an optional loop region.
+///
+/// Everything before `loop_start` is the intro and plays once; the region from
+/// `loop_start` to `loop_end` (or the end of the file) repeats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Music {
+    pub path: PathBuf,
+    pub loop_start: Duration,
+    /// End of the loop region; `None` is the end of the file.
+    pub loop_end: Option<Duration>,
+    /// Play the track once instead of looping.
+    pub looping: bool,
+    pub tempo: Option<Tempo>,
+}
+
+impl Music {
+    /// Creates a track that loops the whole file and has no tempo.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            loop_start: Duration::ZERO,
+            loop_end: None,
+            looping: true,
+            tempo: None,
+        }
+    }
+}
+
+type Decoded = UniformSourceIterator<rodio::Decoder<BufReader<File>>>;
+
+/// Decoder state of a playing track.
+pub(crate) struct MusicStream {
+    music: Music,
+    decoder: Decoded,
+    /// Position in the track in frames.
+    frame: usize,
+    loop_start: usize,
+    loop_end: Option<usize>,
+}
+
+impl MusicStream {
+    /// Opens the file of `music` for streaming.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Io)` if the file cannot be opened.
+    /// * `Err(AudioError::Decode)` if its format is not supported.
+    pub fn open(music: &Music) -> Result<Self, AudioError> {
+        Ok(Self {
+            decoder: open_decoder(music)?,
+            frame: 0,
+            loop_start: super::frames(music.loop_start),
+            loop_end: music.loop_end.map(super::frames),
+            music: music.clone(),
+        })
+    }
+
+    pub fn tempo(&self) -> Option<Tempo> {
+        self.music.tempo
+    }
+
+    /// Returns the current position in the track in frames.
+    pub fn position(&self) -> usize {
+        self.frame
+    }
+
+    /// Returns the next stereo frame, or `None` when the track is over.
+    pub fn next_frame(&mut self) -> Option<(f32, f32)> {
+        if self.music.looping && self.loop_end.is_some_and(|end| self.frame >= end) {
+            self.jump_to_loop()?;
+        }
+        match self.read_frame() {
+            Some(frame) => Some(frame),
+            // End of file: loop unless nothing was played since the last jump.
+            None if self.music.looping && self.frame > self.loop_start => {
+                self.jump_to_loop()?;
+                self.read_frame()
+            }
+            None => None,
+        }
+    }
+
+    fn read_frame(&mut self) -> Option<(f32, f32)> {
+        let left = self.decoder.next()?;
+        let right = self.decoder.next()?;
+        self.frame += 1;
+        Some((left, right))
+    }
+
+    /// Continues at the loop start, seeking if the decoder supports it and
+    /// decoding from the beginning otherwise.
+    fn jump_to_loop(&mut self) -> Option<()> {
+        let target = Duration::from_secs_f64(self.loop_start as f64 / SAMPLE_RATE as f64);
+        if self.decoder.try_seek(target).is_err() {
+            self.decoder = open_decoder(&self.music).ok()?;
+            for _ in 0..self.loop_start * CHANNELS as usize {
+                self.decoder.next()?;
+            }
+        }
+        self.frame = self.loop_start;
+        Some(())
+    }
+}
+
+fn open_decoder(music: &Music) -> Result<Decoded, AudioError> {
+    let file = File::open(&music.path)
+        .map_err(|err| AudioError::Io(format!("{}: {err}", music.path.display())))?;
+    let decoder = rodio::Decoder::try_from(file)
+        .map_err(|err| AudioError::Decode(format!("{}: {err}", music.path.display())))?;
+    Ok(UniformSourceIterator::new(decoder, CHANNELS, SAMPLE_RATE))
+}
+
+/// Returns the number of frames from `position` to the next beat or bar of `tempo`.
+pub(crate) fn frames_until(position: usize, tempo: Tempo, quantize: Quantize) -> usize {
+    let beat = 60.0 / tempo.bpm as f64 * SAMPLE_RATE as f64;
+    let unit = match quantize {
+        Quantize::Immediate => return 0,
+        Quantize::Beat => beat,
+        Quantize::Bar => beat * tempo.beats_per_bar.max(1) as f64,
+    };
+    if !unit.is_finite() || unit < 1.0 {
+        return 0;
+    }
+    let next = (position as f64 / unit).ceil() * unit;
+    (next - position as f64).round() as usize
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::audio::wav;
+
+    /// Writes a stereo WAV file whose frame `i` has the value `i / 100` on both channels.
+    pub(crate) fn ramp_wav(name: &str, frames: usize) -> PathBuf {
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|i| [i as f32 / 100.0, i as f32 / 100.0])
+            .collect();
+        let path = std::env::temp_dir().join(format!(
+            "game_engine_music_{name}_{}.wav",
+            std::process::id()
+        ));
+        std::fs::write(&path, wav::encode(&samples, CHANNELS, SAMPLE_RATE)).unwrap();
+        path
+    }
+
+    /// Reads frames from the stream and returns their indices in the ramp file.
+    fn play(stream: &mut MusicStream, frames: usize) -> Vec<usize> {
+        (0..frames)
+            .map_while(|_| stream.next_frame())
+            .map(|(left, _)| (left * 100.0).round() as usize)
+            .collect()
+    }
+
+    fn at_frame(frame: u64) -> Duration {
+        Duration::from_nanos(frame * 1_000_000_000 / SAMPLE_RATE as u64)
+    }
+
+    /// Verify that the intro plays once and the loop region repeats.
+    #[test]
+    fn test_intro_and_loop_region() {
+        let path = ramp_wav("region", 10);
+        let music = Music {
+            loop_start: at_frame(4),
+            loop_end: Some(at_frame(8)),
+            ..Music::new(&path)
+        };
+        let mut stream = MusicStream::open(&music).unwrap();
+
+        assert_eq!(
+            play(&mut stream, 14),
+            [0, 1, 2, 3, 4, 5, 6, 7, 4, 5, 6, 7, 4, 5]
+        );
+        assert_eq!(stream.position(), 6);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Verify that a track without loop end loops the whole file, and `looping: false` ends.
+    #[test]
+    fn test_loop_whole_file_and_once() {
+        let path = ramp_wav("whole", 3);
+
+        let mut stream = MusicStream::open(&Music::new(&path)).unwrap();
+        assert_eq!(play(&mut stream, 7), [0, 1, 2, 0, 1, 2, 0]);
+
+        let once = Music {
+            looping: false,
+            ..Music::new(&path)
+        };
+        let mut stream = MusicStream::open(&once).unwrap();
+        assert_eq!(play(&mut stream, 7), [0, 1, 2]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Verify that missing files are reported when the stream is opened.
+    #[test]
+    fn test_open_missing_file() {
+        let result = MusicStream::open(&Music::new("does/not/exist.ogg"));
+        assert!(matches!(result, Err(AudioError::Io(_))));
+    }
+
+    /// Verify the distance to the next beat and bar.
+    #[test]
+    fn test_frames_until() {
+        // 100 frames per beat
+        let tempo = Tempo {
+            bpm: 60.0 * SAMPLE_RATE as f32 / 100.0,
+            beats_per_bar: 4,
+        };
+
+        assert_eq!(frames_until(30, tempo, Quantize::Beat), 70);
+        assert_eq!(frames_until(200, tempo, Quantize::Beat), 0);
+        assert_eq!(frames_until(30, tempo, Quantize::Bar), 370);
+        assert_eq!(frames_until(30, tempo, Quantize::Immediate), 0);
+    }
+}
@@ -0,0 +1,133 @@
+//! Decoded sounds that can be played any number of times.
+
+use super::{AudioError, CHANNELS, SAMPLE_RATE};
+use rodio::Source;
+use rodio::buffer::SamplesBuffer;
+use rodio::source::UniformSourceIterator;
+use std::f32::consts::TAU;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A fully decoded sound in the mixer format (interleaved stereo at [`SAMPLE_RATE`]).
+///
+/// Cloning is cheap: the samples are shared.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    samples: Arc<[f32]>,
+}
+
+impl Sound {
+    /// Loads and decodes a WAV or OGG/Vorbis file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Io)` if the file cannot be read.
+    /// * `Err(AudioError::Decode)` if the format is not supported or the data is broken.
+    pub fn load(path: impl AsRef<Path>) -> Result<Sound, AudioError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|err| AudioError::Io(format!("{}: {err}", path.display())))?;
+        Sound::from_bytes(bytes)
+    }
+
+    /// Decodes an in-memory WAV or OGG/Vorbis file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Decode)` if the format is not supported or the data is broken.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Sound, AudioError> {
+        let decoder = rodio::Decoder::new(Cursor::new(bytes))
+            .map_err(|err| AudioError::Decode(err.to_string()))?;
+        Ok(Sound::from_source(decoder))
+    }
+
+    /// Creates a sound from interleaved samples in any channel count and sample rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is zero.
+    pub fn from_samples(samples: Vec<f32>, channels: u16, sample_rate: u32) -> Sound {
+        Sound::from_source(SamplesBuffer::new(channels, sample_rate, samples))
+    }
+
+    /// Creates a sine tone, e.g. for simple beeps.
+    ///
+    /// # Arguments
+    ///
+    /// * `freq` - Frequency in Hz.
+    /// * `duration` - Length of the tone.
+    /// * `amplitude` - Peak amplitude, 1.0 is full scale.
+    pub fn tone(freq: f32, duration: Duration, amplitude: f32) -> Sound {
+        let frames = (duration.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+        let samples = (0..frames)
+            .map(|i| amplitude * (TAU * freq * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        Sound::from_samples(samples, 1, SAMPLE_RATE)
+    }
+
+    /// Wraps samples that are already interleaved stereo at [`SAMPLE_RATE`].
+    pub(crate) fn from_stereo_frames(samples: Vec<f32>) -> Sound {
+        Sound {
+            samples: samples.into(),
+        }
+    }
+
+    fn from_source<S: Source>(source: S) -> Sound {
+        let samples: Vec<f32> = UniformSourceIterator::new(source, CHANNELS, SAMPLE_RATE).collect();
+        Sound::from_stereo_frames(samples)
+    }
+
+    /// Returns the number of stereo frames.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / CHANNELS as usize
+    }
+
+    /// Returns the playing time of the sound.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / SAMPLE_RATE as f64)
+    }
+
+    /// Returns the left and right sample of frame `index`.
+    pub(crate) fn frame(&self, index: usize) -> (f32, f32) {
+        (self.samples[index * 2], self.samples[index * 2 + 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::wav;
+
+    /// Verify that a mono WAV is decoded and converted to stereo frames.
+    #[test]
+    fn test_decode_wav() {
+        let sound = Sound::from_bytes(wav::encode(&[0.5; 441], 1, SAMPLE_RATE)).unwrap();
+
+        assert_eq!(sound.frames(), 441);
+        assert_eq!(sound.duration(), Duration::from_millis(10));
+        let (left, right) = sound.frame(100);
+        assert!((left - 0.5).abs() < 0.01);
+        assert_eq!(left, right);
+    }
+
+    /// Verify that data in an unknown format is rejected.
+    #[test]
+    fn test_decode_garbage() {
+        let result = Sound::from_bytes(b"definitely not audio".to_vec());
+        assert!(matches!(result, Err(AudioError::Decode(_))));
+    }
+
+    /// Verify that tones have the requested length and amplitude.
+    #[test]
+    fn test_tone() {
+        let sound = Sound::tone(441.0, Duration::from_millis(100), 0.2);
+
+        assert_eq!(sound.frames(), 4410);
+        let peak = (0..sound.frames())
+            .map(|i| sound.frame(i).0.abs())
+            .fold(0.0, f32::max);
+        assert!((peak - 0.2).abs() < 0.001);
+    }
+}
@@ -0,0 +1,121 @@
+//! Stereo pan and distance attenuation for sounds with a world position.
+
+use crate::sprite::{Pos, Size};
+
+/// The point sounds are heard from, usually the screen or camera center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    pub position: Pos,
+    /// Horizontal distance at which a sound is panned fully to one side.
+    pub pan_width: f32,
+    /// Distance up to which sounds play at full volume.
+    pub ref_distance: f32,
+    /// How fast the volume drops beyond `ref_distance`; 0.0 disables attenuation.
+    pub rolloff: f32,
+}
+
+impl Listener {
+    /// Creates a listener in the center of a window of the given size.
+    ///
+    /// Sounds at the left and right border are panned fully to that side, and
+    /// sounds inside the window are not attenuated.
+    pub fn centered(window: Size) -> Self {
+        let half_width = window.width / 2.0;
+        let half_height = window.height / 2.0;
+        Self {
+            position: Pos {
+                x: half_width,
+                y: half_height,
+            },
+            pan_width: half_width,
+            ref_distance: half_width.hypot(half_height),
+            rolloff: 1.0,
+        }
+    }
+
+    /// Returns the pan of a sound at `position`, from -1.0 (left) to 1.0 (right).
+    pub fn pan(&self, position: Pos) -> f32 {
+        if self.pan_width <= 0.0 {
+            return 0.0;
+        }
+        ((position.x - self.position.x) / self.pan_width).clamp(-1.0, 1.0)
+    }
+
+    /// Returns the distance attenuation of a sound at `position`, from 0.0 to 1.0.
+    ///
+    /// Inverse distance law, clamped to full volume inside `ref_distance`.
+    pub fn attenuation(&self, position: Pos) -> f32 {
+        let distance = (position.x - self.position.x).hypot(position.y - self.position.y);
+        if distance <= self.ref_distance || self.ref_distance <= 0.0 {
+            return 1.0;
+        }
+        self.ref_distance
+            / (self.ref_distance + self.rolloff.max(0.0) * (distance - self.ref_distance))
+    }
+
+    /// Returns the left and right channel gains of a sound at `position`.
+    ///
+    /// Balance law: a centered sound keeps both channels at full level, a sound
+    /// panned to one side silences the other channel.
+    pub fn gains(&self, position: Pos) -> (f32, f32) {
+        let pan = self.pan(position);
+        let attenuation = self.attenuation(position);
+        (
+            (1.0 - pan).min(1.0) * attenuation,
+            (1.0 + pan).min(1.0) * attenuation,
+        )
+    }
+}
+
+impl Default for Listener {
+    /// A listener in the center of the default 1024x768 window.
+    fn default() -> Self {
+        Listener::centered(Size {
+            width: 1024.0,
+            height: 768.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener() -> Listener {
+        Listener::centered(Size {
+            width: 200.0,
+            height: 100.0,
+        })
+    }
+
+    /// Verify that sounds are panned by their horizontal offset from the listener.
+    #[test]
+    fn test_pan() {
+        let listener = listener();
+
+        assert_eq!(listener.gains(Pos { x: 100.0, y: 50.0 }), (1.0, 1.0));
+        assert_eq!(listener.gains(Pos { x: 200.0, y: 50.0 }), (0.0, 1.0));
+        assert_eq!(listener.gains(Pos { x: 0.0, y: 0.0 }), (1.0, 0.0));
+        assert_eq!(listener.gains(Pos { x: 150.0, y: 50.0 }), (0.5, 1.0));
+        assert_eq!(listener.pan(Pos { x: 1000.0, y: 50.0 }), 1.0);
+    }
+
+    /// Verify that sounds outside the reference distance get quieter.
+    #[test]
+    fn test_attenuation() {
+        let listener = listener();
+        let far = Pos {
+            x: 100.0 + 2.0 * listener.ref_distance,
+            y: 50.0,
+        };
+
+        assert_eq!(listener.attenuation(Pos { x: 0.0, y: 0.0 }), 1.0);
+        assert!((listener.attenuation(far) - 0.5).abs() < 1e-6);
+
+        let flat = Listener {
+            rolloff: 0.0,
+            ..listener
+        };
+        assert_eq!(flat.attenuation(far), 1.0);
+    }
+}
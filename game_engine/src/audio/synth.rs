@@ -0,0 +1,359 @@
+//! Procedural sound effects in the style of sfxr.
+//!
+//! An [`SfxPreset`] describes one effect: an oscillator, an ADSR envelope, an
+//! optional pitch slide and an optional arpeggio. With the `serde` feature presets
+//! are loaded from JSON files, so effects are designed as data:
+//!
+//! ```json
+//! { "waveform": "square", "frequency": 180.0, "slide_to": 360.0,
+//!   "envelope": { "attack": 0.0, "decay": 0.05, "sustain": 0.4, "hold": 0.03, "release": 0.06 } }
+//! ```
+//!
+//! Missing fields take their [`Default`] values.
+
+#[cfg(feature = "serde")]
+use super::AudioError;
+use super::{SAMPLE_RATE, Sound};
+use std::f32::consts::TAU;
+
+/// Oscillator shape of a synth voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Waveform {
+    /// Pulse wave with a configurable duty cycle.
+    Square,
+    Triangle,
+    Sawtooth,
+    Sine,
+    /// White noise, resampled once per oscillator period, so the pitch still matters.
+    Noise,
+}
+
+/// Attack, decay, sustain and release of the volume, times in seconds.
+///
+/// The sustain level is held for `hold` seconds before the release starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    /// Level after the decay, 0.0 to 1.0.
+    pub sustain: f32,
+    pub hold: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Returns the total length in seconds.
+    pub fn duration(&self) -> f32 {
+        self.attack.max(0.0) + self.decay.max(0.0) + self.hold.max(0.0) + self.release.max(0.0)
+    }
+
+    /// Returns the envelope level at `t` seconds after the start.
+    pub fn level(&self, t: f32) -> f32 {
+        let (attack, decay) = (self.attack.max(0.0), self.decay.max(0.0));
+        let (hold, release) = (self.hold.max(0.0), self.release.max(0.0));
+        let sustain = self.sustain.clamp(0.0, 1.0);
+
+        if t < attack {
+            t / attack
+        } else if t < attack + decay {
+            1.0 - (1.0 - sustain) * (t - attack) / decay
+        } else if t < attack + decay + hold {
+            sustain
+        } else if t < attack + decay + hold + release {
+            sustain * (1.0 - (t - attack - decay - hold) / release)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: 0.005,
+            decay: 0.05,
+            sustain: 0.5,
+            hold: 0.05,
+            release: 0.05,
+        }
+    }
+}
+
+/// Description of a synthesized sound effect.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SfxPreset {
+    pub waveform: Waveform,
+    /// Start frequency in Hz.
+    pub frequency: f32,
+    /// Frequency in Hz reached at the end of the sound (exponential slide).
+    pub slide_to: Option<f32>,
+    /// Semitone offsets that are cycled through, e.g. `[0, 4, 7]` for a major chord.
+    pub arpeggio: Vec<f32>,
+    /// Arpeggio steps per second.
+    pub arpeggio_rate: f32,
+    /// High part of the square wave period, 0.0 to 1.0.
+    pub duty: f32,
+    /// Peak amplitude, 1.0 is full scale.
+    pub volume: f32,
+    pub envelope: Envelope,
+    /// Seed of the noise generator; the same preset always renders the same sound.
+    pub seed: u32,
+}
+
+impl Default for SfxPreset {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Square,
+            frequency: 440.0,
+            slide_to: None,
+            arpeggio: Vec::new(),
+            arpeggio_rate: 0.0,
+            duty: 0.5,
+            volume: 0.3,
+            envelope: Envelope::default(),
+            seed: 1,
+        }
+    }
+}
+
+impl SfxPreset {
+    /// Parses a preset from JSON. Missing fields take their default values.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Preset)` if the JSON does not describe a preset.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<SfxPreset, AudioError> {
+        serde_json::from_str(json).map_err(|err| AudioError::Preset(err.to_string()))
+    }
+
+    /// Loads a preset from a JSON file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(AudioError::Io)` if the file cannot be read.
+    /// * `Err(AudioError::Preset)` if the JSON does not describe a preset.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<SfxPreset, AudioError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| AudioError::Io(format!("{}: {err}", path.display())))?;
+        SfxPreset::from_json(&json)
+            .map_err(|err| AudioError::Preset(format!("{}: {err}", path.display())))
+    }
+
+    /// Returns the oscillator frequency at `t` seconds, with slide and arpeggio applied.
+    fn frequency_at(&self, t: f32, duration: f32) -> f32 {
+        let mut freq = match self.slide_to {
+            Some(end) if duration > 0.0 && end > 0.0 => {
+                self.frequency * (end / self.frequency).powf(t / duration)
+            }
+            _ => self.frequency,
+        };
+        if !self.arpeggio.is_empty() && self.arpeggio_rate > 0.0 {
+            let step = (t * self.arpeggio_rate) as usize % self.arpeggio.len();
+            freq *= 2f32.powf(self.arpeggio[step] / 12.0);
+        }
+        freq
+    }
+
+    /// Renders the effect to mono samples at [`SAMPLE_RATE`].
+    pub fn render_samples(&self) -> Vec<f32> {
+        let duration = self.envelope.duration();
+        let frames = (duration * SAMPLE_RATE as f32) as usize;
+        let duty = self.duty.clamp(0.0, 1.0);
+        let mut noise = Noise(self.seed.max(1));
+        let mut noise_value = noise.next();
+        let mut phase = 0.0f32;
+
+        (0..frames)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let value = match self.waveform {
+                    Waveform::Square => {
+                        if phase < duty {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                    Waveform::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+                    Waveform::Sawtooth => 2.0 * phase - 1.0,
+                    Waveform::Sine => (TAU * phase).sin(),
+                    Waveform::Noise => noise_value,
+                };
+
+                phase += self.frequency_at(t, duration).max(0.0) / SAMPLE_RATE as f32;
+                if phase >= 1.0 {
+                    phase = phase.fract();
+                    noise_value = noise.next();
+                }
+                value * self.volume * self.envelope.level(t)
+            })
+            .collect()
+    }
+
+    /// Renders the effect to a [`Sound`] for the mixer.
+    pub fn render(&self) -> Sound {
+        Sound::from_samples(self.render_samples(), 1, SAMPLE_RATE)
+    }
+}
+
+/// Xorshift noise source, deterministic per seed.
+struct Noise(u32);
+
+impl Noise {
+    /// Returns the next value in -1.0..1.0.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts sign changes from negative to positive, i.e. oscillator periods.
+    fn periods(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .count()
+    }
+
+    fn flat(waveform: Waveform, frequency: f32) -> SfxPreset {
+        SfxPreset {
+            waveform,
+            frequency,
+            volume: 1.0,
+            envelope: Envelope {
+                attack: 0.0,
+                decay: 0.0,
+                sustain: 1.0,
+                hold: 0.1,
+                release: 0.0,
+            },
+            ..SfxPreset::default()
+        }
+    }
+
+    /// Verify the envelope shape at its corner points.
+    #[test]
+    fn test_envelope_levels() {
+        let envelope = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            hold: 0.2,
+            release: 0.1,
+        };
+
+        assert!((envelope.duration() - 0.5).abs() < 1e-6);
+        assert!((envelope.level(0.05) - 0.5).abs() < 1e-5);
+        assert!((envelope.level(0.15) - 0.75).abs() < 1e-5);
+        assert_eq!(envelope.level(0.3), 0.5);
+        assert!((envelope.level(0.45) - 0.25).abs() < 1e-5);
+        assert_eq!(envelope.level(0.6), 0.0);
+    }
+
+    /// Verify that the oscillators produce the requested pitch and range.
+    #[test]
+    fn test_waveforms() {
+        for waveform in [
+            Waveform::Square,
+            Waveform::Triangle,
+            Waveform::Sawtooth,
+            Waveform::Sine,
+        ] {
+            let samples = flat(waveform, 100.0).render_samples();
+            assert_eq!(samples.len(), 4410);
+            assert!(
+                (9..=10).contains(&periods(&samples)),
+                "{waveform:?}: {} periods",
+                periods(&samples)
+            );
+            assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+        }
+
+        let square = flat(Waveform::Square, 100.0).render_samples();
+        assert!(square.iter().all(|sample| sample.abs() == 1.0));
+    }
+
+    /// Verify that noise is deterministic per seed and changes with the seed.
+    #[test]
+    fn test_noise_is_seeded() {
+        let preset = flat(Waveform::Noise, 2000.0);
+        assert_eq!(preset.render_samples(), preset.render_samples());
+
+        let other = SfxPreset {
+            seed: 7,
+            ..preset.clone()
+        };
+        assert_ne!(preset.render_samples(), other.render_samples());
+    }
+
+    /// Verify that a slide ends near its target frequency.
+    #[test]
+    fn test_slide() {
+        let preset = SfxPreset {
+            slide_to: Some(400.0),
+            ..flat(Waveform::Square, 100.0)
+        };
+        let samples = preset.render_samples();
+        let (start, end) = samples.split_at(samples.len() / 2);
+
+        assert!(2 * periods(end) > 3 * periods(start));
+        assert!((preset.frequency_at(0.1, 0.1) - 400.0).abs() < 0.01);
+    }
+
+    /// Verify that arpeggio steps shift the pitch by semitones.
+    #[test]
+    fn test_arpeggio() {
+        let preset = SfxPreset {
+            arpeggio: vec![0.0, 12.0],
+            arpeggio_rate: 20.0,
+            ..flat(Waveform::Square, 100.0)
+        };
+
+        assert_eq!(preset.frequency_at(0.01, 0.1), 100.0);
+        assert!((preset.frequency_at(0.06, 0.1) - 200.0).abs() < 0.01);
+        assert_eq!(preset.frequency_at(0.11, 0.1), 100.0);
+    }
+
+    /// Verify that presets load from partial JSON with defaults for missing fields.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json() {
+        let preset = SfxPreset::from_json(
+            r#"{ "waveform": "triangle", "frequency": 220.0, "envelope": { "hold": 0.2 } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(preset.waveform, Waveform::Triangle);
+        assert_eq!(preset.frequency, 220.0);
+        assert_eq!(preset.envelope.hold, 0.2);
+        assert_eq!(preset.envelope.sustain, Envelope::default().sustain);
+        assert_eq!(preset.volume, SfxPreset::default().volume);
+
+        let result = SfxPreset::from_json(r#"{ "waveform": "kazoo" }"#);
+        assert!(matches!(result, Err(AudioError::Preset(_))));
+    }
+}
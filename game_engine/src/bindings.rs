@@ -0,0 +1,9 @@
+//! Raw bindings to `opengl_wrapper_lib.h`.
+//!
+//! The contents are generated by `build.rs` with bindgen, so the `Sprite` struct and
+//! the function signatures always follow the C header. Use the safe wrappers in
+//! [`crate::ffi`] instead of calling these functions directly.
+
+#![allow(non_upper_case_globals, non_camel_case_types, non_snake_case, dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -0,0 +1,379 @@
+//! Broad-phase collision culling.
+//!
+//! Testing every pair of sprites against each other grows with the square of their
+//! number. A [`SpatialHash`] sorts boxes into a uniform grid of square cells, so only
+//! boxes that share a cell become candidate pairs for the exact tests in
+//! [`crate::collision`]. Entries are updated in place when they move, and the hash
+//! answers region and point queries as well.
+//!
+//! Boxes that would cover more than [`MAX_CELLS`] cells, or that have non-finite
+//! corners, stay out of the grid. They are kept in an overflow list and tested
+//! against every other box.
+//!
+//! All results are sorted by key, so code iterating them behaves the same in every run.
+
+use crate::math::{Aabb, Vec2};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound;
+
+/// Cell size used when none is given, in pixels. Roughly the size of a typical sprite.
+pub const DEFAULT_CELL_SIZE: f32 = 64.0;
+
+/// Most cells a box is sorted into; larger boxes go to the overflow list.
+pub const MAX_CELLS: f32 = 1024.0;
+
+/// Inclusive range of grid cells covered by a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellRange {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl CellRange {
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min.0..=self.max.0).flat_map(move |x| (self.min.1..=self.max.1).map(move |y| (x, y)))
+    }
+}
+
+struct Entry {
+    bounds: Aabb,
+    /// `None` for boxes in the overflow list.
+    cells: Option<CellRange>,
+}
+
+/// A uniform grid that maps boxes to the cells they cover.
+///
+/// Keys identify the boxes, e.g. sprite names. Boxes that only touch count as
+/// overlapping, so sprites resting against each other stay candidates.
+pub struct SpatialHash<K> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<K>>,
+    /// Keys of the boxes that are too large for the grid or not finite.
+    overflow: BTreeSet<K>,
+    entries: BTreeMap<K, Entry>,
+}
+
+impl<K: Clone + Ord> SpatialHash<K> {
+    /// Creates an empty hash with square cells of `cell_size` pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` is not positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "cell size must be positive, got {cell_size}"
+        );
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            overflow: BTreeSet::new(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Returns the box stored for `key`.
+    pub fn bounds(&self, key: &K) -> Option<Aabb> {
+        self.entries.get(key).map(|entry| entry.bounds)
+    }
+
+    /// Returns all keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.keys()
+    }
+
+    /// Inserts the box of `key`, or moves it if the key is already present.
+    ///
+    /// Moving only touches the grid when the box enters or leaves a cell, so updating
+    /// every entry each frame is cheap for slow objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the box.
+    /// * `bounds` - The new box.
+    pub fn insert(&mut self, key: K, bounds: Aabb) {
+        let cells = self.cell_range(&bounds);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.bounds = bounds;
+            if entry.cells == cells {
+                return;
+            }
+            let old = std::mem::replace(&mut entry.cells, cells);
+            self.unlink(&key, old);
+        }
+        match cells {
+            Some(range) => {
+                for cell in range.cells() {
+                    self.cells.entry(cell).or_default().push(key.clone());
+                }
+            }
+            None => {
+                self.overflow.insert(key.clone());
+            }
+        }
+        self.entries.insert(key, Entry { bounds, cells });
+    }
+
+    /// Removes `key`, returning its box.
+    pub fn remove(&mut self, key: &K) -> Option<Aabb> {
+        let entry = self.entries.remove(key)?;
+        self.unlink(key, entry.cells);
+        Some(entry.bounds)
+    }
+
+    /// Removes every key for which `keep` returns `false`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        let removed: Vec<K> = self
+            .entries
+            .keys()
+            .filter(|key| !keep(key))
+            .cloned()
+            .collect();
+        for key in &removed {
+            self.remove(key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.overflow.clear();
+        self.entries.clear();
+    }
+
+    /// Returns the keys of all boxes that overlap or touch `area`, in order.
+    pub fn query(&self, area: Aabb) -> Vec<K> {
+        let overlaps = |key: &&K| self.entries[*key].bounds.overlaps(&area);
+        let mut found: BTreeSet<&K> = self.overflow.iter().filter(overlaps).collect();
+        match self.cell_range(&area) {
+            Some(range) => {
+                for cell in range.cells() {
+                    found.extend(self.cells.get(&cell).into_iter().flatten().filter(overlaps));
+                }
+            }
+            // An area too large for the grid is tested against every box.
+            None => found.extend(self.entries.keys().filter(overlaps)),
+        }
+        found.into_iter().cloned().collect()
+    }
+
+    /// Returns the keys of all boxes that contain `point`, in order.
+    pub fn query_point(&self, point: Vec2) -> Vec<K> {
+        let mut found: Vec<K> = self
+            .cells
+            .get(&self.cell_of(point))
+            .into_iter()
+            .flatten()
+            .chain(&self.overflow)
+            .filter(|key| self.entries[*key].bounds.contains(point))
+            .cloned()
+            .collect();
+        found.sort();
+        found
+    }
+
+    /// Returns every pair of keys whose boxes overlap or touch, each once with the
+    /// smaller key first, in order.
+    pub fn pairs(&self) -> Vec<(K, K)> {
+        let mut pairs = Vec::new();
+        let after = |a| (Bound::Excluded(a), Bound::Unbounded);
+        for (a, entry_a) in &self.entries {
+            let overlaps = |b: &K| entry_a.bounds.overlaps(&self.entries[b].bounds);
+            // Overflow boxes pair with every later key, grid boxes with the later
+            // overflow keys and through the grid.
+            let later: Vec<&K> = match entry_a.cells {
+                None => self
+                    .entries
+                    .range::<K, _>(after(a))
+                    .map(|(b, _)| b)
+                    .collect(),
+                Some(_) => self.overflow.range::<K, _>(after(a)).collect(),
+            };
+            for b in later {
+                if overlaps(b) {
+                    pairs.push((a.clone(), b.clone()));
+                }
+            }
+            let Some(cells_a) = entry_a.cells else {
+                continue;
+            };
+            for cell in cells_a.cells() {
+                for b in &self.cells[&cell] {
+                    // Keys in the grid always have cells.
+                    let Some(cells_b) = self.entries[b].cells else {
+                        continue;
+                    };
+                    if b <= a {
+                        continue;
+                    }
+                    // Boxes sharing several cells meet in all of them; only the
+                    // first shared cell reports the pair.
+                    let first = (
+                        cells_a.min.0.max(cells_b.min.0),
+                        cells_a.min.1.max(cells_b.min.1),
+                    );
+                    if cell == first && overlaps(b) {
+                        pairs.push((a.clone(), b.clone()));
+                    }
+                }
+            }
+        }
+        pairs.sort();
+        pairs
+    }
+
+    fn cell_of(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Returns the cells covered by `bounds`, or `None` if they are more than
+    /// [`MAX_CELLS`] or the box is not finite.
+    fn cell_range(&self, bounds: &Aabb) -> Option<CellRange> {
+        let span = |min: f32, max: f32| {
+            (max / self.cell_size).floor() - (min / self.cell_size).floor() + 1.0
+        };
+        let count = span(bounds.min.x, bounds.max.x) * span(bounds.min.y, bounds.max.y);
+        if count.is_nan() || count > MAX_CELLS {
+            return None;
+        }
+        Some(CellRange {
+            min: self.cell_of(bounds.min),
+            max: self.cell_of(bounds.max),
+        })
+    }
+
+    fn unlink(&mut self, key: &K, range: Option<CellRange>) {
+        let Some(range) = range else {
+            self.overflow.remove(key);
+            return;
+        };
+        for cell in range.cells() {
+            if let Some(keys) = self.cells.get_mut(&cell) {
+                keys.retain(|k| k != key);
+                if keys.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+}
+
+impl<K: Clone + Ord> Default for SpatialHash<K> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aabb(x: f32, y: f32, width: f32, height: f32) -> Aabb {
+        Aabb::new(Vec2::new(x, y), Vec2::new(x + width, y + height))
+    }
+
+    /// Verify that only overlapping boxes pair up, once each, even across several cells.
+    #[test]
+    fn test_pairs() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert("a", aabb(0.0, 0.0, 25.0, 25.0));
+        hash.insert("b", aabb(15.0, 15.0, 25.0, 25.0));
+        hash.insert("c", aabb(25.0, 0.0, 5.0, 5.0));
+        hash.insert("d", aabb(100.0, 100.0, 5.0, 5.0));
+        assert_eq!(hash.pairs(), vec![("a", "b"), ("a", "c")]);
+    }
+
+    /// Verify that moving and removing boxes updates the pairs and the grid.
+    #[test]
+    fn test_update() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(1, aabb(0.0, 0.0, 5.0, 5.0));
+        hash.insert(2, aabb(50.0, 50.0, 5.0, 5.0));
+        assert!(hash.pairs().is_empty());
+
+        hash.insert(2, aabb(4.0, 4.0, 5.0, 5.0));
+        assert_eq!(hash.len(), 2);
+        assert_eq!(hash.pairs(), vec![(1, 2)]);
+        assert!(hash.query(aabb(50.0, 50.0, 1.0, 1.0)).is_empty());
+
+        hash.retain(|key| *key != 1);
+        assert!(!hash.contains(&1));
+        assert!(hash.pairs().is_empty());
+        assert_eq!(hash.remove(&2), Some(aabb(4.0, 4.0, 5.0, 5.0)));
+        assert!(hash.is_empty());
+        assert!(hash.cells.is_empty());
+    }
+
+    /// Verify that region and point queries return exactly the boxes hit, in order.
+    #[test]
+    fn test_queries() {
+        let mut hash = SpatialHash::default();
+        hash.insert("wall", aabb(-100.0, 0.0, 90.0, 500.0));
+        hash.insert("ball", aabb(20.0, 20.0, 10.0, 10.0));
+        hash.insert("box", aabb(0.0, 0.0, 40.0, 40.0));
+
+        assert_eq!(hash.query_point(Vec2::new(25.0, 25.0)), vec!["ball", "box"]);
+        assert_eq!(hash.query_point(Vec2::new(35.0, 35.0)), vec!["box"]);
+        assert_eq!(hash.query_point(Vec2::new(-50.0, 400.0)), vec!["wall"]);
+        assert_eq!(
+            hash.query(aabb(-20.0, 0.0, 25.0, 15.0)),
+            vec!["box", "wall"]
+        );
+        assert!(hash.query(aabb(200.0, 200.0, 10.0, 10.0)).is_empty());
+    }
+
+    /// Verify that infinite, NaN and huge boxes and areas stay out of the grid but
+    /// are still found.
+    #[test]
+    fn test_overflow() {
+        let mut hash = SpatialHash::new(10.0);
+        let everywhere = Aabb::new(
+            Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            Vec2::new(f32::INFINITY, f32::INFINITY),
+        );
+        hash.insert("a", aabb(0.0, 0.0, 5.0, 5.0));
+        hash.insert("b", everywhere);
+        hash.insert("c", aabb(-1.0e30, 0.0, 2.0e30, 1.0));
+        hash.insert("d", aabb(f32::NAN, 0.0, 1.0, 1.0));
+        hash.insert("e", aabb(100.0, 100.0, 5.0, 5.0));
+        assert_eq!(hash.cells.len(), 2);
+        assert_eq!(
+            hash.pairs(),
+            vec![("a", "b"), ("a", "c"), ("b", "c"), ("b", "e")]
+        );
+        assert_eq!(hash.query(everywhere), vec!["a", "b", "c", "e"]);
+        assert_eq!(hash.query(aabb(90.0, 90.0, 20.0, 20.0)), vec!["b", "e"]);
+        assert_eq!(hash.query_point(Vec2::new(1.0, 1.0)), vec!["a", "b", "c"]);
+
+        hash.insert("b", aabb(0.0, 0.0, 1.0, 1.0));
+        hash.remove(&"c");
+        hash.remove(&"d");
+        assert!(hash.overflow.is_empty());
+        assert_eq!(hash.pairs(), vec![("a", "b")]);
+    }
+
+    /// Verify that a zero cell size is rejected.
+    #[test]
+    #[should_panic(expected = "cell size must be positive")]
+    fn test_zero_cell_size_panics() {
+        SpatialHash::<u32>::new(0.0);
+    }
+}
@@ -0,0 +1,477 @@
+//! C API for the Rust engine layer.
+//!
+//! Exports the [`World`], its movement and collision handling, the fixed-step
+//! [`Physics`] and the window loop through a C ABI. Build the library with
+//! `cargo rustc -p game_engine --features capi --crate-type cdylib,staticlib`;
+//! `build.rs` then writes the matching header to `game_engine/include/game_engine.h`.
+//!
+//! All functions are prefixed with `ge_`. Worlds, simulations and engines are opaque
+//! pointers owned by the caller and released with [`ge_world_free`],
+//! [`ge_physics_free`] and `ge_engine_free`.
+//!
+//! The `ge_world_*` and `ge_physics_*` functions are pure logic and need no window: with
+//! `--no-default-features --features capi` the library builds without GLFW and
+//! OpenGL. The window loop (`ge_engine_*` and `ge_world_render`) needs a backend,
+//! `gl-backend` or the windowless `headless` stand-in.
+
+use crate::collision::{self, CollisionEvent, CollisionType};
+#[cfg(backend)]
+use crate::engine::Engine;
+#[cfg(backend)]
+use crate::ffi;
+use crate::math::Vec2;
+use crate::movement;
+use crate::physics::Physics;
+use crate::sprite::{Color, Pos, Size, Velocity};
+use crate::world::World;
+use std::ffi::{CStr, c_char};
+
+/// Kind of the last collision found by [`ge_world_step`], [`ge_world_collision`] and
+/// [`ge_physics_update`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeCollision {
+    None = 0,
+    WithSprite = 1,
+    WithBorder = 2,
+}
+
+impl From<&[CollisionEvent]> for GeCollision {
+    fn from(events: &[CollisionEvent]) -> Self {
+        match events.last().map(CollisionEvent::kind) {
+            Some(CollisionType::WithSprite) => GeCollision::WithSprite,
+            Some(CollisionType::WithBorder) => GeCollision::WithBorder,
+            None => GeCollision::None,
+        }
+    }
+}
+
+/// Converts a C string to `&str`, returning `None` for null or invalid UTF-8.
+///
+/// # Safety
+///
+/// `name` must be null or point to a NUL-terminated string.
+unsafe fn name_from_c<'a>(name: *const c_char) -> Option<&'a str> {
+    if name.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(name) }.to_str().ok()
+}
+
+/// Creates an empty world with the given window size.
+///
+/// # Returns
+///
+/// A world pointer that must be released with [`ge_world_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn ge_world_new(width: f32, height: f32) -> *mut World {
+    let mut world = World::empty();
+    world.window = Size { width, height };
+    Box::into_raw(Box::new(world))
+}
+
+/// Releases a world created by [`ge_world_new`]. Null is ignored.
+///
+/// # Safety
+///
+/// `world` must be null or a pointer returned by [`ge_world_new`] that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_free(world: *mut World) {
+    if !world.is_null() {
+        drop(unsafe { Box::from_raw(world) });
+    }
+}
+
+/// Adds a sprite to the world, replacing any sprite with the same name.
+///
+/// # Returns
+///
+/// `false` if `world` is null or `name` is not valid UTF-8.
+///
+/// # Safety
+///
+/// `world` must be a valid world pointer and `name` a NUL-terminated string.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ge_world_add_sprite(
+    world: *mut World,
+    name: *const c_char,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    dx: f32,
+    dy: f32,
+    r: i32,
+    g: i32,
+    b: i32,
+) -> bool {
+    let (Some(world), Some(name)) = (unsafe { world.as_mut() }, unsafe { name_from_c(name) })
+    else {
+        return false;
+    };
+    world.add_sprite(
+        name,
+        Pos { x, y },
+        Velocity { dx, dy },
+        Size { width, height },
+        Color { r, g, b },
+    );
+    true
+}
+
+/// Moves a sprite to a new position.
+///
+/// # Returns
+///
+/// `false` if the world or the sprite does not exist.
+///
+/// # Safety
+///
+/// `world` must be a valid world pointer and `name` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_set_sprite_pos(
+    world: *mut World,
+    name: *const c_char,
+    x: f32,
+    y: f32,
+) -> bool {
+    let (Some(world), Some(name)) = (unsafe { world.as_mut() }, unsafe { name_from_c(name) })
+    else {
+        return false;
+    };
+    if !world.sprites.contains_key(name) {
+        return false;
+    }
+    world.set_sprite_pos(name, Pos { x, y });
+    true
+}
+
+/// Reads the position of a sprite into `out_x` and `out_y`.
+///
+/// # Returns
+///
+/// `false` if the world or the sprite does not exist; the outputs are left untouched.
+///
+/// # Safety
+///
+/// `world` must be a valid world pointer, `name` a NUL-terminated string and
+/// `out_x`/`out_y` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_get_sprite_pos(
+    world: *const World,
+    name: *const c_char,
+    out_x: *mut f32,
+    out_y: *mut f32,
+) -> bool {
+    let (Some(world), Some(name)) = (unsafe { world.as_ref() }, unsafe { name_from_c(name) })
+    else {
+        return false;
+    };
+    match world.sprites.get(name) {
+        Some(sprite) if !out_x.is_null() && !out_y.is_null() => {
+            unsafe {
+                *out_x = sprite.pos.x;
+                *out_y = sprite.pos.y;
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns the number of sprites in the world, or 0 for a null world.
+///
+/// # Safety
+///
+/// `world` must be null or a valid world pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_sprite_count(world: *const World) -> usize {
+    unsafe { world.as_ref() }.map_or(0, |world| world.sprites.len())
+}
+
+/// Returns `true` if the two named sprites overlap.
+///
+/// # Safety
+///
+/// `world` must be a valid world pointer and both names NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_sprites_intersect(
+    world: *const World,
+    name_a: *const c_char,
+    name_b: *const c_char,
+) -> bool {
+    let (Some(world), Some(name_a), Some(name_b)) = (
+        unsafe { world.as_ref() },
+        unsafe { name_from_c(name_a) },
+        unsafe { name_from_c(name_b) },
+    ) else {
+        return false;
+    };
+    match (world.sprites.get(name_a), world.sprites.get(name_b)) {
+        (Some(a), Some(b)) => collision::intersects(a, b),
+        _ => false,
+    }
+}
+
+/// Moves all sprites by their velocity times `dt` (milliseconds), bouncing them off
+/// the sprites and borders they touch on the way.
+///
+/// # Safety
+///
+/// `world` must be null or a valid world pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_move_objects(world: *mut World, dt: f32) {
+    if let Some(world) = unsafe { world.as_mut() } {
+        movement::move_objects(world, dt);
+    }
+}
+
+/// Detects and resolves collisions between sprites and with the window borders.
+///
+/// # Safety
+///
+/// `world` must be null or a valid world pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_collision(world: *mut World, dt: f32) -> GeCollision {
+    match unsafe { world.as_mut() } {
+        Some(world) => collision::collision(world, dt).as_slice().into(),
+        None => GeCollision::None,
+    }
+}
+
+/// Advances the world by one frame: movement followed by collision handling.
+///
+/// # Safety
+///
+/// `world` must be null or a valid world pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_step(world: *mut World, dt: f32) -> GeCollision {
+    match unsafe { world.as_mut() } {
+        Some(world) => {
+            let mut events = movement::move_objects(world, dt);
+            events.extend(collision::collision(world, dt));
+            events.as_slice().into()
+        }
+        None => GeCollision::None,
+    }
+}
+
+/// Creates a simulation that advances worlds in fixed steps of `step_size`
+/// milliseconds, accelerating their dynamic sprites by the gravity
+/// (`gravity_x`, `gravity_y`) in pixels per millisecond squared.
+///
+/// # Returns
+///
+/// A simulation pointer that must be released with [`ge_physics_free`], or null if
+/// `step_size` is not positive.
+#[unsafe(no_mangle)]
+pub extern "C" fn ge_physics_new(step_size: f32, gravity_x: f32, gravity_y: f32) -> *mut Physics {
+    if step_size.is_nan() || step_size <= 0.0 {
+        return std::ptr::null_mut();
+    }
+    let physics = Physics::new(step_size).with_gravity(Vec2::new(gravity_x, gravity_y));
+    Box::into_raw(Box::new(physics))
+}
+
+/// Releases a simulation created by [`ge_physics_new`]. Null is ignored.
+///
+/// # Safety
+///
+/// `physics` must be null or a pointer returned by [`ge_physics_new`] that was not
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_physics_free(physics: *mut Physics) {
+    if !physics.is_null() {
+        drop(unsafe { Box::from_raw(physics) });
+    }
+}
+
+/// Advances the world by `dt` milliseconds in as many fixed steps as fit, with gravity,
+/// movement and collision handling in each; the rest of `dt` is kept for the next
+/// update.
+///
+/// # Safety
+///
+/// `physics` and `world` must each be null or a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_physics_update(
+    physics: *mut Physics,
+    world: *mut World,
+    dt: f32,
+) -> GeCollision {
+    match (unsafe { physics.as_mut() }, unsafe { world.as_mut() }) {
+        (Some(physics), Some(world)) => physics.update(world, dt).as_slice().into(),
+        _ => GeCollision::None,
+    }
+}
+
+/// Creates the game window.
+///
+/// # Returns
+///
+/// An engine pointer to release with [`ge_engine_free`], or null if the window
+/// could not be created (invalid title, or a window already exists).
+///
+/// # Safety
+///
+/// `title` must be a NUL-terminated string.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_create_window(
+    title: *const c_char,
+    width: i32,
+    height: i32,
+) -> *mut Engine {
+    let Some(title) = (unsafe { name_from_c(title) }) else {
+        return std::ptr::null_mut();
+    };
+    match ffi::rust_create_game_window(title, width, height) {
+        Ok(engine) => Box::into_raw(Box::new(engine)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Releases an engine created by [`ge_engine_create_window`]. Null is ignored.
+///
+/// # Safety
+///
+/// `engine` must be null or a pointer returned by [`ge_engine_create_window`]
+/// that was not freed yet.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_free(engine: *mut Engine) {
+    if !engine.is_null() {
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+/// Returns `true` if the window should close. A null engine always closes.
+///
+/// # Safety
+///
+/// `engine` must be null or a valid engine pointer.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_should_close(engine: *const Engine) -> bool {
+    unsafe { engine.as_ref() }.is_none_or(ffi::rust_window_should_close)
+}
+
+/// Clears the screen at the start of a frame.
+///
+/// # Safety
+///
+/// `engine` must be null or a valid engine pointer.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_begin_frame(engine: *const Engine) {
+    if let Some(engine) = unsafe { engine.as_ref() } {
+        ffi::rust_clear_screen(engine);
+    }
+}
+
+/// Swaps buffers and polls events at the end of a frame.
+///
+/// # Safety
+///
+/// `engine` must be null or a valid engine pointer.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_end_frame(engine: *const Engine) {
+    if let Some(engine) = unsafe { engine.as_ref() } {
+        ffi::rust_update_game_window(engine);
+    }
+}
+
+/// Returns `true` while the given GLFW key is pressed.
+///
+/// # Safety
+///
+/// `engine` must be null or a valid engine pointer.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_engine_key_pressed(engine: *const Engine, key: i32) -> bool {
+    unsafe { engine.as_ref() }
+        .is_some_and(|engine| ffi::rust_get_key(engine, key) == ffi::GLFW_PRESS)
+}
+
+/// Renders all sprites of the world.
+///
+/// # Safety
+///
+/// `engine` and `world` must be null or valid pointers.
+#[cfg(backend)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ge_world_render(engine: *const Engine, world: *const World) {
+    if let (Some(engine), Some(world)) = (unsafe { engine.as_ref() }, unsafe { world.as_ref() }) {
+        for sprite in world.get_sprites().values() {
+            sprite.render(engine);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that sprites added through the C API move and report border collisions.
+    #[test]
+    fn test_world_step_reports_border() {
+        let world = ge_world_new(100.0, 100.0);
+        unsafe {
+            assert!(ge_world_add_sprite(
+                world, c"ball".as_ptr(), 85.0, 10.0, 10.0, 10.0, 1.0, 0.0, 255, 255, 255
+            ));
+            assert_eq!(ge_world_sprite_count(world), 1);
+            assert_eq!(ge_world_step(world, 10.0), GeCollision::WithBorder);
+
+            let (mut x, mut y) = (0.0, 0.0);
+            assert!(ge_world_get_sprite_pos(world, c"ball".as_ptr(), &mut x, &mut y));
+            // The ball reaches the border after 5 ms and moves back for the other 5 ms.
+            assert_eq!(x, 85.0);
+            assert_eq!(y, 10.0);
+            ge_world_free(world);
+        }
+    }
+
+    /// Verify that a simulation pulls sprites down by gravity in fixed steps.
+    #[test]
+    fn test_physics_update() {
+        assert!(ge_physics_new(0.0, 0.0, 1.0).is_null());
+
+        let world = ge_world_new(100.0, 100.0);
+        let physics = ge_physics_new(8.0, 0.0, 1.0 / 1024.0);
+        unsafe {
+            assert!(ge_world_add_sprite(
+                world, c"ball".as_ptr(), 10.0, 10.0, 10.0, 10.0, 0.0, 0.0, 255, 255, 255
+            ));
+            assert_eq!(ge_physics_update(physics, world, 12.0), GeCollision::None);
+
+            let (mut x, mut y) = (0.0, 0.0);
+            assert!(ge_world_get_sprite_pos(world, c"ball".as_ptr(), &mut x, &mut y));
+            // One step of 8 ms at 1/128 pixels per millisecond; 4 ms are kept.
+            assert_eq!(x, 10.0);
+            assert_eq!(y, 10.0625);
+            ge_physics_free(physics);
+            ge_world_free(world);
+        }
+    }
+
+    /// Verify that null pointers and unknown sprites are rejected instead of crashing.
+    #[test]
+    fn test_invalid_arguments() {
+        unsafe {
+            assert_eq!(ge_world_sprite_count(std::ptr::null()), 0);
+            assert_eq!(ge_world_step(std::ptr::null_mut(), 1.0), GeCollision::None);
+            let physics = ge_physics_update(std::ptr::null_mut(), std::ptr::null_mut(), 1.0);
+            assert_eq!(physics, GeCollision::None);
+
+            let world = ge_world_new(100.0, 100.0);
+            assert!(!ge_world_add_sprite(
+                world, std::ptr::null(), 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0, 0, 0
+            ));
+            assert!(!ge_world_set_sprite_pos(world, c"ghost".as_ptr(), 1.0, 1.0));
+            ge_world_free(world);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Frame timing.
+//!
+//! Games take the time delta of each frame from a [`Clock`] instead of reading the
+//! system time themselves, so the source of time can be swapped: a [`RealClock`]
+//! when playing, a [`FixedClock`] for a simulation that does not depend on
+//! wall-clock jitter, and a [`ManualClock`] that a test advances by hand.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Source of the time delta of each frame.
+pub trait Clock {
+    /// Returns the time since the previous tick in milliseconds.
+    fn tick(&mut self) -> f32;
+
+    /// Restarts timing, so the next tick does not count the time spent loading.
+    fn reset(&mut self) {}
+}
+
+/// Measures wall-clock time.
+pub struct RealClock {
+    last: Instant,
+    min_dt: f32,
+}
+
+impl RealClock {
+    /// Creates a clock that reports at least `min_dt` milliseconds per tick, so frames
+    /// faster than the timer resolution still advance the game.
+    pub fn new(min_dt: f32) -> Self {
+        Self {
+            last: Instant::now(),
+            min_dt,
+        }
+    }
+}
+
+impl Clock for RealClock {
+    fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last).as_secs_f32() * 1000.0;
+        self.last = now;
+        dt.max(self.min_dt)
+    }
+
+    fn reset(&mut self) {
+        self.last = Instant::now();
+    }
+}
+
+/// Advances by the same step every tick, however long the frame took.
+pub struct FixedClock {
+    step: f32,
+}
+
+impl FixedClock {
+    /// Creates a clock that reports `step` milliseconds per tick.
+    pub fn new(step: f32) -> Self {
+        Self { step }
+    }
+}
+
+impl Clock for FixedClock {
+    fn tick(&mut self) -> f32 {
+        self.step
+    }
+}
+
+/// Reports the time it was advanced by since the previous tick.
+///
+/// Clones share their time, so a test can keep one to advance a clock it handed to
+/// the game.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    pending: Rc<Cell<f32>>,
+}
+
+impl ManualClock {
+    /// Creates a clock that reports zero until it is advanced.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `ms` milliseconds to the next tick.
+    pub fn advance(&self, ms: f32) {
+        self.pending.set(self.pending.get() + ms);
+    }
+}
+
+impl Clock for ManualClock {
+    fn tick(&mut self) -> f32 {
+        self.pending.take()
+    }
+
+    fn reset(&mut self) {
+        self.pending.set(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the fixed and manual clocks report the expected deltas.
+    #[test]
+    fn test_fixed_and_manual() {
+        let mut fixed = FixedClock::new(16.0);
+        assert_eq!(fixed.tick(), 16.0);
+        assert_eq!(fixed.tick(), 16.0);
+
+        let mut clock = ManualClock::new();
+        let handle = clock.clone();
+        assert_eq!(clock.tick(), 0.0);
+        handle.advance(10.0);
+        handle.advance(5.0);
+        assert_eq!(clock.tick(), 15.0);
+        assert_eq!(clock.tick(), 0.0);
+    }
+
+    /// Verify that the real clock never reports less than its minimum.
+    #[test]
+    fn test_real_min_dt() {
+        let mut clock = RealClock::new(2.0);
+        clock.reset();
+        assert!(clock.tick() >= 2.0);
+    }
+}
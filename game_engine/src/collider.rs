@@ -0,0 +1,440 @@
+//! Collider shapes and the narrow phase.
+//!
+//! A sprite collides with its rectangle unless its [`Collider`] says otherwise: a
+//! circle, an axis-aligned box, an oriented (rotated) box or a convex polygon, all
+//! placed relative to the sprite center. The collider does not change how the sprite
+//! is drawn.
+//!
+//! For the tests a collider becomes a [`Shape`] in world space: a circle or a convex
+//! polygon, boxes being polygons with four corners. [`manifold`] tests two shapes with
+//! the separating-axis theorem: the shapes overlap only if their projections overlap
+//! on every edge normal, and for a circle on the axis towards the closest polygon
+//! corner. The axis of least overlap gives the normal and depth of the manifold.
+
+use crate::collision::Manifold;
+use crate::math::{Aabb, Vec2};
+use crate::sprite::{Pos, Sprite};
+
+/// Collision shape of a sprite, relative to the sprite center.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "shape", rename_all = "snake_case"))]
+pub enum Collider {
+    /// The rectangle the sprite is drawn with.
+    #[default]
+    Sprite,
+    /// An axis-aligned box centered on the sprite.
+    Aabb {
+        width: f32,
+        height: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+    /// A box rotated by `angle` radians, clockwise on screen, around the sprite center.
+    Obb {
+        width: f32,
+        height: f32,
+        angle: f32,
+    },
+    /// A convex polygon with at least three corners, in order.
+    Polygon {
+        points: Vec<Vec2>,
+    },
+}
+
+impl Collider {
+    /// Returns `true` if the sizes and the radius are positive and finite, the angle
+    /// is finite, and a polygon is convex with at least three finite corners.
+    pub fn is_valid(&self) -> bool {
+        let positive = |value: f32| value > 0.0 && value.is_finite();
+        match self {
+            Collider::Sprite => true,
+            Collider::Aabb { width, height } => positive(*width) && positive(*height),
+            Collider::Obb {
+                width,
+                height,
+                angle,
+            } => positive(*width) && positive(*height) && angle.is_finite(),
+            Collider::Circle { radius } => positive(*radius),
+            Collider::Polygon { points } => is_convex(points),
+        }
+    }
+
+    /// Returns the collider of a sprite at `pos` with `size` as a shape in world space.
+    pub fn shape(&self, pos: Pos, size: Vec2) -> Shape {
+        let center = Vec2::from(pos) + size * 0.5;
+        let corners = |half: Vec2, angle: f32| {
+            [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, y)| center + Vec2::new(half.x * x, half.y * y).rotated(angle))
+                .to_vec()
+        };
+        match self {
+            Collider::Sprite => Shape::Polygon(corners(size * 0.5, 0.0)),
+            Collider::Aabb { width, height } => {
+                Shape::Polygon(corners(Vec2::new(*width, *height) * 0.5, 0.0))
+            }
+            Collider::Circle { radius } => Shape::Circle {
+                center,
+                radius: *radius,
+            },
+            Collider::Obb {
+                width,
+                height,
+                angle,
+            } => Shape::Polygon(corners(Vec2::new(*width, *height) * 0.5, *angle)),
+            Collider::Polygon { points } => {
+                Shape::Polygon(points.iter().map(|point| center + *point).collect())
+            }
+        }
+    }
+}
+
+/// Returns `true` if `points` are the finite corners of a convex polygon, in either
+/// order.
+///
+/// All turns must go the same way, and together make exactly one revolution: a star
+/// such as a pentagram turns the same way at every corner, but twice around.
+fn is_convex(points: &[Vec2]) -> bool {
+    if points.len() < 3
+        || !points
+            .iter()
+            .all(|point| point.x.is_finite() && point.y.is_finite())
+    {
+        return false;
+    }
+    let edges: Vec<(Vec2, Vec2)> = (0..points.len())
+        .map(|i| {
+            let [a, b, c] = [0, 1, 2].map(|k| points[(i + k) % points.len()]);
+            (b - a, c - b)
+        })
+        .collect();
+    let turns: Vec<f32> = edges.iter().map(|(ab, bc)| ab.cross(*bc)).collect();
+    if !(turns.iter().all(|turn| *turn > 0.0) || turns.iter().all(|turn| *turn < 0.0)) {
+        return false;
+    }
+    // With all turns the same way the total is a whole number of revolutions.
+    let total: f32 = edges
+        .iter()
+        .map(|(ab, bc)| ab.cross(*bc).atan2(ab.dot(*bc)))
+        .sum();
+    (total.abs() - std::f32::consts::TAU).abs() < std::f32::consts::PI
+}
+
+/// A collider in world space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    /// Corners of a convex polygon, in order.
+    Polygon(Vec<Vec2>),
+}
+
+impl Shape {
+    /// Returns the smallest axis-aligned box around the shape.
+    ///
+    /// A polygon without corners gives an empty box, with `min` at positive and `max`
+    /// at negative infinity, which overlaps nothing.
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            Shape::Circle { center, radius } => Aabb::new(
+                *center - Vec2::new(*radius, *radius),
+                *center + Vec2::new(*radius, *radius),
+            ),
+            Shape::Polygon(points) => {
+                let mut bounds = Aabb::new(
+                    Vec2::new(f32::INFINITY, f32::INFINITY),
+                    Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+                );
+                for point in points {
+                    bounds.min = Vec2::new(bounds.min.x.min(point.x), bounds.min.y.min(point.y));
+                    bounds.max = Vec2::new(bounds.max.x.max(point.x), bounds.max.y.max(point.y));
+                }
+                bounds
+            }
+        }
+    }
+
+    /// Returns the center of a circle, or the average of the corners of a polygon.
+    pub fn center(&self) -> Vec2 {
+        match self {
+            Shape::Circle { center, .. } => *center,
+            Shape::Polygon(points) => {
+                let sum = points.iter().fold(Vec2::ZERO, |sum, point| sum + *point);
+                sum * (1.0 / points.len() as f32)
+            }
+        }
+    }
+
+    /// Returns the interval the shape covers when projected onto the unit vector `axis`.
+    fn project(&self, axis: Vec2) -> (f32, f32) {
+        match self {
+            Shape::Circle { center, radius } => {
+                let center = center.dot(axis);
+                (center - radius, center + radius)
+            }
+            Shape::Polygon(points) => points
+                .iter()
+                .map(|point| point.dot(axis))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), d| {
+                    (min.min(d), max.max(d))
+                }),
+        }
+    }
+
+    /// Returns the unit axes this shape adds to a separating-axis test with `other`:
+    /// the edge normals of a polygon, or for a circle the axis to the closest corner or
+    /// center of `other`.
+    fn axes(&self, other: &Shape) -> Vec<Vec2> {
+        match self {
+            Shape::Polygon(points) => (0..points.len())
+                .filter_map(|i| {
+                    let edge = points[(i + 1) % points.len()] - points[i];
+                    Vec2::new(edge.y, -edge.x).normalized()
+                })
+                .collect(),
+            Shape::Circle { center, .. } => {
+                let closest = match other {
+                    Shape::Circle { center, .. } => *center,
+                    Shape::Polygon(points) => points
+                        .iter()
+                        .copied()
+                        .min_by(|a, b| (*a - *center).length().total_cmp(&(*b - *center).length()))
+                        .unwrap_or(*center),
+                };
+                // Concentric circles overlap on every axis; any one will do.
+                vec![
+                    (*center - closest)
+                        .normalized()
+                        .unwrap_or(Vec2::new(1.0, 0.0)),
+                ]
+            }
+        }
+    }
+
+    /// Returns the point of the shape furthest along `direction`, averaged over the
+    /// corners of a polygon side that faces it.
+    fn support(&self, direction: Vec2) -> Vec2 {
+        match self {
+            Shape::Circle { center, radius } => *center + direction * *radius,
+            Shape::Polygon(points) => {
+                let furthest = self.project(direction).1;
+                let tolerance = 1e-4 * furthest.abs().max(1.0);
+                let side: Vec<Vec2> = points
+                    .iter()
+                    .copied()
+                    .filter(|point| point.dot(direction) >= furthest - tolerance)
+                    .collect();
+                Shape::Polygon(side).center()
+            }
+        }
+    }
+}
+
+/// Returns the manifold of two overlapping shapes, found with the separating-axis
+/// theorem.
+///
+/// # Returns
+///
+/// * `Some(Manifold)` with the normal pointing towards `a`, or `None` if the shapes
+///   do not overlap. Shapes that only touch do not overlap. The contact point is the
+///   deepest point of a circle in a polygon, and otherwise halfway between the
+///   deepest points of both shapes.
+pub fn manifold(a: &Shape, b: &Shape) -> Option<Manifold> {
+    let mut least: Option<(f32, Vec2)> = None;
+    for axis in a.axes(b).into_iter().chain(b.axes(a)) {
+        let (a_min, a_max) = a.project(axis);
+        let (b_min, b_max) = b.project(axis);
+        let overlap = a_max.min(b_max) - a_min.max(b_min);
+        if overlap <= 0.0 {
+            return None;
+        }
+        if least.is_none_or(|(depth, _)| overlap < depth) {
+            least = Some((overlap, axis));
+        }
+    }
+
+    let (depth, mut normal) = least?;
+    if (a.center() - b.center()).dot(normal) < 0.0 {
+        normal = -normal;
+    }
+    let point = match (a, b) {
+        (Shape::Circle { .. }, Shape::Polygon(_)) => a.support(-normal),
+        (Shape::Polygon(_), Shape::Circle { .. }) => b.support(normal),
+        _ => (a.support(-normal) + b.support(normal)) * 0.5,
+    };
+    Some(Manifold {
+        normal,
+        depth,
+        point: point.into(),
+    })
+}
+
+impl Sprite {
+    /// Returns the collider of the sprite in world space.
+    pub fn shape(&self) -> Shape {
+        let size = Vec2::new(self.size.width, self.size.height);
+        self.collider.shape(self.pos, size)
+    }
+
+    /// Returns the box around the collider, the sprite rectangle for the default
+    /// collider.
+    pub fn collider_bounds(&self) -> Aabb {
+        match self.collider {
+            Collider::Sprite => self.bounds(),
+            _ => self.shape().bounds(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Shape {
+        Collider::Sprite.shape(Pos { x, y }, Vec2::new(size, size))
+    }
+
+    /// Verify that circles overlap by the distance of their centers.
+    #[test]
+    fn test_circles() {
+        let a = Shape::Circle {
+            center: Vec2::new(0.0, 0.0),
+            radius: 5.0,
+        };
+        let b = Shape::Circle {
+            center: Vec2::new(8.0, 0.0),
+            radius: 5.0,
+        };
+        let manifold = manifold(&a, &b).unwrap();
+        assert_eq!(manifold.normal, Vec2::new(-1.0, 0.0));
+        assert_eq!(manifold.depth, 2.0);
+        assert_eq!(manifold.point, Pos { x: 4.0, y: 0.0 });
+
+        let far = Shape::Circle {
+            center: Vec2::new(10.0, 0.0),
+            radius: 5.0,
+        };
+        assert!(super::manifold(&a, &far).is_none());
+    }
+
+    /// Verify that a circle near the corner of a box misses it although their bounding
+    /// boxes overlap, and hits a side with the side normal.
+    #[test]
+    fn test_circle_and_box() {
+        let block = square(0.0, 0.0, 10.0);
+        let corner = Shape::Circle {
+            center: Vec2::new(13.0, 13.0),
+            radius: 4.0,
+        };
+        assert!(corner.bounds().overlaps(&block.bounds()));
+        assert!(manifold(&corner, &block).is_none());
+
+        let side = Shape::Circle {
+            center: Vec2::new(5.0, 13.0),
+            radius: 4.0,
+        };
+        let manifold = manifold(&side, &block).unwrap();
+        assert_eq!(manifold.normal, Vec2::new(0.0, 1.0));
+        assert_eq!(manifold.depth, 1.0);
+        assert_eq!(manifold.point, Pos { x: 5.0, y: 9.0 });
+    }
+
+    /// Verify that a box rotated by 45 degrees overlaps along its own edge normals.
+    #[test]
+    fn test_oriented_box() {
+        let diamond = Collider::Obb {
+            width: 10.0,
+            height: 10.0,
+            angle: std::f32::consts::FRAC_PI_4,
+        }
+        .shape(Pos { x: 0.0, y: 0.0 }, Vec2::new(10.0, 10.0));
+        let half_diagonal = 50.0_f32.sqrt();
+        assert!((diamond.bounds().max.x - (5.0 + half_diagonal)).abs() < 1e-4);
+
+        // The corner of the box points into the diamond's corner gap.
+        assert!(manifold(&square(10.5, 10.5, 10.0), &diamond).is_none());
+        let manifold = manifold(&square(11.0, 0.0, 10.0), &diamond).unwrap();
+        assert_eq!(manifold.normal, Vec2::new(1.0, 0.0));
+        assert!((manifold.depth - (half_diagonal - 6.0)).abs() < 1e-4);
+    }
+
+    /// Verify that polygons must be convex, with corners in either order.
+    #[test]
+    fn test_polygon_validity() {
+        let triangle = vec![
+            Vec2::new(0.0, -5.0),
+            Vec2::new(5.0, 5.0),
+            Vec2::new(-5.0, 5.0),
+        ];
+        let mut reversed = triangle.clone();
+        reversed.reverse();
+        let dart = vec![
+            Vec2::new(0.0, -5.0),
+            Vec2::new(5.0, 5.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(-5.0, 5.0),
+        ];
+        assert!(Collider::Polygon { points: triangle }.is_valid());
+        assert!(Collider::Polygon { points: reversed }.is_valid());
+        assert!(!Collider::Polygon { points: dart }.is_valid());
+        assert!(!Collider::Circle { radius: 0.0 }.is_valid());
+    }
+
+    /// Verify that stars, which turn the same way at every corner but more than once
+    /// around, and shapes with infinite or NaN dimensions are invalid.
+    #[test]
+    fn test_invalid_colliders() {
+        let pentagram: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::new(0.0, -5.0).rotated(i as f32 * 4.0 * std::f32::consts::PI / 5.0))
+            .collect();
+        let pentagon: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::new(0.0, -5.0).rotated(i as f32 * 2.0 * std::f32::consts::PI / 5.0))
+            .collect();
+        assert!(Collider::Polygon { points: pentagon }.is_valid());
+        assert!(!Collider::Polygon { points: pentagram }.is_valid());
+
+        let far = vec![
+            Vec2::new(0.0, -5.0),
+            Vec2::new(f32::INFINITY, 5.0),
+            Vec2::new(-5.0, 5.0),
+        ];
+        assert!(!Collider::Polygon { points: far }.is_valid());
+        assert!(
+            !Collider::Circle {
+                radius: f32::INFINITY
+            }
+            .is_valid()
+        );
+        assert!(
+            !Collider::Aabb {
+                width: f32::INFINITY,
+                height: 1.0
+            }
+            .is_valid()
+        );
+        assert!(
+            !Collider::Obb {
+                width: 1.0,
+                height: 1.0,
+                angle: f32::NAN
+            }
+            .is_valid()
+        );
+    }
+
+    /// Verify that a polygon without corners is invalid and overlaps nothing instead
+    /// of panicking.
+    #[test]
+    fn test_empty_polygon() {
+        let empty = Collider::Polygon { points: vec![] };
+        assert!(!empty.is_valid());
+        let shape = empty.shape(Pos { x: 0.0, y: 0.0 }, Vec2::new(10.0, 10.0));
+        let square = square(0.0, 0.0, 10.0);
+        assert!(!shape.bounds().overlaps(&square.bounds()));
+        assert!(manifold(&shape, &square).is_none());
+        assert!(manifold(&square, &shape).is_none());
+    }
+}
@@ -0,0 +1,1006 @@
+//! Collision logic.
+//!
+//! This module provides functions to detect and resolve collisions
+//! between sprites and with the game window borders.
+//!
+//! [`collision`] finds sprites that already overlap, computes their [`Manifold`]
+//! (normal, depth and contact point) and responds by pushing them apart and reflecting
+//! their velocities along the normal, scaled by the restitution. Only dynamic sprites
+//! are moved by collisions, see [`BodyKind`]. Candidate pairs come from the world's
+//! [`crate::broad_phase::SpatialHash`], so sprites far apart are never compared.
+//! Every collision is reported as a [`CollisionEvent`] naming the sprites, or the
+//! sprite and the [`Border`], with the normal and contact point.
+//!
+//! Sprites collide with their rectangles unless they have another
+//! [`Collider`](crate::collider::Collider); pairs with a circle, oriented box or polygon
+//! are tested with the separating-axis test of [`crate::collider::manifold`].
+//!
+//! [`sweep`] and [`sweep_border`]
+//! are continuous tests of the sprite rectangles: they find the time within a frame at which a moving sprite
+//! first touches another one or a border, so [`crate::movement::move_objects`] can stop
+//! it there instead of letting a fast sprite jump through a thin one.
+
+use crate::broad_phase::SpatialHash;
+use crate::collider::{self, Collider};
+use crate::ecs::{self, Entity};
+use crate::math::{Aabb, Vec2};
+use crate::sprite::{BodyKind, Pos, Size, Sprite};
+use crate::world::World;
+
+/// Kind of a [`CollisionEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionType {
+    WithSprite,
+    WithBorder,
+}
+
+/// A side of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Border {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Border {
+    /// Returns the border whose normal, pointing into the window, is `normal`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `normal` is not one of the four axis-aligned unit normals.
+    pub fn from_normal(normal: Vec2) -> Border {
+        match (normal.x, normal.y) {
+            (1.0, 0.0) => Border::Left,
+            (-1.0, 0.0) => Border::Right,
+            (0.0, 1.0) => Border::Top,
+            (0.0, -1.0) => Border::Bottom,
+            _ => panic!("{normal:?} is not the normal of a window border"),
+        }
+    }
+
+    /// Returns the unit normal of the border, pointing into the window.
+    pub fn normal(self) -> Vec2 {
+        match self {
+            Border::Left => Vec2::new(1.0, 0.0),
+            Border::Right => Vec2::new(-1.0, 0.0),
+            Border::Top => Vec2::new(0.0, 1.0),
+            Border::Bottom => Vec2::new(0.0, -1.0),
+        }
+    }
+}
+
+/// What a sprite collided with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Touched {
+    /// Another sprite, by name.
+    Sprite(String),
+    Border(Border),
+}
+
+/// A collision between a sprite and another sprite or a window border, reported by
+/// [`collision`] and [`crate::movement::move_objects`] and passed to the handlers
+/// registered with [`World::subscribe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionEvent {
+    /// Name of the sprite the normal points to.
+    pub sprite: String,
+    pub other: Touched,
+    /// Unit contact normal, pointing towards `sprite`. It is axis aligned unless a
+    /// collider of the sprites is a circle, an oriented box or a polygon.
+    pub normal: Vec2,
+    /// Center of the overlap or shared edge of two sprites, or the point where the
+    /// sprite touched the border.
+    pub point: Pos,
+    /// `true` if one of the sprites is a trigger; the collision was only reported.
+    pub trigger: bool,
+}
+
+impl CollisionEvent {
+    pub fn kind(&self) -> CollisionType {
+        match self.other {
+            Touched::Sprite(_) => CollisionType::WithSprite,
+            Touched::Border(_) => CollisionType::WithBorder,
+        }
+    }
+
+    /// Returns `true` if the sprite `name` took part in the collision.
+    pub fn involves(&self, name: &str) -> bool {
+        self.sprite == name || self.other == Touched::Sprite(name.to_string())
+    }
+
+    /// Returns the border that was touched, or `None` for two sprites.
+    pub fn border(&self) -> Option<Border> {
+        match self.other {
+            Touched::Border(border) => Some(border),
+            Touched::Sprite(_) => None,
+        }
+    }
+}
+
+/// First contact of a moving sprite, found by [`sweep`] or [`sweep_border`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Time of impact in milliseconds from the start of the sweep.
+    pub time: f32,
+    /// Unit normal of the touched surface, pointing towards the moving sprite. It is
+    /// axis aligned.
+    pub normal: Vec2,
+}
+
+/// Finds when two moving sprites first touch within `dt` (swept AABB test).
+///
+/// The sprites move by their velocities, static sprites not at all; only the relative
+/// motion matters. The test uses the boxes around the colliders, see
+/// [`Sprite::collider_bounds`], so a circle or rotated box stops where its box
+/// touches the other one.
+///
+/// # Arguments
+///
+/// * `a` - The first sprite. The normal of the hit points towards it.
+/// * `b` - The second sprite.
+/// * `dt` - Length of the sweep in milliseconds.
+///
+/// # Returns
+///
+/// * `Some(Hit)` if `a` and `b` are apart and start touching within `dt`, otherwise
+///   `None`. Sprites that already overlap, or touch and move apart, are not hit.
+pub fn sweep(a: &Sprite, b: &Sprite, dt: f32) -> Option<Hit> {
+    let v = motion(a) - motion(b);
+    let (a, b) = (a.collider_bounds(), b.collider_bounds());
+    let (entry_x, exit_x) = axis_times(a.min.x, a.max.x, b.min.x, b.max.x, v.x)?;
+    let (entry_y, exit_y) = axis_times(a.min.y, a.max.y, b.min.y, b.max.y, v.y)?;
+
+    let entry = entry_x.max(entry_y);
+    let exit = exit_x.min(exit_y);
+    if entry >= exit || entry < 0.0 || entry > dt {
+        return None;
+    }
+
+    let normal = if entry_x >= entry_y {
+        Vec2::new(-v.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, -v.y.signum())
+    };
+    Some(Hit {
+        time: entry,
+        normal,
+    })
+}
+
+/// Returns the velocity a sprite moves with: zero for static sprites.
+fn motion(sprite: &Sprite) -> Vec2 {
+    if sprite.body == BodyKind::Static {
+        Vec2::ZERO
+    } else {
+        Vec2::from(sprite.velocity)
+    }
+}
+
+/// Returns the times at which a segment `a` moving by `v` starts and stops overlapping
+/// the segment `b` on one axis, or `None` if they never overlap.
+fn axis_times(a_min: f32, a_max: f32, b_min: f32, b_max: f32, v: f32) -> Option<(f32, f32)> {
+    if v == 0.0 {
+        let overlapping = a_min < b_max && a_max > b_min;
+        return overlapping.then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let close = (b_min - a_max) / v;
+    let far = (b_max - a_min) / v;
+    Some((close.min(far), close.max(far)))
+}
+
+/// Finds when a moving sprite inside the window first touches a window border within `dt`.
+///
+/// Like [`sweep`] this uses the box around the collider of the sprite.
+///
+/// # Arguments
+///
+/// * `sprite` - The moving sprite.
+/// * `window` - Size of the window.
+/// * `dt` - Length of the sweep in milliseconds.
+///
+/// # Returns
+///
+/// * `Some(Hit)` with the normal pointing into the window, or `None` if the sprite does
+///   not reach a border it moves towards. Borders the sprite is already beyond are
+///   left to [`collision`].
+pub fn sweep_border(sprite: &Sprite, window: Size, dt: f32) -> Option<Hit> {
+    let border = |min: f32, max: f32, limit: f32, v: f32| {
+        let time = if v > 0.0 {
+            (limit - max) / v
+        } else if v < 0.0 {
+            -min / v
+        } else {
+            return None;
+        };
+        (0.0..=dt).contains(&time).then_some(time)
+    };
+
+    let (bounds, v) = (sprite.collider_bounds(), sprite.velocity);
+    let x = border(bounds.min.x, bounds.max.x, window.width, v.dx)
+        .map(|time| (time, Vec2::new(-v.dx.signum(), 0.0)));
+    let y = border(bounds.min.y, bounds.max.y, window.height, v.dy)
+        .map(|time| (time, Vec2::new(0.0, -v.dy.signum())));
+    let (time, normal) = match (x, y) {
+        (Some(x), Some(y)) if y.0 < x.0 => y,
+        (Some(x), _) => x,
+        (None, y) => y?,
+    };
+    Some(Hit { time, normal })
+}
+
+/// Returns the center of the side of the collider box of `sprite` that faces away
+/// from `normal`, the point where it touches a surface with that normal.
+pub(crate) fn face_center(sprite: &Sprite, normal: Vec2) -> Pos {
+    let bounds = sprite.collider_bounds();
+    let side = |min: f32, max: f32, normal: f32| {
+        if normal > 0.0 {
+            min
+        } else if normal < 0.0 {
+            max
+        } else {
+            (min + max) / 2.0
+        }
+    };
+    Pos {
+        x: side(bounds.min.x, bounds.max.x, normal.x),
+        y: side(bounds.min.y, bounds.max.y, normal.y),
+    }
+}
+
+/// Overlap of two sprites, or of a sprite and a window border.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Manifold {
+    /// Unit normal along which the overlap is smallest, pointing towards the first
+    /// sprite. It is axis aligned for sprite rectangles and borders.
+    pub normal: Vec2,
+    /// How far the shapes overlap along the normal.
+    pub depth: f32,
+    /// Center of the overlap, or the point where a sprite crossed a border.
+    pub point: Pos,
+}
+
+/// Returns the manifold of two overlapping sprites.
+///
+/// Two sprite rectangles overlap along the axis of least overlap, with the contact in
+/// the center of the overlap. Other colliders go through [`collider::manifold`].
+///
+/// # Returns
+///
+/// * `Some(Manifold)` with the normal pointing towards `a`, or `None` if the sprites
+///   do not overlap.
+pub fn manifold(a: &Sprite, b: &Sprite) -> Option<Manifold> {
+    if a.collider != Collider::Sprite || b.collider != Collider::Sprite {
+        return collider::manifold(&a.shape(), &b.shape());
+    }
+    let overlap_x = (a.pos.x + a.size.width).min(b.pos.x + b.size.width) - a.pos.x.max(b.pos.x);
+    let overlap_y = (a.pos.y + a.size.height).min(b.pos.y + b.size.height) - a.pos.y.max(b.pos.y);
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+
+    let center = |sprite: &Sprite| {
+        Vec2::from(sprite.pos) + Vec2::new(sprite.size.width / 2.0, sprite.size.height / 2.0)
+    };
+    let offset = center(a) - center(b);
+    let sign = |v: f32| if v < 0.0 { -1.0 } else { 1.0 };
+    let (normal, depth) = if overlap_x <= overlap_y {
+        (Vec2::new(sign(offset.x), 0.0), overlap_x)
+    } else {
+        (Vec2::new(0.0, sign(offset.y)), overlap_y)
+    };
+    Some(Manifold {
+        normal,
+        depth,
+        point: overlap_center(a, b),
+    })
+}
+
+/// Returns a manifold for each window border the collider of a sprite reaches beyond,
+/// with the normal pointing into the window.
+pub fn border_manifolds(sprite: &Sprite, window: Size) -> Vec<Manifold> {
+    let (bounds, center) = extent(sprite);
+    let point = border_contact(bounds, center, window.width, window.height);
+    let mut manifolds = Vec::new();
+    let mut add = |normal: Vec2, depth: f32| {
+        if depth > 0.0 {
+            manifolds.push(Manifold {
+                normal,
+                depth,
+                point,
+            });
+        }
+    };
+    add(Vec2::new(1.0, 0.0), -bounds.min.x);
+    add(Vec2::new(-1.0, 0.0), bounds.max.x - window.width);
+    add(Vec2::new(0.0, 1.0), -bounds.min.y);
+    add(Vec2::new(0.0, -1.0), bounds.max.y - window.height);
+    manifolds
+}
+
+/// Returns the box around the collider of a sprite and the center of the collider.
+fn extent(sprite: &Sprite) -> (Aabb, Vec2) {
+    match sprite.collider {
+        Collider::Sprite => {
+            let half = Vec2::new(sprite.size.width / 2.0, sprite.size.height / 2.0);
+            (sprite.bounds(), Vec2::from(sprite.pos) + half)
+        }
+        _ => {
+            let shape = sprite.shape();
+            (shape.bounds(), shape.center())
+        }
+    }
+}
+
+/// Pushes two sprites apart and bounces them off each other.
+///
+/// Only dynamic sprites are moved, in inverse proportion to their mass: two dynamic
+/// sprites of the same mass share the correction and the impulse equally, a dynamic
+/// sprite touching a static or kinematic one takes all of it. The velocities change
+/// only if the sprites move towards each other; the speed along the normal is then
+/// reflected and scaled by the larger restitution, and friction slows their sliding
+/// along the contact.
+///
+/// # Arguments
+///
+/// * `a` - The sprite the normal points to.
+/// * `b` - The other sprite.
+/// * `manifold` - Their overlap, see [`manifold`]. A depth of zero only bounces.
+pub fn resolve(a: &mut Sprite, b: &mut Sprite, manifold: &Manifold) {
+    let (inverse_a, inverse_b) = (a.inverse_mass(), b.inverse_mass());
+    let total = inverse_a + inverse_b;
+    if total == 0.0 {
+        return;
+    }
+    let normal = manifold.normal;
+
+    let correction = normal * (manifold.depth / total);
+    a.pos = (Vec2::from(a.pos) + correction * inverse_a).into();
+    b.pos = (Vec2::from(b.pos) - correction * inverse_b).into();
+
+    let relative = motion(a) - motion(b);
+    if relative.dot(normal) < 0.0 {
+        let restitution = a.restitution.max(b.restitution);
+        let friction = (a.friction * b.friction).sqrt();
+        let impulse = contact_impulse(relative, normal, restitution, friction) * (1.0 / total);
+        a.velocity = (Vec2::from(a.velocity) + impulse * inverse_a).into();
+        b.velocity = (Vec2::from(b.velocity) - impulse * inverse_b).into();
+    }
+}
+
+/// Returns the impulse, per unit of inverse mass, that bounces the relative velocity
+/// `relative` off a surface with `normal`: the reflected normal speed scaled by the
+/// restitution, and against the sliding at most `friction` times that (Coulomb
+/// friction), which can stop the sliding but not reverse it.
+fn contact_impulse(relative: Vec2, normal: Vec2, restitution: f32, friction: f32) -> Vec2 {
+    let approach = relative.dot(normal);
+    let normal_impulse = -(1.0 + restitution) * approach;
+    let sliding = relative - normal * approach;
+    let speed = sliding.length();
+    let friction_impulse = if speed > 0.0 {
+        sliding * -(speed.min(friction * normal_impulse) / speed)
+    } else {
+        Vec2::ZERO
+    };
+    normal * normal_impulse + friction_impulse
+}
+
+/// Pushes a dynamic sprite back into the window and bounces it off the border of
+/// `manifold`, like [`resolve`] with a static border.
+pub fn resolve_border(sprite: &mut Sprite, manifold: &Manifold) {
+    if !sprite.body.is_dynamic() {
+        return;
+    }
+    let normal = manifold.normal;
+    sprite.pos = (Vec2::from(sprite.pos) + normal * manifold.depth).into();
+
+    let velocity = Vec2::from(sprite.velocity);
+    if velocity.dot(normal) < 0.0 {
+        let impulse = contact_impulse(velocity, normal, sprite.restitution, sprite.friction);
+        sprite.velocity = (velocity + impulse).into();
+    }
+}
+
+/// Returns `true` if two sprites bounce off each other when they touch: their layers
+/// and masks match, neither is a trigger, and at least one is dynamic.
+pub(crate) fn collides(a: &Sprite, b: &Sprite) -> bool {
+    a.interacts_with(b) && !a.trigger && !b.trigger && (a.body.is_dynamic() || b.body.is_dynamic())
+}
+
+/// Detects and resolves collisions between sprites and with the window borders.
+///
+/// Overlapping sprites are pushed apart and bounced with [`resolve`], dynamic sprites
+/// beyond a border are pushed back and bounced with [`resolve_border`]. Pairs without
+/// a dynamic sprite do not collide, and neither do sprites whose layers and masks
+/// exclude each other, see [`Sprite::interacts_with`]. Overlaps with a trigger are
+/// only reported. Only pairs whose bounds share a cell of the world's broad phase, see
+/// [`crate::broad_phase`], are tested.
+///
+/// # Arguments
+///
+/// * `world` - Mutable reference to the game world containing all sprites.
+/// * `_dt` - The time delta since the last update (unused).
+///
+/// # Returns
+///
+/// * An event for every collision, border collisions after sprite collisions. They
+///   are also passed to the world's subscribers.
+pub fn collision(world: &mut World, _dt: f32) -> Vec<CollisionEvent> {
+    let broad_phase = world.sync_broad_phase();
+    // Sorted, so overlaps resolve in the same order in every run.
+    let names: Vec<String> = broad_phase.keys().cloned().collect();
+    let pairs = broad_phase.pairs();
+
+    let mut events = Vec::new();
+
+    for (name_a, name_b) in &pairs {
+        let [Some(a), Some(b)] = world.sprites.get_disjoint_mut([name_a, name_b]) else {
+            continue;
+        };
+        let trigger = a.trigger || b.trigger;
+        let reported = if trigger {
+            a.interacts_with(b)
+        } else {
+            collides(a, b)
+        };
+        if !reported {
+            continue;
+        }
+        if let Some(manifold) = manifold(a, b) {
+            if !trigger {
+                resolve(a, b, &manifold);
+            }
+            events.push(CollisionEvent {
+                sprite: name_a.clone(),
+                other: Touched::Sprite(name_b.clone()),
+                normal: manifold.normal,
+                point: manifold.point,
+                trigger,
+            });
+        }
+    }
+
+    // collision with window borders
+    let window = world.window;
+    for name in &names {
+        let sprite = world.sprites.get_mut(name).unwrap();
+        if !sprite.touches_borders() {
+            continue;
+        }
+        for manifold in border_manifolds(sprite, window) {
+            resolve_border(sprite, &manifold);
+            events.push(CollisionEvent {
+                sprite: name.clone(),
+                other: Touched::Border(Border::from_normal(manifold.normal)),
+                normal: manifold.normal,
+                point: manifold.point,
+                trigger: false,
+            });
+        }
+    }
+
+    world.publish(&events);
+    events
+}
+
+/// Overlap of a trigger sprite entity with another sprite entity, found by
+/// [`trigger_system`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEvent {
+    /// The entity whose sprite is a trigger.
+    pub trigger: Entity,
+    pub other: Entity,
+}
+
+/// Resource of an [`ecs::World`] with the overlaps found by the last run of
+/// [`trigger_system`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriggerEvents(pub Vec<TriggerEvent>);
+
+/// System that finds the entities whose [`Sprite`] overlaps a trigger sprite and stores
+/// them in the [`TriggerEvents`] resource, replacing the events of the last run.
+///
+/// Sprite entities are not pushed apart or bounced, so this only reports. Layers and
+/// masks apply as in [`collision`]. Two overlapping triggers are reported once, with
+/// the older entity as the trigger.
+///
+/// # Arguments
+///
+/// * `world` - Mutable reference to the ECS world.
+/// * `_dt` - The time delta since the last update (unused).
+pub fn trigger_system(world: &mut ecs::World, _dt: f32) {
+    let mut bounds = SpatialHash::default();
+    world.query::<&Sprite>(|entity, sprite| bounds.insert(entity, sprite.collider_bounds()));
+
+    let mut events = Vec::new();
+    for (a, b) in bounds.pairs() {
+        let (Some(sprite_a), Some(sprite_b)) = (world.get::<Sprite>(a), world.get::<Sprite>(b))
+        else {
+            continue;
+        };
+        if (sprite_a.trigger || sprite_b.trigger)
+            && sprite_a.interacts_with(&sprite_b)
+            && intersects(&sprite_a, &sprite_b)
+        {
+            let (trigger, other) = if sprite_a.trigger { (a, b) } else { (b, a) };
+            events.push(TriggerEvent { trigger, other });
+        }
+    }
+    world.insert_resource(TriggerEvents(events));
+}
+
+/// Returns the center of the overlapping area of the collider boxes of two intersecting
+/// sprites, or of the shared edge of two touching ones.
+pub(crate) fn overlap_center(a: &Sprite, b: &Sprite) -> Pos {
+    let (a, b) = (a.collider_bounds(), b.collider_bounds());
+    let left = a.min.x.max(b.min.x);
+    let right = a.max.x.min(b.max.x);
+    let top = a.min.y.max(b.min.y);
+    let bottom = a.max.y.min(b.max.y);
+    Pos {
+        x: (left + right) / 2.0,
+        y: (top + bottom) / 2.0,
+    }
+}
+
+/// Returns the point on the window border hit by a collider that crossed it.
+///
+/// On the axis of the crossed border the coordinate is the border itself, on the
+/// other axis it is the collider center.
+fn border_contact(bounds: Aabb, center: Vec2, width: f32, height: f32) -> Pos {
+    Pos {
+        x: if bounds.min.x < 0.0 {
+            0.0
+        } else if bounds.max.x > width {
+            width
+        } else {
+            center.x
+        },
+        y: if bounds.min.y < 0.0 {
+            0.0
+        } else if bounds.max.y > height {
+            height
+        } else {
+            center.y
+        },
+    }
+}
+
+/// Checks if two sprites intersect (axis-aligned bounding box collision, or the
+/// separating-axis test for other colliders).
+///
+/// # Arguments
+///
+/// * `a` - Reference to the first sprite.
+/// * `b` - Reference to the second sprite.
+///
+/// # Returns
+///
+/// * `true` if the sprites intersect, otherwise `false`.
+pub fn intersects(a: &Sprite, b: &Sprite) -> bool {
+    if a.collider != Collider::Sprite || b.collider != Collider::Sprite {
+        return collider::manifold(&a.shape(), &b.shape()).is_some();
+    }
+    a.pos.x < b.pos.x + b.size.width
+        && a.pos.x + a.size.width > b.pos.x
+        && a.pos.y < b.pos.y + b.size.height
+        && a.pos.y + a.size.height > b.pos.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::{BORDER_LAYER, Color, Pos, Size, Velocity};
+    use crate::world::World;
+
+    fn make_sprite(pos: Pos, velocity: Velocity, size: Size) -> crate::sprite::Sprite {
+        let color = Color { r: 0, g: 0, b: 0 };
+        crate::sprite::Sprite::new(pos, velocity, color, size)
+    }
+
+    #[test]
+    fn test_intersects_true() {
+        let a = make_sprite(
+            Pos { x: 0.0, y: 0.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        let b = make_sprite(
+            Pos { x: 5.0, y: 5.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        assert!(intersects(&a, &b));
+    }
+
+    #[test]
+    fn test_intersects_false() {
+        let a = make_sprite(
+            Pos { x: 0.0, y: 0.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        let b = make_sprite(
+            Pos { x: 20.0, y: 20.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 5.0, height: 5.0 },
+        );
+        assert!(!intersects(&a, &b));
+    }
+
+    /// Verify that a fast sprite hits a thin one it would jump over in one step.
+    #[test]
+    fn test_sweep_hits_thin_sprite() {
+        let ball = make_sprite(
+            Pos { x: 0.0, y: 0.0 },
+            Velocity { dx: 2.0, dy: 0.5 },
+            Size { width: 30.0, height: 30.0 },
+        );
+        let paddle = make_sprite(
+            Pos { x: 100.0, y: 0.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 5.0, height: 200.0 },
+        );
+
+        let hit = sweep(&ball, &paddle, 100.0).unwrap();
+        assert_eq!(hit.time, 35.0);
+        assert_eq!(hit.normal, Vec2::new(-1.0, 0.0));
+        // Seen from the paddle, the ball comes from the left.
+        assert_eq!(sweep(&paddle, &ball, 100.0).unwrap().normal, Vec2::new(1.0, 0.0));
+
+        assert_eq!(sweep(&ball, &paddle, 30.0), None);
+        let mut away = make_sprite(ball.pos, Velocity { dx: -2.0, dy: 0.0 }, ball.size);
+        assert_eq!(sweep(&away, &paddle, 100.0), None);
+        away.pos.y = 300.0;
+        away.velocity.dx = 2.0;
+        assert_eq!(sweep(&away, &paddle, 100.0), None);
+        let overlapping = make_sprite(Pos { x: 90.0, y: 0.0 }, ball.velocity, ball.size);
+        assert_eq!(sweep(&overlapping, &paddle, 100.0), None);
+    }
+
+    /// Verify that the border sweep finds the first border on the way.
+    #[test]
+    fn test_sweep_border() {
+        let window = Size {
+            width: 100.0,
+            height: 100.0,
+        };
+        let sprite = make_sprite(
+            Pos { x: 50.0, y: 80.0 },
+            Velocity { dx: 1.0, dy: 1.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        assert_eq!(
+            sweep_border(&sprite, window, 50.0),
+            Some(Hit {
+                time: 10.0,
+                normal: Vec2::new(0.0, -1.0)
+            })
+        );
+        assert_eq!(sweep_border(&sprite, window, 5.0), None);
+        assert_eq!(
+            face_center(&sprite, Vec2::new(0.0, -1.0)),
+            Pos { x: 55.0, y: 90.0 }
+        );
+    }
+
+    #[test]
+    fn test_collision_between_sprites() {
+        let mut world = World::empty();
+        let velocity = Velocity { dx: 0.0, dy: 0.0 };
+        let velocity2 = Velocity { dx: 0.0, dy: 0.0 };
+        let size = Size { width: 10.0, height: 10.0 };
+        let size2 = Size { width: 10.0, height: 10.0 };
+        world.sprites.insert(
+            "a".to_string(),
+            make_sprite(Pos { x: 0.0, y: 0.0 }, velocity, size),
+        );
+        world.sprites.insert(
+            "b".to_string(),
+            make_sprite(Pos { x: 5.0, y: 5.0 }, velocity2, size2),
+        );
+        let events = collision(&mut world, 0.0);
+        assert_eq!(
+            events[0],
+            CollisionEvent {
+                sprite: "a".to_string(),
+                other: Touched::Sprite("b".to_string()),
+                normal: Vec2::new(-1.0, 0.0),
+                point: Pos { x: 7.5, y: 7.5 },
+                trigger: false,
+            }
+        );
+    }
+
+    /// Verify that separating two sprites away from the border pushes both apart, and
+    /// that a sprite pushed against the border stays there and reports the contact
+    /// while the other one takes the whole push.
+    #[test]
+    fn test_collision_between_sprites_at_border() {
+        let size = Size {
+            width: 10.0,
+            height: 10.0,
+        };
+        let still = Velocity { dx: 0.0, dy: 0.0 };
+        let overlapping = |a: Pos, b: Pos| {
+            let mut world = World::empty();
+            for (name, pos) in [("a", a), ("b", b)] {
+                world
+                    .sprites
+                    .insert(name.to_string(), make_sprite(pos, still, size));
+            }
+            world
+        };
+
+        let mut world = overlapping(Pos { x: 100.0, y: 100.0 }, Pos { x: 105.0, y: 105.0 });
+        let events = collision(&mut world, 0.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(world.sprites["a"].pos, Pos { x: 97.5, y: 100.0 });
+        assert_eq!(world.sprites["b"].pos, Pos { x: 107.5, y: 105.0 });
+
+        let mut world = overlapping(Pos { x: 0.0, y: 0.0 }, Pos { x: 5.0, y: 5.0 });
+        let events = collision(&mut world, 0.0);
+        assert_eq!(
+            events[1],
+            CollisionEvent {
+                sprite: "a".to_string(),
+                other: Touched::Border(Border::Left),
+                normal: Vec2::new(1.0, 0.0),
+                point: Pos { x: 0.0, y: 5.0 },
+                trigger: false,
+            }
+        );
+        assert_eq!(events.len(), 2);
+        assert_eq!(world.sprites["a"].pos, Pos { x: 0.0, y: 0.0 });
+        assert_eq!(world.sprites["b"].pos, Pos { x: 7.5, y: 5.0 });
+    }
+
+    /// Verify the manifold of two overlapping sprites.
+    #[test]
+    fn test_manifold() {
+        let still = Velocity { dx: 0.0, dy: 0.0 };
+        let a = make_sprite(
+            Pos { x: 0.0, y: 8.0 },
+            still,
+            Size {
+                width: 10.0,
+                height: 10.0,
+            },
+        );
+        let b = make_sprite(
+            Pos { x: 2.0, y: 0.0 },
+            still,
+            Size {
+                width: 20.0,
+                height: 10.0,
+            },
+        );
+        assert_eq!(
+            manifold(&a, &b),
+            Some(Manifold {
+                normal: Vec2::new(0.0, 1.0),
+                depth: 2.0,
+                point: Pos { x: 6.0, y: 9.0 },
+            })
+        );
+        assert_eq!(manifold(&b, &a).unwrap().normal, Vec2::new(0.0, -1.0));
+        let apart = make_sprite(Pos { x: 0.0, y: 10.0 }, still, b.size);
+        assert_eq!(manifold(&b, &apart), None);
+    }
+
+    /// Verify the response: reflection along the normal only, restitution, and that
+    /// only dynamic sprites move.
+    #[test]
+    fn test_resolve() {
+        let size = Size {
+            width: 10.0,
+            height: 10.0,
+        };
+        let manifold = Manifold {
+            normal: Vec2::new(0.0, -1.0),
+            depth: 2.0,
+            point: Pos { x: 5.0, y: 10.0 },
+        };
+
+        // A ball grazing the top of a paddle only turns around vertically.
+        let mut ball = make_sprite(Pos { x: 0.0, y: 0.0 }, Velocity { dx: 3.0, dy: 1.0 }, size);
+        let mut paddle = make_sprite(Pos { x: 0.0, y: 8.0 }, Velocity { dx: 0.0, dy: 0.0 }, size);
+        paddle.body = BodyKind::Kinematic;
+        resolve(&mut ball, &mut paddle, &manifold);
+        assert_eq!(ball.pos, Pos { x: 0.0, y: -2.0 });
+        assert_eq!(ball.velocity, Velocity { dx: 3.0, dy: -1.0 });
+        assert_eq!(paddle.pos, Pos { x: 0.0, y: 8.0 });
+
+        // Moving apart already: pushed out, but the velocity is kept.
+        resolve(&mut ball, &mut paddle, &manifold);
+        assert_eq!(ball.velocity, Velocity { dx: 3.0, dy: -1.0 });
+
+        // Two dynamic sprites of which one is half elastic swap their speed along the
+        // normal, losing half of it.
+        let mut a = make_sprite(Pos { x: 0.0, y: 0.0 }, Velocity { dx: 0.0, dy: 2.0 }, size);
+        let mut b = make_sprite(Pos { x: 0.0, y: 8.0 }, Velocity { dx: 0.0, dy: 0.0 }, size);
+        a.restitution = 0.0;
+        b.restitution = 0.5;
+        resolve(&mut a, &mut b, &manifold);
+        assert_eq!(a.pos, Pos { x: 0.0, y: -1.0 });
+        assert_eq!(b.pos, Pos { x: 0.0, y: 9.0 });
+        assert_eq!(a.velocity, Velocity { dx: 0.0, dy: 0.5 });
+        assert_eq!(b.velocity, Velocity { dx: 0.0, dy: 1.5 });
+
+        // Nothing moves two sprites that are not dynamic.
+        a.body = BodyKind::Static;
+        b.body = BodyKind::Kinematic;
+        resolve(&mut a, &mut b, &manifold);
+        assert_eq!(a.pos, Pos { x: 0.0, y: -1.0 });
+    }
+
+    #[test]
+    fn test_collision_with_border() {
+        let mut world = World::empty();
+        let velocity = Velocity { dx: 5.0, dy: 0.0 };
+        let size = Size { width: 10.0, height: 10.0 };
+        // Place sprite near right border
+        let pos = Pos { x: world.window.width + 9.0, y: 100.0 };
+        world.sprites.insert(
+            "border".to_string(),
+            make_sprite(pos, velocity, size),
+        );
+        let events = collision(&mut world, 0.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind(), CollisionType::WithBorder);
+        assert_eq!(events[0].border(), Some(Border::Right));
+        assert!(events[0].involves("border"));
+        // Contact on the right border at the height of the sprite center
+        assert_eq!(
+            events[0].point,
+            Pos {
+                x: world.window.width,
+                y: 105.0
+            }
+        );
+        let sprite = &world.sprites["border"];
+        // Should be clamped within window
+        assert!(sprite.pos.x <= world.window.width - sprite.size.width);
+        // Velocity should be inverted
+        assert_eq!(sprite.velocity.dx, -5.0);
+    }
+
+    /// Verify that the lighter sprite bounces more, and friction slows sliding along a
+    /// border without reversing it.
+    #[test]
+    fn test_resolve_mass_and_friction() {
+        let mut a = make_sprite(
+            Pos { x: 0.0, y: 0.0 },
+            Velocity { dx: 1.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        let mut b = make_sprite(
+            Pos { x: 10.0, y: 0.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        b.mass = 3.0;
+        let touching = Manifold {
+            normal: Vec2::new(-1.0, 0.0),
+            depth: 0.0,
+            point: Pos { x: 10.0, y: 5.0 },
+        };
+        resolve(&mut a, &mut b, &touching);
+        assert_eq!(a.velocity, Velocity { dx: -0.5, dy: 0.0 });
+        assert_eq!(b.velocity, Velocity { dx: 0.5, dy: 0.0 });
+
+        a.velocity = Velocity { dx: 1.0, dy: 0.5 };
+        a.restitution = 0.0;
+        a.friction = 0.5;
+        let floor = Manifold {
+            normal: Vec2::new(0.0, -1.0),
+            depth: 0.0,
+            point: Pos { x: 5.0, y: 10.0 },
+        };
+        resolve_border(&mut a, &floor);
+        assert_eq!(a.velocity, Velocity { dx: 0.75, dy: 0.0 });
+    }
+
+    /// Verify that masks keep sprites apart, triggers only report, and sprites without
+    /// the border layer leave the window.
+    #[test]
+    fn test_layers_and_triggers() {
+        let mut world = World::empty();
+        for (name, x) in [("a", 100.0), ("b", 105.0), ("c", 98.0)] {
+            world.sprites.insert(
+                name.to_string(),
+                make_sprite(
+                    Pos { x, y: 100.0 },
+                    Velocity { dx: 0.0, dy: 0.0 },
+                    Size { width: 10.0, height: 10.0 },
+                ),
+            );
+        }
+        world.sprites.get_mut("b").unwrap().layer = 2;
+        world.sprites.get_mut("a").unwrap().mask = !2;
+        world.sprites.get_mut("c").unwrap().trigger = true;
+
+        let events = collision(&mut world, 0.0);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.trigger && event.involves("c")));
+        assert_eq!(world.sprites["a"].pos, Pos { x: 100.0, y: 100.0 });
+        assert_eq!(world.sprites["b"].pos, Pos { x: 105.0, y: 100.0 });
+
+        let ghost = world.sprites.get_mut("b").unwrap();
+        ghost.pos.x = -5.0;
+        ghost.mask = !BORDER_LAYER;
+        assert!(collision(&mut world, 0.0).iter().all(|event| event.trigger));
+        assert_eq!(world.sprites["b"].pos.x, -5.0);
+    }
+
+    /// Verify that sprites collide with their colliders rather than their rectangles.
+    #[test]
+    fn test_colliders() {
+        let mut world = World::empty();
+        let mut ball = make_sprite(
+            Pos { x: 100.0, y: 100.0 },
+            Velocity { dx: 1.0, dy: 0.0 },
+            Size { width: 16.0, height: 16.0 },
+        );
+        ball.collider = Collider::Circle { radius: 8.0 };
+        let mut block = make_sprite(
+            Pos { x: 114.0, y: 114.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+        );
+        block.body = BodyKind::Static;
+        assert!(!intersects(&ball, &block));
+        world.sprites.insert("ball".to_string(), ball);
+        world.sprites.insert("block".to_string(), block);
+        assert!(collision(&mut world, 0.0).is_empty());
+
+        world.sprites.get_mut("block").unwrap().pos = Pos { x: 110.0, y: 100.0 };
+        let events = collision(&mut world, 0.0);
+        assert_eq!(events[0].normal, Vec2::new(-1.0, 0.0));
+        assert_eq!(events[0].point, Pos { x: 116.0, y: 108.0 });
+        assert_eq!(world.sprites["ball"].pos.x, 94.0);
+        assert_eq!(world.sprites["ball"].velocity.dx, -1.0);
+
+        // Only the circle, not the larger rectangle, is pushed back into the window.
+        let ball = world.sprites.get_mut("ball").unwrap();
+        ball.pos = Pos { x: -10.0, y: 300.0 };
+        ball.size = Size { width: 32.0, height: 32.0 };
+        let events = collision(&mut world, 0.0);
+        assert_eq!(events[0].border(), Some(Border::Left));
+        assert_eq!(world.sprites["ball"].pos.x, -8.0);
+    }
+
+    /// Verify that the trigger system reports sprite entities overlapping a trigger.
+    #[test]
+    fn test_trigger_system() {
+        let mut world = ecs::World::new();
+        let mut spawn = |x: f32, trigger: bool| {
+            let entity = world.spawn();
+            let mut sprite = make_sprite(
+                Pos { x, y: 0.0 },
+                Velocity { dx: 0.0, dy: 0.0 },
+                Size { width: 10.0, height: 10.0 },
+            );
+            sprite.trigger = trigger;
+            world.insert(entity, sprite);
+            entity
+        };
+        let coin = spawn(0.0, true);
+        let player = spawn(5.0, false);
+        spawn(12.0, false);
+
+        trigger_system(&mut world, 16.0);
+        let events = world.resource::<TriggerEvents>().unwrap();
+        assert_eq!(
+            events.0,
+            vec![TriggerEvent {
+                trigger: coin,
+                other: player
+            }]
+        );
+
+        world.get_mut::<Sprite>(player).unwrap().pos.x = 50.0;
+        trigger_system(&mut world, 16.0);
+        assert!(world.resource::<TriggerEvents>().unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+//! Entity-component-system world.
+//!
+//! An [`Entity`] is a generational id; any `'static` type can be attached to it as a
+//! component. Systems are plain functions over the [`World`] that visit entities
+//! with [`World::query`] and run in order in a [`Schedule`]:
+//!
+//! ```
+//! use game_engine::ecs::{Schedule, World};
+//!
+//! struct Health(i32);
+//! struct Poisoned;
+//!
+//! let mut world = World::new();
+//! let entity = world.spawn();
+//! world.insert(entity, Health(10));
+//! world.insert(entity, Poisoned);
+//!
+//! let mut schedule = Schedule::new();
+//! schedule.add_system("poison", |world, _dt| {
+//!     world.query::<(&mut Health, &Poisoned)>(|_, (health, _)| health.0 -= 1);
+//! });
+//! schedule.run(&mut world, 16.0);
+//!
+//! assert_eq!(world.get::<Health>(entity).unwrap().0, 9);
+//! ```
+//!
+//! Besides components the world holds resources: single values of a type that are
+//! shared by all systems, like the window size.
+
+mod entity;
+mod query;
+mod schedule;
+mod storage;
+
+pub use entity::Entity;
+pub use query::Query;
+pub use schedule::{Schedule, System};
+
+use entity::Entities;
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use storage::{AnyStorage, Storage};
+
+/// Entities with their components, and resources.
+#[derive(Default)]
+pub struct World {
+    entities: Entities,
+    components: HashMap<TypeId, Box<dyn AnyStorage>>,
+    resources: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl World {
+    /// Creates a world without entities and resources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an entity without components.
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.alloc()
+    }
+
+    /// Removes an entity and drops all its components.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the entity was not alive.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.entities.free(entity) {
+            return false;
+        }
+        for storage in self.components.values_mut() {
+            storage.clear(entity.index() as usize);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    /// Returns the number of living entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the living entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter()
+    }
+
+    /// Attaches a component to an entity, replacing a component of the same type.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the entity is not alive; the component is dropped.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        self.storage_mut::<T>()
+            .insert(entity.index() as usize, component);
+        true
+    }
+
+    /// Detaches a component from an entity and returns it.
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.components
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<RefCell<Storage<T>>>()?
+            .get_mut()
+            .remove(entity.index() as usize)
+    }
+
+    /// Returns `true` if the entity is alive and has a component of type `T`.
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// Returns a component of an entity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from inside a query that borrows `T` mutably.
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        let storage = <&T as Query>::borrow(self)?;
+        Ref::filter_map(storage, |storage| storage.get(entity.index() as usize)).ok()
+    }
+
+    /// Returns a component of an entity for modification.
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.components
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<RefCell<Storage<T>>>()?
+            .get_mut()
+            .get_mut(entity.index() as usize)
+    }
+
+    /// Calls `f` for every entity that matches the query `Q`, in slot order.
+    ///
+    /// Components are yielded as references: `world.query::<(&mut Pos, &Velocity)>(…)`
+    /// visits all entities with a position and a velocity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `Q` borrows a component type mutably together with any other
+    /// borrow of the same type, e.g. `(&mut Pos, &Pos)`, or if `f` accesses such a
+    /// component through `get`.
+    pub fn query<Q: Query>(&self, mut f: impl FnMut(Entity, Q::Item<'_>)) {
+        let Some(mut borrow) = Q::borrow(self) else {
+            return;
+        };
+        for entity in self.entities.iter() {
+            if let Some(item) = Q::fetch(&mut borrow, entity.index() as usize) {
+                f(entity, item);
+            }
+        }
+    }
+
+    /// Stores a resource, replacing and returning a resource of the same type.
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) -> Option<R> {
+        self.resources
+            .insert(TypeId::of::<R>(), Box::new(resource))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.resources.get(&TypeId::of::<R>())?.downcast_ref()
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut(&TypeId::of::<R>())?.downcast_mut()
+    }
+
+    pub fn remove_resource<R: 'static>(&mut self) -> Option<R> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast().ok())
+            .map(|resource| *resource)
+    }
+
+    /// Returns the storage of component type `T`, if one was created.
+    fn storage<T: 'static>(&self) -> Option<&RefCell<Storage<T>>> {
+        self.components
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref()
+    }
+
+    /// Returns the storage of component type `T`, creating it if needed.
+    fn storage_mut<T: 'static>(&mut self) -> &mut Storage<T> {
+        self.components
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(RefCell::new(Storage::<T>::default())))
+            .as_any_mut()
+            .downcast_mut::<RefCell<Storage<T>>>()
+            .expect("storage has the type of its key")
+            .get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(i32);
+    #[derive(Debug, PartialEq)]
+    struct Vel(i32);
+    struct Tag;
+
+    /// Verify that components can be inserted, read, replaced and removed.
+    #[test]
+    fn test_insert_get_remove() {
+        let mut world = World::new();
+        let entity = world.spawn();
+
+        assert!(world.insert(entity, Pos(1)));
+        assert_eq!(*world.get::<Pos>(entity).unwrap(), Pos(1));
+        world.insert(entity, Pos(2));
+        world.get_mut::<Pos>(entity).unwrap().0 += 1;
+        assert_eq!(*world.get::<Pos>(entity).unwrap(), Pos(3));
+
+        assert!(!world.has::<Vel>(entity));
+        assert_eq!(world.remove::<Pos>(entity), Some(Pos(3)));
+        assert!(!world.has::<Pos>(entity));
+    }
+
+    /// Verify that despawning drops the components and stale ids see nothing.
+    #[test]
+    fn test_despawn() {
+        let mut world = World::new();
+        let old = world.spawn();
+        world.insert(old, Pos(1));
+
+        assert!(world.despawn(old));
+        let new = world.spawn();
+
+        assert_eq!(new.index(), old.index());
+        assert!(!world.is_alive(old));
+        assert!(!world.has::<Pos>(new));
+        assert!(!world.insert(old, Pos(5)));
+        assert!(world.get::<Pos>(old).is_none());
+        assert_eq!(world.len(), 1);
+    }
+
+    /// Verify that tuple queries visit only entities with all components.
+    #[test]
+    fn test_tuple_query() {
+        let mut world = World::new();
+        let moving = world.spawn();
+        world.insert(moving, Pos(0));
+        world.insert(moving, Vel(2));
+        let fixed = world.spawn();
+        world.insert(fixed, Pos(5));
+        let tagged = world.spawn();
+        world.insert(tagged, Pos(1));
+        world.insert(tagged, Vel(-1));
+        world.insert(tagged, Tag);
+
+        world.query::<(&mut Pos, &Vel)>(|_, (pos, vel)| pos.0 += vel.0);
+        let mut visited = Vec::new();
+        world.query::<(&Tag, &Pos)>(|entity, (_, pos)| visited.push((entity, pos.0)));
+
+        assert_eq!(*world.get::<Pos>(moving).unwrap(), Pos(2));
+        assert_eq!(*world.get::<Pos>(fixed).unwrap(), Pos(5));
+        assert_eq!(visited, [(tagged, 0)]);
+    }
+
+    /// Verify that optional components match entities with and without them.
+    #[test]
+    fn test_optional_query() {
+        let mut world = World::new();
+        let a = world.spawn();
+        world.insert(a, Pos(1));
+        let b = world.spawn();
+        world.insert(b, Pos(2));
+        world.insert(b, Vel(3));
+
+        let mut visited = Vec::new();
+        world.query::<(&Pos, Option<&Vel>)>(|_, (pos, vel)| {
+            visited.push((pos.0, vel.map(|vel| vel.0)))
+        });
+        assert_eq!(visited, [(1, None), (2, Some(3))]);
+
+        // A component type that was never inserted matches nothing.
+        let mut count = 0;
+        world.query::<&Tag>(|_, _| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    /// Verify that borrowing the same component mutably twice panics.
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn test_conflicting_query() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.insert(entity, Pos(1));
+        world.query::<(&mut Pos, &Pos)>(|_, _| {});
+    }
+
+    /// Verify that resources are stored per type.
+    #[test]
+    fn test_resources() {
+        let mut world = World::new();
+        assert_eq!(world.insert_resource(3u32), None);
+        assert_eq!(world.insert_resource(4u32), Some(3));
+        *world.resource_mut::<u32>().unwrap() += 1;
+
+        assert_eq!(world.resource::<u32>(), Some(&5));
+        assert_eq!(world.resource::<i64>(), None);
+        assert_eq!(world.remove_resource::<u32>(), Some(5));
+        assert_eq!(world.resource::<u32>(), None);
+    }
+}
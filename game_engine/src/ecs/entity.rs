@@ -0,0 +1,121 @@
+//! Generational entity ids.
+
+/// Handle of an entity in an [`super::World`].
+///
+/// The index of a despawned entity is reused by later spawns with a higher
+/// generation, so a stale handle never refers to the new entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    /// Returns the slot of the entity in the component storages.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns how often the slot was reused before this entity.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Allocator of entity ids.
+#[derive(Default)]
+pub(crate) struct Entities {
+    /// Current generation of each slot.
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    /// Slots of despawned entities, reused last in first out.
+    free: Vec<u32>,
+}
+
+impl Entities {
+    /// Returns a new entity, reusing a free slot if there is one.
+    pub fn alloc(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = index as usize;
+                self.generations[slot] += 1;
+                self.alive[slot] = true;
+                Entity {
+                    index,
+                    generation: self.generations[slot],
+                }
+            }
+            None => {
+                let index = u32::try_from(self.generations.len()).expect("too many entities");
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Frees the slot of `entity`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the entity was not alive.
+    pub fn free(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        self.alive[entity.index as usize] = false;
+        self.free.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let slot = entity.index as usize;
+        self.alive.get(slot).copied().unwrap_or(false)
+            && self.generations[slot] == entity.generation
+    }
+
+    /// Returns the number of living entities.
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+
+    /// Returns the living entities in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.generations
+            .iter()
+            .zip(&self.alive)
+            .enumerate()
+            .filter(|(_, (_, alive))| **alive)
+            .map(|(index, (generation, _))| Entity {
+                index: index as u32,
+                generation: *generation,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that freed slots are reused with a new generation.
+    #[test]
+    fn test_generational_reuse() {
+        let mut entities = Entities::default();
+        let a = entities.alloc();
+        let b = entities.alloc();
+
+        assert!(entities.free(a));
+        assert!(!entities.free(a));
+        let c = entities.alloc();
+
+        assert_eq!(c.index(), a.index());
+        assert_eq!(c.generation(), a.generation() + 1);
+        assert!(!entities.is_alive(a));
+        assert!(entities.is_alive(c));
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities.iter().collect::<Vec<_>>(), [c, b]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Queries over component tuples.
+
+use super::World;
+use super::storage::Storage;
+use std::any::type_name;
+use std::cell::{Ref, RefMut};
+
+/// A set of components that [`World::query`] visits for every entity that has them.
+///
+/// Implemented for `&T`, `&mut T`, `Option<Q>` (for components an entity may lack)
+/// and tuples of up to four queries, e.g. `(&mut Sprite, &Player, Option<&Velocity>)`.
+///
+/// A query borrows each component storage for its whole run, so one query cannot
+/// name the same component type twice if one of them is `&mut`.
+pub trait Query {
+    /// Borrow of the component storages the query reads.
+    type Borrow<'w>;
+    /// What the query yields for one entity.
+    type Item<'b>;
+
+    /// Borrows the storages from `world`.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if a required component type was never inserted, so nothing matches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a storage is already borrowed in a conflicting way.
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>>;
+
+    /// Returns the components of the entity in slot `index`, or `None` if it lacks one.
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>>;
+}
+
+impl<T: 'static> Query for &T {
+    type Borrow<'w> = Ref<'w, Storage<T>>;
+    type Item<'b> = &'b T;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        let storage = world.storage::<T>()?;
+        Some(storage.try_borrow().unwrap_or_else(|_| {
+            panic!("component {} is already borrowed mutably", type_name::<T>())
+        }))
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        borrow.get(index)
+    }
+}
+
+impl<T: 'static> Query for &mut T {
+    type Borrow<'w> = RefMut<'w, Storage<T>>;
+    type Item<'b> = &'b mut T;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        let storage = world.storage::<T>()?;
+        Some(
+            storage
+                .try_borrow_mut()
+                .unwrap_or_else(|_| panic!("component {} is already borrowed", type_name::<T>())),
+        )
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        borrow.get_mut(index)
+    }
+}
+
+impl<Q: Query> Query for Option<Q> {
+    type Borrow<'w> = Option<Q::Borrow<'w>>;
+    type Item<'b> = Option<Q::Item<'b>>;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        Some(Q::borrow(world))
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        Some(borrow.as_mut().and_then(|borrow| Q::fetch(borrow, index)))
+    }
+}
+
+/// Implements [`Query`] for a tuple of queries, matching entities that match all of them.
+macro_rules! impl_query_tuple {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: Query),+> Query for ($($name,)+) {
+            type Borrow<'w> = ($($name::Borrow<'w>,)+);
+            type Item<'b> = ($($name::Item<'b>,)+);
+
+            fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+                Some(($($name::borrow(world)?,)+))
+            }
+
+            fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+                Some(($($name::fetch(&mut borrow.$index, index)?,)+))
+            }
+        }
+    };
+}
+
+impl_query_tuple!(A: 0);
+impl_query_tuple!(A: 0, B: 1);
+impl_query_tuple!(A: 0, B: 1, C: 2);
+impl_query_tuple!(A: 0, B: 1, C: 2, D: 3);
@@ -0,0 +1,93 @@
+//! Systems run in a fixed order.
+
+use super::World;
+
+/// A system: a function over the world and the time delta in milliseconds.
+pub type System = Box<dyn FnMut(&mut World, f32)>;
+
+/// Named systems that run one after another in the order they were added.
+#[derive(Default)]
+pub struct Schedule {
+    systems: Vec<(String, System)>,
+}
+
+impl Schedule {
+    /// Creates a schedule without systems.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a system that runs after all systems added before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name used to remove the system again; need not be unique.
+    /// * `system` - The system, e.g. [`crate::movement::move_system`].
+    pub fn add_system(
+        &mut self,
+        name: &str,
+        system: impl FnMut(&mut World, f32) + 'static,
+    ) -> &mut Self {
+        self.systems.push((String::from(name), Box::new(system)));
+        self
+    }
+
+    /// Removes all systems with the given name.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a system was removed.
+    pub fn remove_system(&mut self, name: &str) -> bool {
+        let len = self.systems.len();
+        self.systems.retain(|(system, _)| system != name);
+        self.systems.len() != len
+    }
+
+    /// Returns the names of the systems in run order.
+    pub fn system_names(&self) -> impl Iterator<Item = &str> {
+        self.systems.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Runs every system once, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world the systems work on.
+    /// * `dt` - The time delta since the last run (in milliseconds).
+    pub fn run(&mut self, world: &mut World, dt: f32) {
+        for (_, system) in &mut self.systems {
+            system(world, dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that systems run in insertion order and can be removed by name.
+    #[test]
+    fn test_run_order() {
+        let mut world = World::new();
+        world.insert_resource(Vec::<&str>::new());
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system("first", |world, _| {
+                world.resource_mut::<Vec<&str>>().unwrap().push("first")
+            })
+            .add_system("second", |world, _| {
+                world.resource_mut::<Vec<&str>>().unwrap().push("second")
+            });
+
+        schedule.run(&mut world, 1.0);
+        assert!(schedule.remove_system("first"));
+        assert!(!schedule.remove_system("first"));
+        schedule.run(&mut world, 1.0);
+
+        assert_eq!(
+            world.resource::<Vec<&str>>().unwrap(),
+            &["first", "second", "second"]
+        );
+        assert_eq!(schedule.system_names().collect::<Vec<_>>(), ["second"]);
+    }
+}
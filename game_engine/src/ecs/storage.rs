@@ -0,0 +1,64 @@
+//! Typed component storage.
+
+use std::any::Any;
+use std::cell::RefCell;
+
+/// Components of one type, indexed by entity slot.
+///
+/// Public only because [`super::Query`] borrows name it; the module is private.
+pub struct Storage<T> {
+    items: Vec<Option<T>>,
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> Storage<T> {
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index)?.as_mut()
+    }
+
+    /// Stores `component` at `index` and returns the component it replaced.
+    pub fn insert(&mut self, index: usize, component: T) -> Option<T> {
+        if index >= self.items.len() {
+            self.items.resize_with(index + 1, || None);
+        }
+        self.items[index].replace(component)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.items.get_mut(index)?.take()
+    }
+}
+
+/// Storage with the component type erased, so the world can keep all storages in one map.
+///
+/// Each storage lives in a `RefCell`, which lets queries borrow several storages of
+/// different types at once.
+pub(crate) trait AnyStorage {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Drops the component at `index`, if there is one.
+    fn clear(&mut self, index: usize);
+}
+
+impl<T: 'static> AnyStorage for RefCell<Storage<T>> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clear(&mut self, index: usize) {
+        self.get_mut().remove(index);
+    }
+}
@@ -0,0 +1,108 @@
+//! Engine context token.
+//!
+//! An [`Engine`] only exists after the game window was created successfully.
+//! Every render, input and window function in [`crate::ffi`] requires a borrow of it,
+//! so using the C library before the window exists does not compile:
+//!
+//! ```compile_fail
+//! game_engine::ffi::rust_clear_screen();
+//! ```
+//!
+//! The OpenGL context belongs to the thread that created the window, so `Engine`
+//! is neither `Send` nor `Sync` and cannot be moved into worker threads:
+//!
+//! ```compile_fail
+//! fn assert_send<T: Send>() {}
+//! assert_send::<game_engine::Engine>();
+//! ```
+
+use crate::ffi::GLFWwindow;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once the C library created its (single, global) window.
+static WINDOW_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Errors that can occur while creating the engine context.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EngineError {
+    /// The window title contains an interior NUL byte.
+    InvalidTitle,
+    /// A window was already created; the C library only supports one.
+    AlreadyCreated,
+    /// The C library returned no window.
+    WindowCreationFailed,
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::InvalidTitle => write!(f, "window title contains a NUL byte"),
+            EngineError::AlreadyCreated => write!(f, "game window was already created"),
+            EngineError::WindowCreationFailed => write!(f, "could not create game window"),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Proof that the game window exists.
+///
+/// Obtained from [`crate::ffi::rust_create_game_window`]. Holds the raw window
+/// pointer, which also makes the type `!Send` and `!Sync`.
+pub struct Engine {
+    window: *mut GLFWwindow,
+}
+
+impl Engine {
+    /// Reserves the single window slot of the C library.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(EngineError::AlreadyCreated)` if a window was already created.
+    pub(crate) fn claim_window() -> Result<(), EngineError> {
+        if WINDOW_CREATED.swap(true, Ordering::SeqCst) {
+            return Err(EngineError::AlreadyCreated);
+        }
+        Ok(())
+    }
+
+    /// Wraps the window pointer returned by the C library.
+    ///
+    /// A null window gives the slot reserved by [`Engine::claim_window`] back, so
+    /// creating the window can be tried again.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(EngineError::WindowCreationFailed)` if `window` is null.
+    pub(crate) fn from_window(window: *mut GLFWwindow) -> Result<Self, EngineError> {
+        if window.is_null() {
+            WINDOW_CREATED.store(false, Ordering::SeqCst);
+            return Err(EngineError::WindowCreationFailed);
+        }
+        Ok(Self { window })
+    }
+
+    /// Returns the pointer to the main game window. Never null.
+    pub fn window(&self) -> *mut GLFWwindow {
+        self.window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a failed window creation does not keep the window slot, so a retry
+    /// fails for its own reason instead of with `AlreadyCreated`.
+    #[test]
+    fn test_failed_creation_releases_window() {
+        Engine::claim_window().unwrap();
+        assert_eq!(
+            Engine::from_window(std::ptr::null_mut()).err(),
+            Some(EngineError::WindowCreationFailed)
+        );
+        assert_eq!(Engine::claim_window(), Ok(()));
+        WINDOW_CREATED.store(false, Ordering::SeqCst);
+    }
+}
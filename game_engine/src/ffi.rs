@@ -4,85 +4,49 @@
 //! with the underlying game engine, including window management, sprite creation,
 //! rendering, and input handling.
 
+use crate::bindings::*;
+use crate::engine::{Engine, EngineError};
 use std::ffi::CString;
-use std::os::raw::{c_char, c_float, c_int};
 
 /// Represents a sprite in the game engine.
 ///
+/// Generated from `opengl_wrapper_lib.h` by `build.rs`.
+///
 /// # Fields
 /// - `width`, `height`: Dimensions of the sprite.
 /// - `color`: RGB color as an array of three integers.
 /// - `x`, `y`: Position of the sprite.
-#[repr(C)]
-pub struct Sprite {
-    pub width: c_int,
-    pub height: c_int,
-    pub color: [c_int; 3],
-    pub x: c_float,
-    pub y: c_float,
-}
+pub use crate::bindings::Sprite;
 
 /// Opaque type representing a GLFW window.
-#[repr(C)]
-pub struct GLFWwindow {
-    _private: [u8; 0], // opaque type
-}
+pub use crate::bindings::GLFWwindow;
 
-/// Key and action constants for input handling.
-pub const GLFW_PRESS: c_int = 1;
-pub const GLFW_KEY_SPACE: c_int = 32;
-pub const GLFW_KEY_RIGHT: c_int = 262;
-pub const GLFW_KEY_LEFT: c_int = 263;
-pub const GLFW_KEY_DOWN: c_int = 264;
-pub const GLFW_KEY_UP: c_int = 265;
-
-unsafe extern "C" {
-    /// Creates a game window with the given title and dimensions.
-    fn create_game_window(title: *const c_char, width: c_int, height: c_int);
-
-    /// Creates a sprite at the given position, size, and color.
-    fn create_sprite(
-        x: c_float,
-        y: c_float,
-        width: c_int,
-        height: c_int,
-        r: c_int,
-        g: c_int,
-        b: c_int,
-    ) -> *mut Sprite;
-
-    /// Renders the given sprite.
-    fn render_sprite(sprite: *mut Sprite);
-
-    /// Updates the position of the given sprite.
-    fn update_sprite_position(sprite: *mut Sprite, x: c_float, y: c_float);
-
-    /// Updates the game window (swaps buffers, polls events).
-    fn update_game_window();
-
-    /// Clears the screen.
-    fn clear_screen();
-
-    /// Returns non-zero if the window should close.
-    fn window_should_close() -> c_int;
-
-    /// Gets the state of the specified key for the given window.
-    fn get_key(window: *mut GLFWwindow, key: c_int) -> c_int;
-
-    /// Returns a pointer to the main game window.
-    fn get_window() -> *mut GLFWwindow;
-}
+/// Key, gamepad and action constants for input handling, generated from `GLFW/glfw3.h`.
+pub use crate::bindings::{
+    GLFW_GAMEPAD_BUTTON_A, GLFW_GAMEPAD_BUTTON_BACK, GLFW_GAMEPAD_BUTTON_DPAD_DOWN,
+    GLFW_GAMEPAD_BUTTON_DPAD_LEFT, GLFW_GAMEPAD_BUTTON_DPAD_RIGHT, GLFW_GAMEPAD_BUTTON_DPAD_UP,
+    GLFW_GAMEPAD_BUTTON_START, GLFW_GAMEPAD_BUTTON_Y, GLFW_KEY_A, GLFW_KEY_D, GLFW_KEY_DOWN,
+    GLFW_KEY_ENTER, GLFW_KEY_ESCAPE, GLFW_KEY_LEFT, GLFW_KEY_P, GLFW_KEY_RIGHT, GLFW_KEY_S,
+    GLFW_KEY_SPACE, GLFW_KEY_UP, GLFW_KEY_W, GLFW_PRESS,
+};
 
 /// Creates a game window with the specified title, width, and height.
 ///
+/// The C library supports a single window, so this succeeds only once per process.
+///
 /// # Arguments
 /// * `title` - The window title.
 /// * `width` - The window width in pixels.
 /// * `height` - The window height in pixels.
-pub fn rust_create_game_window(title: &str, width: i32, height: i32) {
-    let t = CString::new(title).unwrap();
+///
+/// # Returns
+/// The [`Engine`] token required by all render, input and window functions.
+pub fn rust_create_game_window(title: &str, width: i32, height: i32) -> Result<Engine, EngineError> {
+    let t = CString::new(title).map_err(|_| EngineError::InvalidTitle)?;
+    Engine::claim_window()?;
     unsafe {
         create_game_window(t.as_ptr(), width, height);
+        Engine::from_window(get_window())
     }
 }
 
@@ -97,69 +61,153 @@ pub fn rust_create_game_window(title: &str, width: i32, height: i32) {
 /// Pointer to the created `Sprite`.
 pub fn rust_create_sprite(x: f32, y: f32, width: i32, height: i32, r: i32, g: i32, b: i32) -> *mut Sprite{
     unsafe {
-        return create_sprite(x, y, width, height, r, g, b);
+        create_sprite(x, y, width, height, r, g, b)
     }
 }
 
-/// Renders the specified sprite.
+/// Frees a sprite created by [`rust_create_sprite`]. Null pointers are ignored.
+///
+/// Game code does not call this: [`crate::sprite::Sprite`] frees its sprite when dropped.
 ///
 /// # Arguments
+/// * `sprite` - Pointer to the sprite to free.
+///
+/// # Safety
+/// `sprite` must be null or a pointer returned by [`rust_create_sprite`] that was not
+/// freed yet, and must not be used afterwards.
+pub unsafe fn rust_free_sprite(sprite: *mut Sprite) {
+    if sprite.is_null() {
+        return;
+    }
+    unsafe {
+        free_sprite(sprite);
+    }
+}
+
+/// Renders the specified sprite. Null pointers are ignored.
+///
+/// Game code renders through [`crate::sprite::Sprite::render`] instead.
+///
+/// # Arguments
+/// * `_engine` - The engine context.
 /// * `sprite` - Pointer to the sprite to render.
-pub fn rust_render_sprite(sprite: *mut Sprite) {
+///
+/// # Safety
+/// `sprite` must be null or a pointer returned by [`rust_create_sprite`].
+pub unsafe fn rust_render_sprite(_engine: &Engine, sprite: *mut Sprite) {
+    if sprite.is_null() {
+        return;
+    }
     unsafe {
         render_sprite(sprite);
     }
 }
 
-/// Updates the position of the specified sprite.
+/// Updates the position of the specified sprite. Null pointers are ignored.
 ///
 /// # Arguments
 /// * `sprite` - Pointer to the sprite.
 /// * `x`, `y` - New position.
-pub fn rust_update_sprite_position(sprite: *mut Sprite, x: f32, y: f32) {
+///
+/// # Safety
+/// `sprite` must be null or a pointer returned by [`rust_create_sprite`].
+pub unsafe fn rust_update_sprite_position(sprite: *mut Sprite, x: f32, y: f32) {
+    if sprite.is_null() {
+        return;
+    }
     unsafe {
         update_sprite_position(sprite, x, y);
     }
 }
 
 /// Updates the game window (swaps buffers, polls events).
-pub fn rust_update_game_window() {
+pub fn rust_update_game_window(_engine: &Engine) {
     unsafe {
         update_game_window();
     }
 }
 
 /// Clears the game window screen.
-pub fn rust_clear_screen() {
+pub fn rust_clear_screen(_engine: &Engine) {
     unsafe {
         clear_screen();
     }
 }
 
 /// Returns `true` if the window should close, `false` otherwise.
-pub fn rust_window_should_close() -> bool {
+pub fn rust_window_should_close(_engine: &Engine) -> bool {
     unsafe {
         window_should_close() != 0
     }
 }
 
-/// Gets the state of the specified key for the given window.
+/// Gets the state of the specified key for the game window.
 ///
 /// # Arguments
-/// * `window` - Pointer to the window.
+/// * `engine` - The engine context owning the window.
 /// * `key` - Key code.
 /// 
 /// # Returns
 /// Key state as integer.
-pub fn rust_get_key(window: *mut GLFWwindow, key: i32) -> i32 {
+pub fn rust_get_key(engine: &Engine, key: i32) -> i32 {
+    unsafe {
+        get_key(engine.window(), key)
+    }
+}
+
+/// Gets the state of the specified mouse button for the game window.
+///
+/// # Arguments
+/// * `engine` - The engine context owning the window.
+/// * `button` - Mouse button code, `0` is the left button.
+///
+/// # Returns
+/// Button state as integer.
+pub fn rust_get_mouse_button(engine: &Engine, button: i32) -> i32 {
     unsafe {
-        get_key(window, key)
+        get_mouse_button(engine.window(), button)
     }
 }
 
-/// Returns a pointer to the main game window.
-pub fn rust_get_window() -> *mut GLFWwindow {
+/// Gets the cursor position in window coordinates, with the origin at the top left.
+///
+/// # Arguments
+/// * `engine` - The engine context owning the window.
+///
+/// # Returns
+/// The `(x, y)` position in pixels.
+pub fn rust_get_cursor_pos(engine: &Engine) -> (f64, f64) {
+    let (mut x, mut y) = (0.0, 0.0);
     unsafe {
-        get_window()
+        get_cursor_pos(engine.window(), &mut x, &mut y);
     }
-}
\ No newline at end of file
+    (x, y)
+}
+
+/// Gets the state of a button of the first connected gamepad.
+///
+/// # Arguments
+/// * `_engine` - The engine context.
+/// * `button` - Gamepad button code in the GLFW gamepad layout, `0` is the A button.
+///
+/// # Returns
+/// Button state as integer, released if no gamepad is connected.
+pub fn rust_get_gamepad_button(_engine: &Engine, button: i32) -> i32 {
+    unsafe {
+        get_gamepad_button(button)
+    }
+}
+
+/// Gets an axis of the first connected gamepad.
+///
+/// # Arguments
+/// * `_engine` - The engine context.
+/// * `axis` - Gamepad axis code in the GLFW gamepad layout, `0` is the left stick x axis.
+///
+/// # Returns
+/// The axis value from `-1.0` to `1.0`, `0.0` if no gamepad is connected.
+pub fn rust_get_gamepad_axis(_engine: &Engine, axis: i32) -> f32 {
+    unsafe {
+        get_gamepad_axis(axis)
+    }
+}
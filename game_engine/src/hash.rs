@@ -0,0 +1,84 @@
+//! Stable hashing of game state.
+//!
+//! Determinism tests run a game twice, or replay a recording, and compare a hash
+//! of the resulting state. [`StateHasher`] is FNV-1a, which unlike the standard
+//! library hasher gives the same value in every process, on every platform and in
+//! every release, so hashes can also be stored in tests.
+
+use std::hash::Hasher;
+
+/// FNV-1a 64-bit hasher.
+#[derive(Debug, Clone)]
+pub struct StateHasher {
+    hash: u64,
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self {
+            hash: 0xCBF2_9CE4_8422_2325,
+        }
+    }
+}
+
+impl StateHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes the bits of `value`, so positions hash equal only if they are exactly equal.
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+}
+
+impl Hasher for StateHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    /// Integers are hashed as little endian bytes on every platform.
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::Hash;
+
+    /// Verify the FNV-1a reference values and that values hash by content.
+    #[test]
+    fn test_stable() {
+        assert_eq!(StateHasher::new().finish(), 0xCBF2_9CE4_8422_2325);
+        let mut hasher = StateHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xAF63_DC4C_8601_EC8C);
+
+        let hash = |value: f32, name: &str| {
+            let mut hasher = StateHasher::new();
+            hasher.write_f32(value);
+            name.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(1.5, "ball"), hash(1.5, "ball"));
+        assert_ne!(hash(1.5, "ball"), hash(1.5000001, "ball"));
+        assert_ne!(hash(1.5, "ball"), hash(1.5, "paddle"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Pure Rust stand-in for `opengl_wrapper_lib`, used by the `headless` feature.
+//!
+//! Provides the same raw items as the generated bindings, so [`crate::ffi`] and
+//! everything above it work unchanged without GLFW, OpenGL or a C compiler.
+//! There is no window: rendering does nothing, no key is ever pressed and the
+//! window never asks to close, so the game decides when to stop.
+
+use std::os::raw::{c_char, c_int};
+use std::ptr::NonNull;
+
+pub const GLFW_PRESS: i32 = 1;
+pub const GLFW_KEY_SPACE: i32 = 32;
+pub const GLFW_KEY_A: i32 = 65;
+pub const GLFW_KEY_D: i32 = 68;
+pub const GLFW_KEY_P: i32 = 80;
+pub const GLFW_KEY_S: i32 = 83;
+pub const GLFW_KEY_W: i32 = 87;
+pub const GLFW_KEY_ESCAPE: i32 = 256;
+pub const GLFW_KEY_ENTER: i32 = 257;
+pub const GLFW_KEY_RIGHT: i32 = 262;
+pub const GLFW_KEY_LEFT: i32 = 263;
+pub const GLFW_KEY_DOWN: i32 = 264;
+pub const GLFW_KEY_UP: i32 = 265;
+pub const GLFW_GAMEPAD_BUTTON_A: i32 = 0;
+pub const GLFW_GAMEPAD_BUTTON_Y: i32 = 3;
+pub const GLFW_GAMEPAD_BUTTON_BACK: i32 = 6;
+pub const GLFW_GAMEPAD_BUTTON_START: i32 = 7;
+pub const GLFW_GAMEPAD_BUTTON_DPAD_UP: i32 = 11;
+pub const GLFW_GAMEPAD_BUTTON_DPAD_RIGHT: i32 = 12;
+pub const GLFW_GAMEPAD_BUTTON_DPAD_DOWN: i32 = 13;
+pub const GLFW_GAMEPAD_BUTTON_DPAD_LEFT: i32 = 14;
+
+/// Same layout as the `Sprite` struct of `opengl_wrapper_lib.h`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct Sprite {
+    pub width: c_int,
+    pub height: c_int,
+    pub color: [c_int; 3],
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Opaque window type; headless windows are never dereferenced.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GLFWwindow {
+    _unused: [u8; 0],
+}
+
+pub unsafe fn create_game_window(_title: *const c_char, _width: c_int, _height: c_int) {}
+
+pub unsafe fn get_window() -> *mut GLFWwindow {
+    NonNull::dangling().as_ptr()
+}
+
+pub unsafe fn create_sprite(
+    x: f32,
+    y: f32,
+    width: c_int,
+    height: c_int,
+    r: c_int,
+    g: c_int,
+    b: c_int,
+) -> *mut Sprite {
+    Box::into_raw(Box::new(Sprite {
+        width,
+        height,
+        color: [r, g, b],
+        x,
+        y,
+    }))
+}
+
+pub unsafe fn free_sprite(sprite: *mut Sprite) {
+    drop(unsafe { Box::from_raw(sprite) });
+}
+
+pub unsafe fn render_sprite(_sprite: *mut Sprite) {}
+
+pub unsafe fn update_sprite_position(sprite: *mut Sprite, x: f32, y: f32) {
+    unsafe {
+        (*sprite).x = x;
+        (*sprite).y = y;
+    }
+}
+
+pub unsafe fn update_game_window() {}
+
+pub unsafe fn clear_screen() {}
+
+pub unsafe fn window_should_close() -> c_int {
+    0
+}
+
+pub unsafe fn get_key(_window: *mut GLFWwindow, _key: c_int) -> c_int {
+    0
+}
+
+pub unsafe fn get_mouse_button(_window: *mut GLFWwindow, _button: c_int) -> c_int {
+    0
+}
+
+pub unsafe fn get_cursor_pos(_window: *mut GLFWwindow, x: *mut f64, y: *mut f64) {
+    unsafe {
+        *x = 0.0;
+        *y = 0.0;
+    }
+}
+
+pub unsafe fn get_gamepad_button(_button: c_int) -> c_int {
+    0
+}
+
+pub unsafe fn get_gamepad_axis(_axis: c_int) -> f32 {
+    0.0
+}
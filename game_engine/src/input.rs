@@ -0,0 +1,901 @@
+//! Input actions and axes.
+//!
+//! Games ask for named actions like `"pause"` and axes like `"paddle"` instead of
+//! polling keys. [`Bindings`] maps each action to any mix of keys, mouse buttons and
+//! gamepad buttons, and each axis to button pairs, gamepad sticks and mouse movement.
+//! [`Input`] reads the bound inputs once per frame from an [`InputSource`], usually
+//! the [`Engine`](crate::Engine).
+//!
+//! With the `serde` feature bindings load from and save to JSON config files:
+//!
+//! ```json
+//! { "actions": { "pause": ["key:p", "key:escape", "gamepad:start"] },
+//!   "axes": { "paddle": [
+//!       { "buttons": { "negative": "key:up", "positive": "key:down" } },
+//!       { "gamepad": { "axis": "left_y", "dead_zone": 0.2 } } ] } }
+//! ```
+//!
+//! Buttons are written as `key:<name>`, `mouse:<name>` or `gamepad:<name>`, see
+//! [`Button`]. A menu can rebind an action at runtime with [`Input::start_rebind`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Raw input state an [`Input`] reads every frame.
+pub trait InputSource {
+    /// Returns `true` while the key with the given GLFW key code is down.
+    fn key(&self, key: i32) -> bool;
+    /// Returns `true` while the mouse button with the given GLFW code is down.
+    fn mouse_button(&self, button: i32) -> bool;
+    /// Returns the cursor position in window coordinates.
+    fn cursor(&self) -> (f64, f64);
+    /// Returns `true` while the button of the first gamepad is down.
+    fn gamepad_button(&self, button: i32) -> bool;
+    /// Returns an axis of the first gamepad from `-1.0` to `1.0`.
+    fn gamepad_axis(&self, axis: i32) -> f32;
+}
+
+#[cfg(backend)]
+impl InputSource for crate::Engine {
+    fn key(&self, key: i32) -> bool {
+        crate::ffi::rust_get_key(self, key) == crate::ffi::GLFW_PRESS
+    }
+
+    fn mouse_button(&self, button: i32) -> bool {
+        crate::ffi::rust_get_mouse_button(self, button) == crate::ffi::GLFW_PRESS
+    }
+
+    fn cursor(&self) -> (f64, f64) {
+        crate::ffi::rust_get_cursor_pos(self)
+    }
+
+    fn gamepad_button(&self, button: i32) -> bool {
+        crate::ffi::rust_get_gamepad_button(self, button) == crate::ffi::GLFW_PRESS
+    }
+
+    fn gamepad_axis(&self, axis: i32) -> f32 {
+        crate::ffi::rust_get_gamepad_axis(self, axis)
+    }
+}
+
+/// A digital input, identified by its GLFW code.
+///
+/// Written as `key:up`, `key:a`, `key:f1`, `mouse:left` or `gamepad:start`. Keys
+/// without a name are written with their code, like `key:161`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub enum Button {
+    Key(i32),
+    Mouse(i32),
+    Gamepad(i32),
+}
+
+/// Named keys that are not letters, digits, function or keypad digit keys.
+const KEY_NAMES: [(&str, i32); 39] = [
+    ("space", 32),
+    ("apostrophe", 39),
+    ("comma", 44),
+    ("minus", 45),
+    ("period", 46),
+    ("slash", 47),
+    ("semicolon", 59),
+    ("equal", 61),
+    ("left_bracket", 91),
+    ("backslash", 92),
+    ("right_bracket", 93),
+    ("grave_accent", 96),
+    ("escape", 256),
+    ("enter", 257),
+    ("tab", 258),
+    ("backspace", 259),
+    ("insert", 260),
+    ("delete", 261),
+    ("right", 262),
+    ("left", 263),
+    ("down", 264),
+    ("up", 265),
+    ("page_up", 266),
+    ("page_down", 267),
+    ("home", 268),
+    ("end", 269),
+    ("caps_lock", 280),
+    ("kp_decimal", 330),
+    ("kp_divide", 331),
+    ("kp_multiply", 332),
+    ("kp_subtract", 333),
+    ("kp_add", 334),
+    ("kp_enter", 335),
+    ("left_shift", 340),
+    ("left_control", 341),
+    ("left_alt", 342),
+    ("right_shift", 344),
+    ("right_control", 345),
+    ("right_alt", 346),
+];
+
+const MOUSE_NAMES: [(&str, i32); 3] = [("left", 0), ("right", 1), ("middle", 2)];
+
+/// Buttons of the GLFW gamepad layout, in code order.
+const GAMEPAD_NAMES: [&str; 15] = [
+    "a",
+    "b",
+    "x",
+    "y",
+    "left_bumper",
+    "right_bumper",
+    "back",
+    "start",
+    "guide",
+    "left_thumb",
+    "right_thumb",
+    "dpad_up",
+    "dpad_right",
+    "dpad_down",
+    "dpad_left",
+];
+
+/// Number of mouse buttons GLFW reports.
+const MOUSE_BUTTONS: i32 = 8;
+
+/// Highest GLFW key code.
+const KEY_LAST: i32 = 348;
+
+/// Number of axes of the GLFW gamepad layout.
+pub const GAMEPAD_AXES: usize = 6;
+
+fn key_name(code: i32) -> Option<String> {
+    match code {
+        48..=57 => Some(char::from((code - 48) as u8 + b'0').to_string()),
+        65..=90 => Some(char::from((code - 65) as u8 + b'a').to_string()),
+        290..=314 => Some(format!("f{}", code - 289)),
+        320..=329 => Some(format!("kp_{}", code - 320)),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+fn key_code(name: &str) -> Option<i32> {
+    let bytes = name.as_bytes();
+    if let [c @ (b'0'..=b'9' | b'a'..=b'z')] = bytes {
+        return Some(match c {
+            b'0'..=b'9' => 48 + i32::from(c - b'0'),
+            _ => 65 + i32::from(c - b'a'),
+        });
+    }
+    let number = |prefix: &str, first: i32, count: i32, base: i32| {
+        name.strip_prefix(prefix)
+            .and_then(|n| n.parse::<i32>().ok())
+            .filter(|n| (first..first + count).contains(n))
+            .map(|n| base + n - first)
+    };
+    number("f", 1, 25, 290)
+        .or_else(|| number("kp_", 0, 10, 320))
+        .or_else(|| {
+            KEY_NAMES
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, code)| *code)
+        })
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Button::Key(code) => match key_name(code) {
+                Some(name) => write!(f, "key:{name}"),
+                None => write!(f, "key:{code}"),
+            },
+            Button::Mouse(code) => match MOUSE_NAMES.iter().find(|(_, c)| *c == code) {
+                Some((name, _)) => write!(f, "mouse:{name}"),
+                None => write!(f, "mouse:{code}"),
+            },
+            Button::Gamepad(code) => match GAMEPAD_NAMES.get(code as usize) {
+                Some(name) => write!(f, "gamepad:{name}"),
+                None => write!(f, "gamepad:{code}"),
+            },
+        }
+    }
+}
+
+impl FromStr for Button {
+    type Err = String;
+
+    /// Parses the form written by `Display`; codes are accepted in place of names.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || format!("unknown button `{s}`");
+        let (device, name) = s.split_once(':').ok_or_else(unknown)?;
+        // Names first, `key:7` is the digit key and not code 7.
+        let code = |named: Option<i32>| named.or_else(|| name.parse::<i32>().ok());
+        match device {
+            "key" => code(key_code(name)).map(Button::Key),
+            "mouse" => code(
+                MOUSE_NAMES
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, c)| *c),
+            )
+            .map(Button::Mouse),
+            "gamepad" => code(
+                GAMEPAD_NAMES
+                    .iter()
+                    .position(|n| *n == name)
+                    .map(|c| c as i32),
+            )
+            .map(Button::Gamepad),
+            _ => None,
+        }
+        .ok_or_else(unknown)
+    }
+}
+
+impl TryFrom<String> for Button {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Button> for String {
+    fn from(button: Button) -> Self {
+        button.to_string()
+    }
+}
+
+impl Button {
+    /// Returns `true` while the button is down.
+    pub fn is_down(&self, source: &impl InputSource) -> bool {
+        match *self {
+            Button::Key(code) => source.key(code),
+            Button::Mouse(code) => source.mouse_button(code),
+            Button::Gamepad(code) => source.gamepad_button(code),
+        }
+    }
+
+    /// Returns all named buttons that are down, in key, mouse, gamepad order.
+    pub fn all_down(source: &impl InputSource) -> Vec<Button> {
+        let keys = (32..=KEY_LAST)
+            .filter(|code| key_name(*code).is_some())
+            .map(Button::Key);
+        let mouse = (0..MOUSE_BUTTONS).map(Button::Mouse);
+        let gamepad = (0..GAMEPAD_NAMES.len() as i32).map(Button::Gamepad);
+        keys.chain(mouse)
+            .chain(gamepad)
+            .filter(|button| button.is_down(source))
+            .collect()
+    }
+}
+
+/// Sticks and triggers of the GLFW gamepad layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// Returns the GLFW axis code.
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Direction of cursor movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum MouseAxis {
+    X,
+    Y,
+}
+
+fn default_dead_zone() -> f32 {
+    0.15
+}
+
+/// One input of an axis. The value of an axis is the sum of its inputs, clamped
+/// to `-1.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub enum AxisBinding {
+    /// `-1.0` while `negative` is down, `1.0` while `positive` is down.
+    Buttons { negative: Button, positive: Button },
+    /// A stick or trigger of the first gamepad. Values inside the dead zone count
+    /// as `0.0`, so a worn stick does not drift.
+    Gamepad {
+        axis: GamepadAxis,
+        #[cfg_attr(feature = "serde", serde(default))]
+        invert: bool,
+        #[cfg_attr(feature = "serde", serde(default = "default_dead_zone"))]
+        dead_zone: f32,
+    },
+    /// Cursor movement since the last frame in pixels, times `scale`.
+    Mouse { axis: MouseAxis, scale: f32 },
+}
+
+impl AxisBinding {
+    /// A gamepad axis with the default dead zone.
+    pub fn gamepad(axis: GamepadAxis) -> Self {
+        AxisBinding::Gamepad {
+            axis,
+            invert: false,
+            dead_zone: default_dead_zone(),
+        }
+    }
+
+    /// Returns the value of this input, given the cursor movement of the frame.
+    fn value(&self, source: &impl InputSource, cursor_delta: (f64, f64)) -> f32 {
+        match *self {
+            AxisBinding::Buttons { negative, positive } => {
+                f32::from(u8::from(positive.is_down(source)))
+                    - f32::from(u8::from(negative.is_down(source)))
+            }
+            AxisBinding::Gamepad {
+                axis,
+                invert,
+                dead_zone,
+            } => {
+                let value = source.gamepad_axis(axis.code());
+                let value = if value.abs() < dead_zone { 0.0 } else { value };
+                if invert { -value } else { value }
+            }
+            AxisBinding::Mouse { axis, scale } => {
+                let delta = match axis {
+                    MouseAxis::X => cursor_delta.0,
+                    MouseAxis::Y => cursor_delta.1,
+                };
+                delta as f32 * scale
+            }
+        }
+    }
+}
+
+/// Errors that can occur while loading or saving bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingsError {
+    /// The file could not be read or written.
+    Io(String),
+    /// The file is not a valid bindings file.
+    Parse(String),
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(msg) => write!(f, "could not access bindings file: {msg}"),
+            BindingsError::Parse(msg) => write!(f, "invalid bindings file: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+/// Buttons of the actions and inputs of the axes, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Bindings {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub actions: BTreeMap<String, Vec<Button>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl Bindings {
+    /// Creates bindings without actions and axes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `action` to `buttons`, replacing its previous buttons. For default bindings:
+    ///
+    /// ```
+    /// use game_engine::input::{Bindings, Button};
+    ///
+    /// let bindings = Bindings::new()
+    ///     .with_action("pause", [Button::Key(80), Button::Gamepad(7)])
+    ///     .with_action("fire", [Button::Mouse(0)]);
+    /// ```
+    pub fn with_action(mut self, action: &str, buttons: impl IntoIterator<Item = Button>) -> Self {
+        self.actions
+            .insert(action.to_string(), buttons.into_iter().collect());
+        self
+    }
+
+    /// Binds `axis` to `inputs`, replacing its previous inputs.
+    pub fn with_axis(mut self, axis: &str, inputs: impl IntoIterator<Item = AxisBinding>) -> Self {
+        self.axes
+            .insert(axis.to_string(), inputs.into_iter().collect());
+        self
+    }
+
+    /// Returns the buttons of `action`, empty if it is unbound.
+    pub fn buttons(&self, action: &str) -> &[Button] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds `button` to the buttons of `action`.
+    pub fn bind(&mut self, action: &str, button: Button) {
+        let buttons = self.actions.entry(action.to_string()).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Removes `button` from the buttons of `action`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the button was not bound to the action.
+    pub fn unbind(&mut self, action: &str, button: Button) -> bool {
+        let Some(buttons) = self.actions.get_mut(action) else {
+            return false;
+        };
+        let len = buttons.len();
+        buttons.retain(|b| *b != button);
+        buttons.len() != len
+    }
+
+    /// Returns the inputs of `axis`, empty if it is unbound.
+    pub fn axis_inputs(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds an input to `axis`.
+    pub fn bind_axis(&mut self, axis: &str, input: AxisBinding) {
+        self.axes.entry(axis.to_string()).or_default().push(input);
+    }
+
+    /// Parses bindings from JSON.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(BindingsError::Parse)` for invalid JSON, unknown buttons or unknown fields.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, BindingsError> {
+        serde_json::from_str(json).map_err(|err| BindingsError::Parse(err.to_string()))
+    }
+
+    /// Serializes the bindings as JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("bindings serialize to JSON")
+    }
+
+    /// Reads bindings from a config file.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, BindingsError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| BindingsError::Io(format!("{}: {err}", path.display())))?;
+        Self::from_json(&json)
+            .map_err(|err| BindingsError::Parse(format!("{}: {err}", path.display())))
+    }
+
+    /// Reads bindings from a config file, or writes `defaults` to it if there is none
+    /// yet, so players find a file to edit.
+    ///
+    /// # Returns
+    ///
+    /// * The errors of [`Bindings::load`] and [`Bindings::save`]. A broken file is
+    ///   reported rather than overwritten.
+    #[cfg(feature = "serde")]
+    pub fn load_or_init(
+        path: impl AsRef<std::path::Path>,
+        defaults: Self,
+    ) -> Result<Self, BindingsError> {
+        let path = path.as_ref();
+        if path.exists() {
+            Self::load(path)
+        } else {
+            defaults.save(path)?;
+            Ok(defaults)
+        }
+    }
+
+    /// Writes the bindings to a config file, creating its directory if needed.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), BindingsError> {
+        let path = path.as_ref();
+        let io_error =
+            |err: std::io::Error| BindingsError::Io(format!("{}: {err}", path.display()));
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        std::fs::write(path, self.to_json()).map_err(io_error)
+    }
+}
+
+/// Snapshot of all raw inputs of a frame.
+///
+/// An `InputState` is itself an [`InputSource`], so a frame can be read once and
+/// then fed to an [`Input`], recorded, or replayed later, see [`crate::replay`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputState {
+    /// Buttons that are down, sorted.
+    pub buttons: Vec<Button>,
+    pub cursor: (f64, f64),
+    /// Gamepad axes in [`GamepadAxis`] order.
+    pub gamepad_axes: [f32; GAMEPAD_AXES],
+}
+
+impl InputState {
+    /// Reads all keys, mouse buttons, gamepad buttons and axes and the cursor.
+    pub fn read(source: &impl InputSource) -> Self {
+        let keys = (32..=KEY_LAST).map(Button::Key);
+        let mouse = (0..MOUSE_BUTTONS).map(Button::Mouse);
+        let gamepad = (0..GAMEPAD_NAMES.len() as i32).map(Button::Gamepad);
+        Self {
+            buttons: keys
+                .chain(mouse)
+                .chain(gamepad)
+                .filter(|button| button.is_down(source))
+                .collect(),
+            cursor: source.cursor(),
+            gamepad_axes: std::array::from_fn(|axis| source.gamepad_axis(axis as i32)),
+        }
+    }
+}
+
+impl InputSource for InputState {
+    fn key(&self, key: i32) -> bool {
+        self.buttons.binary_search(&Button::Key(key)).is_ok()
+    }
+
+    fn mouse_button(&self, button: i32) -> bool {
+        self.buttons.binary_search(&Button::Mouse(button)).is_ok()
+    }
+
+    fn cursor(&self) -> (f64, f64) {
+        self.cursor
+    }
+
+    fn gamepad_button(&self, button: i32) -> bool {
+        self.buttons.binary_search(&Button::Gamepad(button)).is_ok()
+    }
+
+    fn gamepad_axis(&self, axis: i32) -> f32 {
+        usize::try_from(axis)
+            .ok()
+            .and_then(|axis| self.gamepad_axes.get(axis))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+/// An action waiting for its new button.
+struct Rebind {
+    action: String,
+    /// Buttons that were down when the rebind started, `None` before the first update.
+    held: Option<Vec<Button>>,
+}
+
+/// Action and axis state of the current frame.
+pub struct Input {
+    bindings: Bindings,
+    down: HashSet<String>,
+    previous: HashSet<String>,
+    axes: HashMap<String, f32>,
+    cursor: Option<(f64, f64)>,
+    rebind: Option<Rebind>,
+}
+
+impl Input {
+    /// Creates the input state for `bindings`, with nothing pressed.
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            down: HashSet::new(),
+            previous: HashSet::new(),
+            axes: HashMap::new(),
+            cursor: None,
+            rebind: None,
+        }
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Returns the bindings for changing them; the change applies from the next update.
+    pub fn bindings_mut(&mut self) -> &mut Bindings {
+        &mut self.bindings
+    }
+
+    /// Reads the bound inputs; call once per frame before the game asks for actions.
+    ///
+    /// While a rebind is pending, all actions are released and the axes are zero.
+    pub fn update(&mut self, source: &impl InputSource) {
+        self.previous = std::mem::take(&mut self.down);
+        self.axes.clear();
+
+        let cursor = source.cursor();
+        let previous_cursor = self.cursor.replace(cursor).unwrap_or(cursor);
+        let cursor_delta = (cursor.0 - previous_cursor.0, cursor.1 - previous_cursor.1);
+
+        if self.rebind.is_some() {
+            self.update_rebind(source);
+            return;
+        }
+
+        for (action, buttons) in &self.bindings.actions {
+            if buttons.iter().any(|button| button.is_down(source)) {
+                self.down.insert(action.clone());
+            }
+        }
+        for (axis, inputs) in &self.bindings.axes {
+            let value: f32 = inputs
+                .iter()
+                .map(|input| input.value(source, cursor_delta))
+                .sum();
+            self.axes.insert(axis.clone(), value.clamp(-1.0, 1.0));
+        }
+    }
+
+    /// Binds the first button that goes down to the pending rebind.
+    fn update_rebind(&mut self, source: &impl InputSource) {
+        let Some(rebind) = &mut self.rebind else {
+            return;
+        };
+        let down = Button::all_down(source);
+        let Some(held) = &mut rebind.held else {
+            rebind.held = Some(down);
+            return;
+        };
+        match down.iter().find(|button| !held.contains(button)) {
+            Some(&button) => {
+                self.bindings
+                    .actions
+                    .insert(rebind.action.clone(), vec![button]);
+                self.rebind = None;
+            }
+            // Released buttons may be pressed again to bind them.
+            None => held.retain(|button| down.contains(button)),
+        }
+    }
+
+    /// Returns `true` while a button of `action` is down.
+    pub fn pressed(&self, action: &str) -> bool {
+        self.down.contains(action)
+    }
+
+    /// Returns `true` if `action` went down this frame.
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.down.contains(action) && !self.previous.contains(action)
+    }
+
+    /// Returns `true` if `action` went up this frame.
+    pub fn just_released(&self, action: &str) -> bool {
+        !self.down.contains(action) && self.previous.contains(action)
+    }
+
+    /// Returns the value of `axis` from `-1.0` to `1.0`, `0.0` if it is unbound.
+    pub fn axis(&self, axis: &str) -> f32 {
+        self.axes.get(axis).copied().unwrap_or(0.0)
+    }
+
+    /// Waits for the next button that goes down and binds it to `action` in place
+    /// of its current buttons. Buttons that are already down when the rebind starts,
+    /// like the one that chose the menu entry, are ignored until released.
+    pub fn start_rebind(&mut self, action: &str) {
+        self.rebind = Some(Rebind {
+            action: action.to_string(),
+            held: None,
+        });
+    }
+
+    /// Returns the action waiting for its new button.
+    pub fn rebinding(&self) -> Option<&str> {
+        self.rebind.as_ref().map(|rebind| rebind.action.as_str())
+    }
+
+    /// Stops waiting for a new button and keeps the old ones.
+    pub fn cancel_rebind(&mut self) {
+        self.rebind = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UP: Button = Button::Key(265);
+    const DOWN: Button = Button::Key(264);
+    const P: Button = Button::Key(80);
+    const START: Button = Button::Gamepad(7);
+
+    #[derive(Default)]
+    struct FakeSource {
+        down: HashSet<Button>,
+        cursor: (f64, f64),
+        left_y: f32,
+    }
+
+    impl InputSource for FakeSource {
+        fn key(&self, key: i32) -> bool {
+            self.down.contains(&Button::Key(key))
+        }
+
+        fn mouse_button(&self, button: i32) -> bool {
+            self.down.contains(&Button::Mouse(button))
+        }
+
+        fn cursor(&self) -> (f64, f64) {
+            self.cursor
+        }
+
+        fn gamepad_button(&self, button: i32) -> bool {
+            self.down.contains(&Button::Gamepad(button))
+        }
+
+        fn gamepad_axis(&self, axis: i32) -> f32 {
+            if axis == GamepadAxis::LeftY.code() {
+                self.left_y
+            } else {
+                0.0
+            }
+        }
+    }
+
+    fn bindings() -> Bindings {
+        Bindings::new().with_action("pause", [P, START]).with_axis(
+            "paddle",
+            [
+                AxisBinding::Buttons {
+                    negative: UP,
+                    positive: DOWN,
+                },
+                AxisBinding::gamepad(GamepadAxis::LeftY),
+                AxisBinding::Mouse {
+                    axis: MouseAxis::Y,
+                    scale: 0.1,
+                },
+            ],
+        )
+    }
+
+    /// Verify that an action is pressed by any of its buttons, and reported as
+    /// pressed and released once.
+    #[test]
+    fn test_actions() {
+        let mut input = Input::new(bindings());
+        let mut source = FakeSource::default();
+
+        source.down.insert(START);
+        input.update(&source);
+        assert!(input.pressed("pause") && input.just_pressed("pause"));
+
+        source.down.insert(P);
+        input.update(&source);
+        assert!(input.pressed("pause") && !input.just_pressed("pause"));
+
+        source.down.clear();
+        input.update(&source);
+        assert!(input.just_released("pause"));
+        assert!(!input.pressed("unbound"));
+    }
+
+    /// Verify that axis inputs add up, respect the dead zone and are clamped.
+    #[test]
+    fn test_axes() {
+        let mut input = Input::new(bindings());
+        let mut source = FakeSource {
+            left_y: 0.1,
+            ..Default::default()
+        };
+        input.update(&source);
+        assert_eq!(input.axis("paddle"), 0.0);
+
+        source.down.insert(UP);
+        source.left_y = 0.5;
+        input.update(&source);
+        assert_eq!(input.axis("paddle"), -0.5);
+
+        source.down.clear();
+        source.cursor.1 = 20.0;
+        input.update(&source);
+        assert_eq!(input.axis("paddle"), 1.0);
+        assert_eq!(input.axis("unbound"), 0.0);
+    }
+
+    /// Verify that a snapshot reports the same inputs as its source.
+    #[test]
+    fn test_input_state() {
+        let mut source = FakeSource {
+            cursor: (3.0, 4.0),
+            left_y: -0.5,
+            ..Default::default()
+        };
+        source.down.extend([DOWN, START, Button::Mouse(2)]);
+
+        let state = InputState::read(&source);
+        assert_eq!(state.buttons, [DOWN, Button::Mouse(2), START]);
+        assert!(state.key(264) && !state.key(265));
+        assert_eq!(state.cursor(), (3.0, 4.0));
+        assert_eq!(state.gamepad_axis(GamepadAxis::LeftY.code()), -0.5);
+        assert_eq!(state.gamepad_axis(9), 0.0);
+    }
+
+    /// Verify that a rebind skips held buttons and binds the next new one.
+    #[test]
+    fn test_rebind() {
+        let mut input = Input::new(bindings());
+        let mut source = FakeSource::default();
+        source.down.insert(Button::Key(257));
+
+        input.start_rebind("pause");
+        input.update(&source);
+        source.down.insert(Button::Mouse(1));
+        source.down.insert(P);
+        input.update(&source);
+
+        assert_eq!(input.rebinding(), None);
+        assert!(!input.pressed("pause"));
+        assert_eq!(input.bindings().buttons("pause"), [P]);
+    }
+
+    /// Verify that buttons are written and parsed by name.
+    #[test]
+    fn test_button_names() {
+        for (text, button) in [
+            ("key:up", UP),
+            ("key:a", Button::Key(65)),
+            ("key:7", Button::Key(55)),
+            ("key:f12", Button::Key(301)),
+            ("key:kp_3", Button::Key(323)),
+            ("key:161", Button::Key(161)),
+            ("mouse:left", Button::Mouse(0)),
+            ("mouse:5", Button::Mouse(5)),
+            ("gamepad:start", START),
+        ] {
+            assert_eq!(button.to_string(), text);
+            assert_eq!(text.parse::<Button>(), Ok(button));
+        }
+        assert!("key:nope".parse::<Button>().is_err());
+        assert!("joystick:a".parse::<Button>().is_err());
+    }
+
+    /// Verify that bindings survive a JSON round trip and typos are rejected.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let bindings = bindings();
+        assert_eq!(Bindings::from_json(&bindings.to_json()), Ok(bindings));
+
+        let json = r#"{ "axes": { "paddle": [ { "gamepad": { "axis": "left_y" } } ] } }"#;
+        let parsed = Bindings::from_json(json).unwrap();
+        assert_eq!(
+            parsed.axis_inputs("paddle"),
+            [AxisBinding::gamepad(GamepadAxis::LeftY)]
+        );
+
+        assert!(Bindings::from_json(r#"{ "actions": { "pause": ["key:pp"] } }"#).is_err());
+        assert!(Bindings::from_json(r#"{ "action": {} }"#).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! Level files: entities and their initial state as JSON.
+//!
+//! A level lists named entities with position, size, velocity, color, collision body,
+//! physical material, collision layers and tags.
+//! Positions are offsets from an anchor point of the window, so a layout does not
+//! depend on the window size:
+//!
+//! ```json
+//! { "entities": [
+//!     { "name": "player2", "anchor": "top_right", "pos": { "x": -50, "y": 200 },
+//!       "size": { "width": 30, "height": 200 }, "tags": ["paddle"] }
+//! ] }
+//! ```
+//!
+//! Missing velocities are zero, missing colors white, and missing bodies dynamic with a
+//! restitution of `1.0`, a mass of `1.0` and neither friction nor damping, on the default
+//! layer, colliding with all layers. Unknown fields are
+//! rejected, so typos do not go unnoticed. [`Level::from_json`] validates the file
+//! and reports problems with file, line and column.
+
+use crate::collider::Collider;
+use crate::ecs;
+use crate::sprite::{
+    ALL_LAYERS, BodyKind, Color, DEFAULT_LAYER, Pos, Size, Sprite, SpriteFields, Velocity,
+};
+use crate::world::World;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Errors that can occur while loading a level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LevelError {
+    /// The file could not be read.
+    Io(String),
+    /// The file is not valid JSON or not a valid level.
+    Invalid {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(msg) => write!(f, "could not read level: {msg}"),
+            LevelError::Invalid {
+                file,
+                line,
+                column,
+                message,
+            } => write!(f, "{file}:{line}:{column}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+/// Point of the window an entity position is relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Returns the anchor point in a window of the given size.
+    pub fn point(self, window: Size) -> Pos {
+        let x = match self {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0.0,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => window.width / 2.0,
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => window.width,
+        };
+        let y = match self {
+            Anchor::TopLeft | Anchor::Top | Anchor::TopRight => 0.0,
+            Anchor::Left | Anchor::Center | Anchor::Right => window.height / 2.0,
+            Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => window.height,
+        };
+        Pos { x, y }
+    }
+}
+
+fn resting() -> Velocity {
+    Velocity { dx: 0.0, dy: 0.0 }
+}
+
+fn elastic() -> f32 {
+    1.0
+}
+
+fn unit_mass() -> f32 {
+    1.0
+}
+
+fn default_layer() -> u32 {
+    DEFAULT_LAYER
+}
+
+fn all_layers() -> u32 {
+    ALL_LAYERS
+}
+
+fn white() -> Color {
+    Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    }
+}
+
+/// One entity of a level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntityDef {
+    /// Unique name; the sprite name in a [`World`].
+    pub name: String,
+    #[serde(default)]
+    pub anchor: Anchor,
+    /// Top left corner, relative to the anchor point.
+    pub pos: Pos,
+    pub size: Size,
+    #[serde(default = "resting")]
+    pub velocity: Velocity,
+    #[serde(default = "white")]
+    pub color: Color,
+    /// How the sprite responds to collisions, see [`Sprite::body`].
+    #[serde(default)]
+    pub body: BodyKind,
+    #[serde(default = "elastic")]
+    pub restitution: f32,
+    /// Mass, see [`Sprite::mass`]; must be positive.
+    #[serde(default = "unit_mass")]
+    pub mass: f32,
+    #[serde(default)]
+    pub friction: f32,
+    #[serde(default)]
+    pub linear_damping: f32,
+    /// Collision layer bits, see [`Sprite::layer`].
+    #[serde(default = "default_layer")]
+    pub layer: u32,
+    /// Layers the sprite collides with, see [`Sprite::mask`].
+    #[serde(default = "all_layers")]
+    pub mask: u32,
+    /// Whether the sprite is a trigger, see [`Sprite::trigger`].
+    #[serde(default)]
+    pub trigger: bool,
+    /// Collision shape, see [`Sprite::collider`]; the sprite rectangle by default.
+    #[serde(default)]
+    pub collider: Collider,
+    /// Free-form tags the game maps to components, e.g. `"player"`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl EntityDef {
+    /// Returns the position in a window of the given size.
+    pub fn position(&self, window: Size) -> Pos {
+        let anchor = self.anchor.point(window);
+        Pos {
+            x: anchor.x + self.pos.x,
+            y: anchor.y + self.pos.y,
+        }
+    }
+
+    /// Creates the sprite of the entity for a window of the given size.
+    pub fn sprite(&self, window: Size) -> Sprite {
+        let mut sprite = Sprite::new(self.position(window), self.velocity, self.color, self.size);
+        sprite.body = self.body;
+        sprite.restitution = self.restitution;
+        sprite.mass = self.mass;
+        sprite.friction = self.friction;
+        sprite.linear_damping = self.linear_damping;
+        sprite.layer = self.layer;
+        sprite.mask = self.mask;
+        sprite.trigger = self.trigger;
+        sprite.collider = self.collider.clone();
+        sprite
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// A level: the entities a game starts with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    #[serde(deserialize_with = "checked_entities")]
+    pub entities: Vec<EntityDef>,
+}
+
+/// Name component of entities spawned from a level into an [`ecs::World`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name(pub String);
+
+/// Tags component of entities spawned from a level into an [`ecs::World`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Tags(pub Vec<String>);
+
+impl Level {
+    /// Parses and validates a level.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The content of the level file.
+    /// * `file` - The file name used in error messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(LevelError::Invalid)` with the position of the first problem: a JSON
+    ///   syntax error, a missing or unknown field, a duplicate or empty name, a size
+    ///   or mass that is not positive, an invalid collider, or a color component
+    ///   outside 0 to 255. Problems with the values of an entity are reported at
+    ///   the end of that entity.
+    pub fn from_json(json: &str, file: &str) -> Result<Level, LevelError> {
+        serde_json::from_str(json).map_err(|err| LevelError::Invalid {
+            file: file.to_string(),
+            line: err.line(),
+            column: err.column(),
+            message: strip_position(&err),
+        })
+    }
+
+    /// Loads and validates a level file.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(LevelError::Io)` if the file cannot be read.
+    /// * `Err(LevelError::Invalid)` as for [`Level::from_json`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Level, LevelError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| LevelError::Io(format!("{}: {err}", path.display())))?;
+        Level::from_json(&json, &path.display().to_string())
+    }
+
+    /// Returns the entity with the given name.
+    pub fn entity(&self, name: &str) -> Option<&EntityDef> {
+        self.entities.iter().find(|entity| entity.name == name)
+    }
+
+    /// Adds all entities as named sprites, positioned for the window of the world.
+    ///
+    /// Sprites with the same names are replaced.
+    pub fn spawn_into(&self, world: &mut World) {
+        for entity in &self.entities {
+            world
+                .sprites
+                .insert(entity.name.clone(), entity.sprite(world.window));
+        }
+    }
+
+    /// Spawns all entities with [`Sprite`], [`Name`] and [`Tags`] components.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The ECS world to spawn into.
+    /// * `window` - Size of the window the anchors refer to.
+    ///
+    /// # Returns
+    ///
+    /// The spawned entities in the order of the file.
+    pub fn spawn_entities(&self, world: &mut ecs::World, window: Size) -> Vec<ecs::Entity> {
+        self.entities
+            .iter()
+            .map(|def| {
+                let entity = world.spawn();
+                world.insert(entity, def.sprite(window));
+                world.insert(entity, Name(def.name.clone()));
+                world.insert(entity, Tags(def.tags.clone()));
+                entity
+            })
+            .collect()
+    }
+}
+
+/// Deserializes the entity list, checking every entity as soon as it is read.
+fn checked_entities<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<EntityDef>, D::Error> {
+    struct Entities;
+
+    impl<'de> Visitor<'de> for Entities {
+        type Value = Vec<EntityDef>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a list of entities")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut entities = Vec::new();
+            let mut names = HashSet::new();
+            while let Some(entity) = seq.next_element_seed(CheckedEntity(&mut names))? {
+                entities.push(entity);
+            }
+            Ok(entities)
+        }
+    }
+
+    deserializer.deserialize_seq(Entities)
+}
+
+/// Deserializes one entity and checks it with [`entity_problem`].
+///
+/// The check fails while the deserializer is still inside the entity object, so the
+/// error is reported at the end of that entity rather than somewhere later in the file.
+struct CheckedEntity<'a>(&'a mut HashSet<String>);
+
+impl<'de> DeserializeSeed<'de> for CheckedEntity<'_> {
+    type Value = EntityDef;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<EntityDef, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CheckedEntity<'_> {
+    type Value = EntityDef;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an entity")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<EntityDef, A::Error> {
+        let entity = EntityDef::deserialize(MapAccessDeserializer::new(map))?;
+        match entity_problem(&entity, self.0) {
+            Some(message) => Err(de::Error::custom(message)),
+            None => Ok(entity),
+        }
+    }
+}
+
+/// Returns what is wrong with an entity, remembering its name for the duplicate check.
+fn entity_problem(entity: &EntityDef, names: &mut HashSet<String>) -> Option<String> {
+    let name = &entity.name;
+    if name.is_empty() {
+        return Some("entity name is empty".to_string());
+    }
+    if !names.insert(name.clone()) {
+        return Some(format!("duplicate entity name `{name}`"));
+    }
+    let fields = SpriteFields {
+        size: entity.size,
+        color: entity.color,
+        mass: entity.mass,
+        restitution: entity.restitution,
+        friction: entity.friction,
+        linear_damping: entity.linear_damping,
+        collider: &entity.collider,
+    };
+    fields
+        .problem()
+        .map(|problem| format!("entity `{name}` {problem}"))
+}
+
+/// Returns the serde error message without the " at line L column C" suffix.
+fn strip_position(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Size = Size {
+        width: 200.0,
+        height: 100.0,
+    };
+
+    const LEVEL: &str = r#"{
+  "entities": [
+    { "name": "left", "pos": { "x": 10, "y": 20 }, "size": { "width": 5, "height": 30 } },
+    { "name": "right", "anchor": "top_right", "pos": { "x": -15, "y": 20 },
+      "size": { "width": 5, "height": 30 }, "velocity": { "dx": 1, "dy": 0 },
+      "color": { "r": 255, "g": 0, "b": 0 }, "body": "kinematic", "layer": 2, "mask": 4,
+      "trigger": true, "collider": { "shape": "circle", "radius": 2.5 }, "tags": ["ai"] }
+  ]
+}"#;
+
+    fn invalid_at(json: &str) -> (usize, usize, String) {
+        match Level::from_json(json, "test.json") {
+            Err(LevelError::Invalid {
+                line,
+                column,
+                message,
+                ..
+            }) => (line, column, message),
+            other => panic!("expected invalid level, got {other:?}"),
+        }
+    }
+
+    /// Verify that a level loads with defaults and anchored positions.
+    #[test]
+    fn test_load_level() {
+        let level = Level::from_json(LEVEL, "test.json").unwrap();
+
+        let left = level.entity("left").unwrap();
+        assert_eq!(left.velocity, resting());
+        assert_eq!(left.color, white());
+        assert_eq!((left.body, left.restitution), (BodyKind::Dynamic, 1.0));
+        assert_eq!((left.layer, left.mask, left.trigger), (DEFAULT_LAYER, ALL_LAYERS, false));
+        assert_eq!((left.mass, left.friction, left.linear_damping), (1.0, 0.0, 0.0));
+        assert_eq!(left.position(WINDOW), Pos { x: 10.0, y: 20.0 });
+        assert_eq!(left.collider, Collider::Sprite);
+
+        let right = level.entity("right").unwrap();
+        assert_eq!(right.position(WINDOW), Pos { x: 185.0, y: 20.0 });
+        assert!(right.has_tag("ai"));
+        assert_eq!(right.collider, Collider::Circle { radius: 2.5 });
+    }
+
+    /// Verify that JSON and field errors are reported with line and column.
+    #[test]
+    fn test_syntax_errors() {
+        let (line, _, message) = invalid_at("{\n  \"entities\": [\n    { \"name\": \"a\" ");
+        assert_eq!(line, 3);
+        assert!(message.contains("EOF"), "{message}");
+
+        let typo = LEVEL.replace("\"velocity\"", "\"velocty\"");
+        let (line, _, message) = invalid_at(&typo);
+        assert_eq!(line, 5);
+        assert!(message.contains("unknown field `velocty`"), "{message}");
+    }
+
+    /// Verify that invalid values are reported at the end of the entity they belong to.
+    #[test]
+    fn test_validation() {
+        let duplicate = LEVEL.replace("\"right\"", "\"left\"");
+        assert_eq!(
+            invalid_at(&duplicate),
+            (7, 89, "duplicate entity name `left`".to_string())
+        );
+
+        let empty = LEVEL.replace(
+            "\"width\": 5, \"height\": 30 } }",
+            "\"width\": 0, \"height\": 30 } }",
+        );
+        let (line, _, message) = invalid_at(&empty);
+        assert_eq!(
+            (line, message.as_str()),
+            (3, "entity `left` needs a positive size")
+        );
+
+        let color = LEVEL.replace("\"g\": 0", "\"g\": 300");
+        let error = Level::from_json(&color, "test.json").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "test.json:7:89: entity `right` has a color component outside 0 to 255"
+        );
+
+        let weightless = LEVEL.replace("\"layer\": 2", "\"mass\": 0, \"layer\": 2");
+        let (line, _, message) = invalid_at(&weightless);
+        assert_eq!(
+            (line, message.as_str()),
+            (7, "entity `right` needs a positive mass")
+        );
+
+        let slowing = LEVEL.replace("\"layer\": 2", "\"linear_damping\": -1, \"layer\": 2");
+        let (line, _, message) = invalid_at(&slowing);
+        assert_eq!(
+            (line, message.as_str()),
+            (7, "entity `right` needs a non-negative linear damping")
+        );
+
+        let pointless = LEVEL.replace("\"radius\": 2.5", "\"radius\": 0");
+        let (line, _, message) = invalid_at(&pointless);
+        assert_eq!(
+            (line, message.as_str()),
+            (
+                7,
+                "entity `right` needs a collider with a positive, finite size or a convex polygon"
+            )
+        );
+    }
+
+    /// Verify that levels spawn into the named world and the ECS world.
+    #[test]
+    fn test_spawn() {
+        let level = Level::from_json(LEVEL, "test.json").unwrap();
+
+        let mut world = World::empty();
+        world.window = WINDOW;
+        level.spawn_into(&mut world);
+        assert_eq!(world.get_sprite("right").pos, Pos { x: 185.0, y: 20.0 });
+        assert_eq!(world.get_sprite("right").body, BodyKind::Kinematic);
+        assert!(world.get_sprite("right").trigger);
+        assert!(!world.get_sprite("right").interacts_with(world.get_sprite("left")));
+
+        let mut ecs_world = ecs::World::new();
+        let entities = level.spawn_entities(&mut ecs_world, WINDOW);
+        assert_eq!(entities.len(), 2);
+        assert_eq!(
+            *ecs_world.get::<Name>(entities[1]).unwrap(),
+            Name("right".to_string())
+        );
+        assert_eq!(ecs_world.get::<Tags>(entities[1]).unwrap().0, ["ai"]);
+        assert_eq!(
+            ecs_world.get::<Sprite>(entities[0]).unwrap().size,
+            Size {
+                width: 5.0,
+                height: 30.0
+            }
+        );
+    }
+}
@@ -1,23 +1,108 @@
 //! Game engine Rust interface.
 //!
 //! This library provides Rust bindings and utilities for interacting with the underlying
-//! C-based game engine. It exposes FFI bindings, macros for game loop management, and
-//! test cases demonstrating usage.
+//! C-based game engine. It exposes FFI bindings, macros for game loop management,
+//! a sprite world with movement and collision handling, an entity-component-system
+//! world for games that need more than named sprites, and test cases demonstrating usage.
+//!
+//! With the `capi` feature the world and loop APIs are also exported to C, see `capi`.
+//!
+//! # Features
+//!
+//! - `gl-backend` (default): window, rendering and input through the C library.
+//! - `headless`: the same API backed by a pure Rust stand-in without a window.
+//! - `audio`: multi-voice sound mixer with buses and a retro effect synth, output through rodio.
+//! - `net`: blocking HTTP downloads.
+//! - `serde`: `Serialize`/`Deserialize` for the sprite types and sound presets, JSON level files
+//!   and versioned save files.
+//!
+//! Without `gl-backend` and `headless` only the pure logic modules ([`world`], [`ecs`], [`scene`],
+//! [`assets`], [`input`], [`replay`], [`clock`], [`rng`], [`hash`], [`sprite`], [`math`],
+//! [`movement`], [`collision`], [`collider`], [`broad_phase`], [`physics`]) are built, with no
+//! native dependencies.
 
+pub mod assets;
+#[cfg(feature = "audio")]
+pub mod audio;
+#[cfg(feature = "gl-backend")]
+mod bindings;
+#[cfg(all(feature = "headless", not(feature = "gl-backend")))]
+mod headless;
+#[cfg(all(feature = "headless", not(feature = "gl-backend")))]
+use headless as bindings;
+pub mod broad_phase;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod clock;
+pub mod collider;
+pub mod collision;
+pub mod ecs;
+#[cfg(backend)]
+pub mod engine;
+#[cfg(backend)]
+pub use engine::{Engine, EngineError};
+#[cfg(backend)]
 pub mod ffi;
+#[cfg(backend)]
 pub use ffi::*;
+pub mod hash;
+pub mod input;
+#[cfg(feature = "serde")]
+pub mod level;
+#[cfg(backend)]
 #[macro_use]
 pub mod macros;
+pub mod math;
+pub mod movement;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod physics;
+pub mod replay;
+pub mod rng;
+#[cfg(feature = "serde")]
+pub mod save;
+pub mod scene;
+pub mod sprite;
+pub mod world;
 
-#[cfg(test)]
+#[cfg(all(test, backend))]
 mod tests {
     use super::*;
 
+    /// Test: Layout of the generated `Sprite` matches what the safe wrappers expect.
+    ///
+    /// Fails when `opengl_wrapper_lib.h` changes the struct, so `ffi.rs` gets reviewed.
+    #[test]
+    fn test_sprite_layout() {
+        use std::mem::{align_of, offset_of, size_of};
+
+        assert_eq!(size_of::<ffi::Sprite>(), 28);
+        assert_eq!(align_of::<ffi::Sprite>(), 4);
+        assert_eq!(offset_of!(ffi::Sprite, width), 0);
+        assert_eq!(offset_of!(ffi::Sprite, height), 4);
+        assert_eq!(offset_of!(ffi::Sprite, color), 8);
+        assert_eq!(offset_of!(ffi::Sprite, x), 20);
+        assert_eq!(offset_of!(ffi::Sprite, y), 24);
+    }
+
+    /// Test: The GLFW window stays opaque on the Rust side.
+    #[test]
+    fn test_window_is_opaque() {
+        assert_eq!(std::mem::size_of::<ffi::GLFWwindow>(), 0);
+    }
+
+    /// Test: Window titles with NUL bytes are rejected before the C library is called.
+    #[test]
+    fn test_invalid_title() {
+        let result = ffi::rust_create_game_window("bad\0title", 100, 100);
+        assert!(matches!(result, Err(EngineError::InvalidTitle)));
+    }
+
     /// Test: Simple game loop.
     #[test]
     #[ignore]
     fn test_simple_game_loop() {
-        start_window_and_game_loop!({}, {}, {});
+        start_window_and_game_loop!(engine, {}, {}, {});
     }
 
     /// Test: Sprite rendering.
@@ -25,9 +110,10 @@ mod tests {
     #[ignore]
     fn test_sprite_rendering() {
         start_window_and_game_loop!(
+            engine,
             {},
             {
-                spawn_sprite!(100.0, 100.0, 100, 100, 255, 0, 0);
+                spawn_sprite!(engine, 100.0, 100.0, 100, 100, 255, 0, 0);
             },
             {}
         );
@@ -44,21 +130,23 @@ mod tests {
         let mut window_cleared = false;
 
         start_window_and_game_loop!(
+            engine,
             {},
             {
                 let elapsed = start.elapsed();
                 let cond = elapsed < std::time::Duration::from_secs(2);
 
                 if cond {
-                    ffi::rust_render_sprite(sprite1);
-                    tick!();
+                    // SAFETY: both sprites come from `rust_create_sprite`.
+                    unsafe { ffi::rust_render_sprite(&engine, sprite1) };
+                    tick!(engine);
                 } else {
                     if !window_cleared {
-                        ffi::rust_clear_screen();
+                        ffi::rust_clear_screen(&engine);
                         window_cleared = true;
                     }
-                    ffi::rust_render_sprite(sprite2);
-                    tick!();
+                    unsafe { ffi::rust_render_sprite(&engine, sprite2) };
+                    tick!(engine);
                 }
             },
             {}
@@ -88,10 +176,10 @@ mod tests {
                 }
             }
 
-            fn check_key_pressed(&mut self) -> bool {
+            fn check_key_pressed(&mut self, engine: &Engine) -> bool {
                 let mut result = false;
                 for (key, pressed) in self.keys_pressed.iter_mut() {
-                    on_key_press!(ffi::rust_get_window(), *key, {
+                    on_key_press!(engine, *key, {
                         *pressed = true;
                         result = true;
                     });
@@ -108,11 +196,12 @@ mod tests {
                 true
             }
 
-            fn game_loop_start(&mut self) {
-                let render = self.check_key_pressed();
-                ffi::rust_clear_screen();
+            fn game_loop_start(&mut self, engine: &Engine) {
+                let render = self.check_key_pressed(engine);
+                ffi::rust_clear_screen(engine);
                 if render {
-                    ffi::rust_render_sprite(self.sprite);
+                    // SAFETY: the sprite comes from `rust_create_sprite`.
+                    unsafe { ffi::rust_render_sprite(engine, self.sprite) };
                 }
             }
 
@@ -127,9 +216,10 @@ mod tests {
         let mut game = Game::new();
 
         start_window_and_game_loop!(
+            engine,
             {},
             {
-                game.game_loop_start();
+                game.game_loop_start(&engine);
                 let run = game.game_loop_end();
                 if !run {
                     break;
@@ -153,9 +243,10 @@ mod tests {
             )
         }
 
-        let sprite1 = spawn_sprite!(100.0, 100.0, 100, 100, 255, 0, 0);
+        let sprite1 = ffi::rust_create_sprite(100.0, 100.0, 100, 100, 255, 0, 0);
 
         start_window_and_game_loop!(
+            engine,
             "My game",
             500,
             500,
@@ -163,7 +254,10 @@ mod tests {
             {
                 (x, y) = move_pos(x, y);
 
-                move_sprite!(sprite1, x, y, true);
+                // SAFETY: the sprite comes from `rust_create_sprite`.
+                unsafe {
+                    move_sprite!(engine, sprite1, x, y, true);
+                }
             },
             {}
         );
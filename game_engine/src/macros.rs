@@ -7,36 +7,49 @@
 ///
 /// # Example
 /// ```ignore
-/// let sprite = spawn_sprite!(100.0, 100.0, 50, 50, 255, 0, 0);
+/// let sprite = spawn_sprite!(engine, 100.0, 100.0, 50, 50, 255, 0, 0);
 /// ```
 #[macro_export]
 macro_rules! spawn_sprite {
-    ($x:expr, $y:expr, $width:expr, $height:expr, $r:expr, $g:expr, $b:expr) => {{
+    ($engine:expr, $x:expr, $y:expr, $width:expr, $height:expr, $r:expr, $g:expr, $b:expr) => {{
+        let engine: &$crate::Engine = &$engine;
         let sprite = ffi::rust_create_sprite($x, $y, $width, $height, $r, $g, $b);
-        ffi::rust_render_sprite(sprite);
+        // SAFETY: the sprite was just created by `rust_create_sprite`.
+        unsafe {
+            ffi::rust_render_sprite(engine, sprite);
+        }
         sprite
     }};
 }
 
 /// Moves a sprite to a new position and renders it. Optionally clears the screen before moving.
 ///
+/// The macro calls the unsafe sprite functions of [`ffi`](crate::ffi), so it must be
+/// used inside an `unsafe` block.
+///
+/// # Safety
+/// `sprite` must be null or a pointer returned by `spawn_sprite!` or
+/// [`rust_create_sprite`](crate::ffi::rust_create_sprite).
+///
 /// # Example
 /// ```ignore
-/// move_sprite!(sprite, 200.0, 200.0);
-/// move_sprite!(sprite, 200.0, 200.0, true);
+/// unsafe {
+///     move_sprite!(engine, sprite, 200.0, 200.0);
+///     move_sprite!(engine, sprite, 200.0, 200.0, true);
+/// }
 /// ```
 #[macro_export]
 macro_rules! move_sprite {
-    ($sprite:expr, $x:expr, $y:expr) => {
+    ($engine:expr, $sprite:expr, $x:expr, $y:expr) => {
         ffi::rust_update_sprite_position($sprite, $x, $y);
-        ffi::rust_render_sprite($sprite);
+        ffi::rust_render_sprite(&$engine, $sprite);
     };
-    ($sprite:expr, $x:expr, $y:expr, $clear:expr) => {
+    ($engine:expr, $sprite:expr, $x:expr, $y:expr, $clear:expr) => {
         if $clear {
-            ffi::rust_clear_screen();
+            ffi::rust_clear_screen(&$engine);
         }
         ffi::rust_update_sprite_position($sprite, $x, $y);
-        ffi::rust_render_sprite($sprite);
+        ffi::rust_render_sprite(&$engine, $sprite);
     };
 }
 
@@ -44,26 +57,30 @@ macro_rules! move_sprite {
 ///
 /// # Example
 /// ```ignore
-/// tick!();
+/// tick!(engine);
 /// ```
 #[macro_export]
 macro_rules! tick {
-    () => {
-        ffi::rust_update_game_window();
+    ($engine:expr) => {
+        ffi::rust_update_game_window(&$engine);
         std::thread::sleep(std::time::Duration::from_millis(16));
     };
 }
 
-/// Executes a function if the specified key is pressed in the given window.
+/// Executes a block or function if the specified key is pressed in the game window.
 ///
 /// # Example
 /// ```ignore
-/// on_key_press!(window, ffi::GLFW_KEY_SPACE, || { println!("Space pressed!"); });
+/// on_key_press!(engine, ffi::GLFW_KEY_SPACE, { println!("Space pressed!"); });
+/// on_key_press!(engine, ffi::GLFW_KEY_SPACE, || { println!("Space pressed!"); });
 /// ```
 #[macro_export]
 macro_rules! on_key_press {
-    ($window:expr, $key:expr, $function:expr) => {
-        if ffi::rust_get_key($window, $key) == ffi::GLFW_PRESS {
+    ($engine:expr, $key:expr, $body:block) => {
+        if ffi::rust_get_key(&$engine, $key) == ffi::GLFW_PRESS $body
+    };
+    ($engine:expr, $key:expr, $function:expr) => {
+        if ffi::rust_get_key(&$engine, $key) == ffi::GLFW_PRESS {
             $function()
         }
     };
@@ -71,9 +88,17 @@ macro_rules! on_key_press {
 
 /// Starts the game window and runs the main game loop, with optional initialization and cleanup blocks.
 ///
+/// The first argument names the [`Engine`](crate::Engine) binding that is visible
+/// inside the three blocks.
+///
+/// # Panics
+///
+/// Panics if the game window cannot be created.
+///
 /// # Example
 /// ```ignore
 /// start_window_and_game_loop!(
+///     engine,
 ///     "My Game", 800, 600,
 ///     { /* init code */ },
 ///     { /* per-frame code */ },
@@ -81,29 +106,31 @@ macro_rules! on_key_press {
 /// );
 /// ```
 ///
-/// If no arguments are given, defaults to a test window.
-/// ```
-/// start_window_and_game_loop!({},{},{});
+/// If no window arguments are given, defaults to a test window.
+/// ```ignore
+/// start_window_and_game_loop!(engine, {}, {}, {});
 /// ```
 #[macro_export]
 macro_rules! start_window_and_game_loop {
-    ($game_name:expr, $width:expr, $height:expr, $init:block, $enter:block, $exit:block) => {
-        ffi::rust_create_game_window($game_name, $width, $height);
+    ($engine:ident, $game_name:expr, $width:expr, $height:expr, $init:block, $enter:block, $exit:block) => {
+        let $engine = ffi::rust_create_game_window($game_name, $width, $height)
+            .expect("create game window");
 
         $init
-        while !ffi::rust_window_should_close() {
+        while !ffi::rust_window_should_close(&$engine) {
             $enter
-            tick!();
+            tick!($engine);
         }
         $exit
     };
-    ($init:block, $enter:block, $exit:block) => {
-        ffi::rust_create_game_window("test game 1", 1024, 768);
+    ($engine:ident, $init:block, $enter:block, $exit:block) => {
+        let $engine = ffi::rust_create_game_window("test game 1", 1024, 768)
+            .expect("create game window");
 
         $init
-        while !ffi::rust_window_should_close() {
+        while !ffi::rust_window_should_close(&$engine) {
             $enter
-            tick!();
+            tick!($engine);
         }
         $exit
     };
@@ -0,0 +1,194 @@
+//! 2D vector math for collision and physics.
+//!
+//! [`Vec2`] is a plain `f32` vector with the usual operators. It converts from and
+//! to the sprite types [`Pos`] and [`Velocity`], so geometry code can work on vectors
+//! while sprites keep their named fields. [`Aabb`] is an axis-aligned box.
+
+use crate::sprite::{Pos, Velocity};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A 2D vector.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the z component of the 3D cross product, positive if `other` points
+    /// clockwise from `self` on screen, where y points down.
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Returns the vector scaled to length 1, or `None` for the zero vector.
+    pub fn normalized(self) -> Option<Vec2> {
+        let length = self.length();
+        (length > 0.0).then(|| self * (1.0 / length))
+    }
+
+    /// Returns the vector rotated by `angle` radians, clockwise on screen.
+    pub fn rotated(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Returns the vector mirrored at a surface with the unit normal `normal`.
+    pub fn reflect(self, normal: Vec2) -> Vec2 {
+        self - normal * (2.0 * self.dot(normal))
+    }
+}
+
+/// An axis-aligned box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// Corner with the smallest coordinates, the top left one on screen.
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns `true` if the boxes overlap or touch.
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// Returns `true` if `point` is inside the box or on its edge.
+    pub fn contains(&self, point: Vec2) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// Returns the box grown by `margin` on every side.
+    pub fn expand(&self, margin: f32) -> Aabb {
+        let margin = Vec2::new(margin, margin);
+        Aabb::new(self.min - margin, self.max + margin)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: f32) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl From<Pos> for Vec2 {
+    fn from(pos: Pos) -> Self {
+        Vec2::new(pos.x, pos.y)
+    }
+}
+
+impl From<Vec2> for Pos {
+    fn from(v: Vec2) -> Self {
+        Pos { x: v.x, y: v.y }
+    }
+}
+
+impl From<Velocity> for Vec2 {
+    fn from(velocity: Velocity) -> Self {
+        Vec2::new(velocity.dx, velocity.dy)
+    }
+}
+
+impl From<Vec2> for Velocity {
+    fn from(v: Vec2) -> Self {
+        Velocity { dx: v.x, dy: v.y }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the operators and that reflecting flips only the normal component.
+    #[test]
+    fn test_vec2() {
+        let v = Vec2::new(3.0, -4.0);
+        assert_eq!(v + Vec2::new(1.0, 1.0), Vec2::new(4.0, -3.0));
+        assert_eq!(v - v, Vec2::ZERO);
+        assert_eq!(-v * 2.0, Vec2::new(-6.0, 8.0));
+        assert_eq!(v.dot(Vec2::new(1.0, 0.0)), 3.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.normalized(), Some(Vec2::new(0.6, -0.8)));
+        assert_eq!(Vec2::ZERO.normalized(), None);
+        assert_eq!(Vec2::new(1.0, 0.0).cross(Vec2::new(0.0, 1.0)), 1.0);
+        let turned = Vec2::new(1.0, 0.0).rotated(std::f32::consts::FRAC_PI_2);
+        assert!((turned - Vec2::new(0.0, 1.0)).length() < 1e-6);
+        assert_eq!(v.reflect(Vec2::new(0.0, 1.0)), Vec2::new(3.0, 4.0));
+        assert_eq!(Velocity::from(v), Velocity { dx: 3.0, dy: -4.0 });
+    }
+
+    /// Verify that boxes that only touch overlap, and that expanding grows every side.
+    #[test]
+    fn test_aabb() {
+        let a = Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+        let b = Aabb::new(Vec2::new(10.0, 5.0), Vec2::new(20.0, 6.0));
+        assert!(a.overlaps(&b) && b.overlaps(&a));
+        assert!(!a.overlaps(&Aabb::new(Vec2::new(10.5, 0.0), Vec2::new(11.0, 1.0))));
+        assert!(a.contains(Vec2::new(10.0, 0.0)));
+        assert!(!a.contains(Vec2::new(-0.1, 5.0)));
+        assert_eq!(
+            a.expand(1.0),
+            Aabb::new(Vec2::new(-1.0, -1.0), Vec2::new(11.0, 11.0))
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Movement logic.
+//!
+//! This module moves sprites according to their velocities, either in the named
+//! sprite [`World`] or as a system over [`ecs::World`] sprite components.
+//! Collision detection and response live in [`crate::collision`].
+//!
+//! In the named sprite world, movement is continuous: sprites that would touch during
+//! the frame stop at the contact point and bounce off, however long the frame was.
+//! Only sprites whose paths through the frame come close are swept against each other.
+//! The sweeps test the boxes around the colliders, so a circle or rotated box bounces
+//! where its box touches; [`crate::collision::collision`] then resolves the exact
+//! shapes if they overlap.
+//! Velocities stay constant here; [`crate::physics::Physics`] adds gravity, forces and
+//! damping, and calls [`move_objects`] in fixed steps.
+
+use crate::broad_phase::SpatialHash;
+use crate::collider::Collider;
+use crate::collision::{self, Border, CollisionEvent, Hit, Manifold, Touched};
+use crate::ecs;
+use crate::math::{Aabb, Vec2};
+use crate::sprite::{BodyKind, Pos, Size, Sprite};
+use crate::world::World;
+
+/// Most impacts handled in one frame. Sprites squeezed between others could bounce
+/// endlessly; after this many impacts the rest of the frame moves without sweeping.
+const MAX_IMPACTS: usize = 32;
+
+/// The earliest impact in the rest of a frame.
+struct Impact {
+    sprite: String,
+    /// The other sprite, or `None` for a window border.
+    other: Option<String>,
+    hit: Hit,
+}
+
+/// Moves all sprites in the world according to their velocities and the time delta.
+///
+/// Sprites are swept with [`collision::sweep`] and [`collision::sweep_border`]: the
+/// world advances to the earliest contact, the touching sprites bounce off each other
+/// along the contact normal, and movement continues for the rest of `dt`. Static
+/// sprites do not move, and only contacts with a dynamic sprite count. Sprites whose
+/// layers exclude each other and triggers pass through; overlaps with triggers are
+/// reported by [`collision::collision`]. Pairs are only swept if the areas the two
+/// sprites cover during the frame overlap.
+///
+/// # Arguments
+///
+/// * `world` - Mutable reference to the game world containing all sprites.
+/// * `dt` - The time delta since the last update (in milliseconds).
+///
+/// # Returns
+///
+/// * An event for every contact on the way, in the order they happened. They are also
+///   passed to the world's subscribers.
+pub fn move_objects(world: &mut World, dt: f32) -> Vec<CollisionEvent> {
+    let mut names: Vec<String> = world.sprites.keys().cloned().collect();
+    // Sorted, so simultaneous impacts resolve in the same order in every run.
+    names.sort();
+
+    let mut paths = SpatialHash::default();
+    for name in &names {
+        paths.insert(name.clone(), path(&world.sprites[name], dt));
+    }
+
+    let mut events = Vec::new();
+    let mut left = dt;
+    for _ in 0..MAX_IMPACTS {
+        let Some(impact) = next_impact(world, &names, &paths.pairs(), left) else {
+            break;
+        };
+        advance(world, impact.hit.time);
+        left -= impact.hit.time;
+        events.push(bounce(world, &impact));
+
+        // The other sprites keep their velocities, so the rest of their paths lies
+        // within the old areas; only the bounced sprites need new ones.
+        for name in [Some(&impact.sprite), impact.other.as_ref()].into_iter().flatten() {
+            paths.insert(name.clone(), path(&world.sprites[name], left));
+        }
+    }
+    advance(world, left);
+
+    for sprite in world.sprites.values() {
+        sprite.update_pos();
+    }
+    world.publish(&events);
+    events
+}
+
+/// Returns the area the collider of `sprite` covers while moving for `dt` milliseconds.
+fn path(sprite: &Sprite, dt: f32) -> Aabb {
+    let bounds = sprite.collider_bounds();
+    if sprite.body == BodyKind::Static {
+        return bounds;
+    }
+    let reach = Vec2::from(sprite.velocity) * dt;
+    Aabb::new(
+        bounds.min + Vec2::new(reach.x.min(0.0), reach.y.min(0.0)),
+        bounds.max + Vec2::new(reach.x.max(0.0), reach.y.max(0.0)),
+    )
+}
+
+/// Returns the earliest impact within `dt` of a sprite with a border or of a candidate
+/// pair from `pairs`; of simultaneous ones the first in `names`.
+fn next_impact(
+    world: &World,
+    names: &[String],
+    pairs: &[(String, String)],
+    dt: f32,
+) -> Option<Impact> {
+    let mut earliest: Option<Impact> = None;
+    let mut consider = |sprite: &String, other: Option<&String>, hit: Option<Hit>| {
+        if let Some(hit) = hit
+            && earliest.as_ref().is_none_or(|e| hit.time < e.hit.time)
+        {
+            earliest = Some(Impact {
+                sprite: sprite.clone(),
+                other: other.cloned(),
+                hit,
+            });
+        }
+    };
+
+    let mut pairs = pairs.iter().peekable();
+    for name_a in names {
+        let a = &world.sprites[name_a];
+        if a.touches_borders() {
+            consider(name_a, None, collision::sweep_border(a, world.window, dt));
+        }
+        while let Some((_, name_b)) = pairs.next_if(|(first, _)| first == name_a) {
+            let b = &world.sprites[name_b];
+            if collision::collides(a, b) {
+                consider(name_a, Some(name_b), collision::sweep(a, b, dt));
+            }
+        }
+    }
+    earliest
+}
+
+/// Moves every sprite that is not static by its velocity for `dt` milliseconds.
+fn advance(world: &mut World, dt: f32) {
+    for sprite in world.sprites.values_mut() {
+        if sprite.body == BodyKind::Static {
+            continue;
+        }
+        sprite.pos.x += dt * sprite.velocity.dx;
+        sprite.pos.y += dt * sprite.velocity.dy;
+    }
+}
+
+/// Bounces the sprites of an impact off each other with [`collision::resolve`]. A
+/// sprite that hit a border is put exactly onto it, so the contact lies on the border.
+fn bounce(world: &mut World, impact: &Impact) -> CollisionEvent {
+    let manifold = |sprite: &Sprite| Manifold {
+        normal: impact.hit.normal,
+        depth: 0.0,
+        point: collision::face_center(sprite, impact.hit.normal),
+    };
+
+    let Some(other) = &impact.other else {
+        let window = world.window;
+        let sprite = world.sprites.get_mut(&impact.sprite).unwrap();
+        collision::resolve_border(sprite, &manifold(sprite));
+        place_against_border(sprite, window, impact.hit.normal);
+        return CollisionEvent {
+            sprite: impact.sprite.clone(),
+            other: Touched::Border(Border::from_normal(impact.hit.normal)),
+            normal: impact.hit.normal,
+            point: collision::face_center(sprite, impact.hit.normal),
+            trigger: false,
+        };
+    };
+
+    let [Some(a), Some(b)] = world.sprites.get_disjoint_mut([&impact.sprite, other]) else {
+        unreachable!("impacts are between two different sprites");
+    };
+    collision::resolve(a, b, &manifold(a));
+    CollisionEvent {
+        sprite: impact.sprite.clone(),
+        other: Touched::Sprite(other.clone()),
+        normal: impact.hit.normal,
+        point: collision::overlap_center(a, b),
+        trigger: false,
+    }
+}
+
+/// Moves `sprite` so the box around its collider lies on the window border with the
+/// inward `normal`.
+fn place_against_border(sprite: &mut Sprite, window: Size, normal: Vec2) {
+    // The collider box relative to the sprite position.
+    let size = Vec2::new(sprite.size.width, sprite.size.height);
+    let local = match sprite.collider {
+        Collider::Sprite => Aabb::new(Vec2::ZERO, size),
+        _ => sprite.collider.shape(Pos { x: 0.0, y: 0.0 }, size).bounds(),
+    };
+    if normal.x > 0.0 {
+        sprite.pos.x = 0.0 - local.min.x;
+    } else if normal.x < 0.0 {
+        sprite.pos.x = window.width - local.max.x;
+    } else if normal.y > 0.0 {
+        sprite.pos.y = 0.0 - local.min.y;
+    } else {
+        sprite.pos.y = window.height - local.max.y;
+    }
+}
+
+/// System that moves every entity with a [`Sprite`] component according to its velocity.
+///
+/// # Arguments
+///
+/// * `world` - Mutable reference to the ECS world.
+/// * `dt` - The time delta since the last update (in milliseconds).
+pub fn move_system(world: &mut ecs::World, dt: f32) {
+    world.query::<&mut Sprite>(|_, sprite| {
+        sprite.pos.x += dt * sprite.velocity.dx;
+        sprite.pos.y += dt * sprite.velocity.dy;
+
+        sprite.update_pos();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::{Color, Pos, Size, Velocity};
+    use crate::world::World;
+
+    fn make_sprite(pos: Pos, velocity: Velocity, size: Size) -> crate::sprite::Sprite {
+        let color = Color { r: 0, g: 0, b: 0 };
+        crate::sprite::Sprite::new(pos, velocity, color, size)
+    }
+
+    #[test]
+    fn test_move_objects_moves_sprite() {
+        let mut world = World::empty();
+        let velocity = Velocity { dx: 1.0, dy: 2.0 };
+        let pos = Pos { x: 0.0, y: 0.0 };
+        let size = Size { width: 10.0, height: 10.0 };
+        world.sprites.insert(
+            "test".to_string(),
+            make_sprite(pos, velocity, size),
+        );
+        move_objects(&mut world, 1.0);
+        let sprite = &world.sprites["test"];
+        assert_eq!(sprite.pos.x, 1.0);
+        assert_eq!(sprite.pos.y, 2.0);
+    }
+
+    /// Verify that a fast sprite bounces off a thin one instead of passing through it.
+    #[test]
+    fn test_move_objects_no_tunneling() {
+        let mut world = World::empty();
+        world.sprites.insert(
+            "ball".to_string(),
+            make_sprite(
+                Pos { x: 0.0, y: 100.0 },
+                Velocity { dx: 5.0, dy: 0.0 },
+                Size { width: 10.0, height: 10.0 },
+            ),
+        );
+        world.sprites.insert(
+            "paddle".to_string(),
+            make_sprite(
+                Pos { x: 200.0, y: 50.0 },
+                Velocity { dx: 0.0, dy: 0.0 },
+                Size { width: 10.0, height: 100.0 },
+            ),
+        );
+        world.sprites.get_mut("paddle").unwrap().body = BodyKind::Kinematic;
+
+        // The ball touches the paddle after 38 ms and moves back for the other 12 ms.
+        let events = move_objects(&mut world, 50.0);
+        assert_eq!(
+            events,
+            vec![CollisionEvent {
+                sprite: "ball".to_string(),
+                other: Touched::Sprite("paddle".to_string()),
+                normal: Vec2::new(-1.0, 0.0),
+                point: Pos { x: 200.0, y: 105.0 },
+                trigger: false,
+            }]
+        );
+        let ball = &world.sprites["ball"];
+        assert_eq!(ball.pos, Pos { x: 130.0, y: 100.0 });
+        assert_eq!(ball.velocity, Velocity { dx: -5.0, dy: 0.0 });
+        assert_eq!(world.sprites["paddle"].pos, Pos { x: 200.0, y: 50.0 });
+    }
+
+    /// Verify that a fast circle bounces off a thin paddle instead of passing through
+    /// it, and off a border with its circle rather than its sprite rectangle.
+    #[test]
+    fn test_move_objects_circle_no_tunneling() {
+        let mut world = World::empty();
+        let mut ball = make_sprite(
+            Pos { x: 0.0, y: 100.0 },
+            Velocity { dx: 5.0, dy: 0.0 },
+            Size {
+                width: 16.0,
+                height: 16.0,
+            },
+        );
+        ball.collider = Collider::Circle { radius: 4.0 };
+        world.sprites.insert("ball".to_string(), ball);
+        let mut paddle = make_sprite(
+            Pos { x: 200.0, y: 50.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size {
+                width: 2.0,
+                height: 100.0,
+            },
+        );
+        paddle.body = BodyKind::Kinematic;
+        world.sprites.insert("paddle".to_string(), paddle);
+
+        // The circle spans 4 to 12 in the sprite, so it touches the paddle after
+        // 37.6 ms and moves back for the other 12.4 ms.
+        let events = move_objects(&mut world, 50.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].other, Touched::Sprite("paddle".to_string()));
+        assert_eq!(events[0].normal, Vec2::new(-1.0, 0.0));
+        let ball = &world.sprites["ball"];
+        assert!((ball.pos.x - 126.0).abs() < 1e-3, "ball at {}", ball.pos.x);
+        assert_eq!(ball.velocity, Velocity { dx: -5.0, dy: 0.0 });
+
+        // Moving left, the circle reaches the border after 1 ms, with the rectangle
+        // 4 pixels beyond it, and moves back for the other 1 ms.
+        world.sprites.get_mut("ball").unwrap().pos.x = 1.0;
+        let events = move_objects(&mut world, 2.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].border(), Some(Border::Left));
+        assert_eq!(events[0].point, Pos { x: 0.0, y: 108.0 });
+        let ball = &world.sprites["ball"];
+        assert_eq!(ball.pos.x, 1.0);
+        assert_eq!(ball.velocity, Velocity { dx: 5.0, dy: 0.0 });
+    }
+
+    /// Verify that a sprite reflects off a border on the axis it hit.
+    #[test]
+    fn test_move_objects_border() {
+        let mut world = World::empty();
+        world.sprites.insert(
+            "ball".to_string(),
+            make_sprite(
+                Pos { x: 100.0, y: 748.0 },
+                Velocity { dx: 1.0, dy: 1.0 },
+                Size { width: 10.0, height: 10.0 },
+            ),
+        );
+
+        let events = move_objects(&mut world, 16.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].border(), Some(Border::Bottom));
+        assert_eq!(events[0].normal, Vec2::new(0.0, -1.0));
+        assert_eq!(events[0].point, Pos { x: 115.0, y: 768.0 });
+        let ball = &world.sprites["ball"];
+        assert_eq!(ball.pos, Pos { x: 116.0, y: 752.0 });
+        assert_eq!(ball.velocity, Velocity { dx: 1.0, dy: -1.0 });
+    }
+
+    /// Verify that the movement system moves sprite entities by their velocity.
+    #[test]
+    fn test_move_system() {
+        let mut world = ecs::World::new();
+        let entity = world.spawn();
+        world.insert(
+            entity,
+            make_sprite(
+                Pos { x: 1.0, y: 1.0 },
+                Velocity { dx: 0.5, dy: -1.0 },
+                Size { width: 10.0, height: 10.0 },
+            ),
+        );
+
+        move_system(&mut world, 2.0);
+
+        let sprite = world.get::<Sprite>(entity).unwrap();
+        assert_eq!(sprite.pos, Pos { x: 2.0, y: -1.0 });
+    }
+}
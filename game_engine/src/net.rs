@@ -0,0 +1,33 @@
+//! Blocking HTTP downloads, enabled by the `net` feature.
+//!
+//! The requests block the calling thread, so games should run them on a worker
+//! thread and send the results to the main thread.
+
+use serde::de::DeserializeOwned;
+
+/// Downloads the body of `url` as text.
+///
+/// # Returns
+///
+/// * `Ok(String)` with the response body.
+/// * `Err(String)` if the download or the text decoding fails.
+pub fn get_text(url: &str) -> Result<String, String> {
+    match reqwest::blocking::get(url) {
+        Ok(response) => match response.text() {
+            Ok(text) => Ok(text),
+            Err(err) => Err(format!("Error reading response from {url}: {err}")),
+        },
+        Err(err) => Err(format!("Error downloading {url}: {err}")),
+    }
+}
+
+/// Downloads the body of `url` and deserializes it from JSON.
+///
+/// # Returns
+///
+/// * `Ok(T)` with the deserialized value.
+/// * `Err(String)` if the download or the parsing fails.
+pub fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let text = get_text(url)?;
+    serde_json::from_str(&text).map_err(|err| format!("Error parsing json: {err}"))
+}
@@ -0,0 +1,336 @@
+//! Rigid-body physics for the named sprite world.
+//!
+//! [`Physics`] advances a [`World`] in fixed steps, so the simulation does not depend
+//! on the frame rate. Each step accelerates the dynamic sprites by gravity and the
+//! forces applied to them, damps their velocities, and then moves and collides all
+//! sprites with [`movement::move_objects`] and [`collision::collision`].
+//!
+//! The body of a simulated object is its [`Sprite`](crate::sprite::Sprite): the [`BodyKind`] decides how it
+//! takes part, and mass, restitution, friction and linear damping are sprite fields,
+//! so they can come from level and save files. Static sprites never move, kinematic
+//! ones move by their velocity alone, and only dynamic ones feel gravity, forces,
+//! impulses and collisions.
+//!
+//! Units are those of the world: pixels and milliseconds, so velocities are in
+//! pixels per millisecond and accelerations in pixels per millisecond squared.
+//!
+//! [`BodyKind`]: crate::sprite::BodyKind
+
+use crate::collision::{self, CollisionEvent};
+use crate::math::Vec2;
+use crate::movement;
+use crate::world::World;
+use std::collections::HashMap;
+
+/// Step size used by [`Physics::default`], in milliseconds: 120 steps per second.
+pub const DEFAULT_STEP: f32 = 1000.0 / 120.0;
+
+/// Most steps run by one [`Physics::update`]. After a long stall the rest of the time
+/// is dropped rather than simulated, so a slow frame cannot cause ever slower ones.
+const MAX_STEPS: u32 = 8;
+
+/// Fixed-rate simulation of the sprites of a [`World`].
+pub struct Physics {
+    /// Acceleration of every dynamic sprite, e.g. `Vec2::new(0.0, 0.002)` to pull
+    /// downwards on screen.
+    pub gravity: Vec2,
+    step_size: f32,
+    /// Time not simulated yet, less than one step after an update.
+    accumulator: f32,
+    forces: HashMap<String, Vec2>,
+}
+
+impl Physics {
+    /// Creates a simulation without gravity that advances in steps of `step_size`
+    /// milliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_size` is not positive.
+    pub fn new(step_size: f32) -> Self {
+        assert!(
+            step_size > 0.0,
+            "step size must be positive, got {step_size}"
+        );
+        Self {
+            gravity: Vec2::ZERO,
+            step_size,
+            accumulator: 0.0,
+            forces: HashMap::new(),
+        }
+    }
+
+    /// Returns the simulation with the given gravity.
+    pub fn with_gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Returns the length of one step in milliseconds.
+    pub fn step_size(&self) -> f32 {
+        self.step_size
+    }
+
+    /// Returns how far the simulation is into the next step, from `0.0` to `1.0`, to
+    /// interpolate rendered positions between steps.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step_size
+    }
+
+    /// Applies a force to a dynamic sprite during the steps of the next
+    /// [`Physics::update`] that steps at all. Forces on the same sprite add up.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sprite.
+    /// * `force` - The force; the sprite accelerates by `force / mass`.
+    pub fn apply_force(&mut self, name: &str, force: Vec2) {
+        *self.forces.entry(name.to_string()).or_insert(Vec2::ZERO) += force;
+    }
+
+    /// Changes the velocity of a dynamic sprite at once by `impulse / mass`, e.g. for
+    /// a jump or a hit, unlike a force, which acts over the steps of an update.
+    ///
+    /// Does nothing for static and kinematic sprites, or if the sprite does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world the sprite is in.
+    /// * `name` - The name of the sprite.
+    /// * `impulse` - The impulse; the velocity changes by `impulse / mass`.
+    pub fn apply_impulse(&self, world: &mut World, name: &str, impulse: Vec2) {
+        if let Some(sprite) = world.sprites.get_mut(name) {
+            let velocity = Vec2::from(sprite.velocity) + impulse * sprite.inverse_mass();
+            sprite.velocity = velocity.into();
+        }
+    }
+
+    /// Advances the world by `dt` milliseconds in as many fixed steps as fit, keeping
+    /// the remainder for the next update. A `dt` that is not positive and finite is
+    /// ignored, so one bad frame can neither rewind the simulation nor stop it for good.
+    ///
+    /// # Returns
+    ///
+    /// * The collision events of all steps, in the order they happened.
+    pub fn update(&mut self, world: &mut World, dt: f32) -> Vec<CollisionEvent> {
+        let mut events = Vec::new();
+        if !(dt > 0.0 && dt.is_finite()) {
+            return events;
+        }
+        self.accumulator += dt;
+        let mut steps = 0;
+        while self.accumulator >= self.step_size {
+            if steps == MAX_STEPS {
+                self.accumulator = 0.0;
+                break;
+            }
+            events.extend(self.step(world));
+            self.accumulator -= self.step_size;
+            steps += 1;
+        }
+        if steps > 0 {
+            self.forces.clear();
+        }
+        events
+    }
+
+    /// Advances the world by one step, ignoring the time kept by [`Physics::update`].
+    ///
+    /// # Returns
+    ///
+    /// * The collision events of the step.
+    pub fn step(&mut self, world: &mut World) -> Vec<CollisionEvent> {
+        let dt = self.step_size;
+        for (name, sprite) in &mut world.sprites {
+            if !sprite.body.is_dynamic() {
+                continue;
+            }
+            let force = self.forces.get(name).copied().unwrap_or(Vec2::ZERO);
+            let acceleration = self.gravity + force * sprite.inverse_mass();
+            let velocity = Vec2::from(sprite.velocity) + acceleration * dt;
+            // Like an invalid mass, a negative or non-finite damping is ignored rather
+            // than flipping or poisoning the velocity.
+            let damping = sprite.linear_damping;
+            let damping = if damping >= 0.0 && damping.is_finite() {
+                damping
+            } else {
+                0.0
+            };
+            sprite.velocity = (velocity * (1.0 / (1.0 + damping * dt))).into();
+        }
+
+        let mut events = movement::move_objects(world, dt);
+        events.extend(collision::collision(world, dt));
+        events
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self::new(DEFAULT_STEP)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::{BodyKind, Color, Pos, Size, Velocity};
+
+    fn world_with_box(velocity: Velocity) -> World {
+        let mut world = World::empty();
+        world.add_sprite(
+            "box",
+            Pos { x: 100.0, y: 100.0 },
+            velocity,
+            Size {
+                width: 10.0,
+                height: 10.0,
+            },
+            Color { r: 0, g: 0, b: 0 },
+        );
+        world
+    }
+
+    /// Verify that updates run whole steps and keep the remainder.
+    #[test]
+    fn test_fixed_steps() {
+        let mut world = world_with_box(Velocity { dx: 0.125, dy: 0.0 });
+        let mut physics = Physics::new(8.0);
+
+        physics.update(&mut world, 20.0);
+        assert_eq!(world.get_sprite("box").pos.x, 102.0);
+        assert_eq!(physics.alpha(), 0.5);
+
+        physics.update(&mut world, 4.0);
+        assert_eq!(world.get_sprite("box").pos.x, 103.0);
+        assert_eq!(physics.alpha(), 0.0);
+    }
+
+    /// Verify that a time step that is not positive and finite is ignored.
+    #[test]
+    fn test_invalid_dt() {
+        let mut world = world_with_box(Velocity { dx: 0.125, dy: 0.0 });
+        let mut physics = Physics::new(8.0);
+
+        physics.update(&mut world, 4.0);
+        for dt in [-4.0, 0.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            physics.update(&mut world, dt);
+            assert_eq!(physics.alpha(), 0.5, "dt {dt}");
+        }
+        physics.update(&mut world, 4.0);
+        assert_eq!(world.get_sprite("box").pos.x, 101.0);
+    }
+
+    /// Verify that gravity accelerates dynamic sprites only, and damping slows them.
+    #[test]
+    fn test_gravity_and_damping() {
+        let mut world = world_with_box(Velocity { dx: 0.0, dy: 0.0 });
+        let mut physics = Physics::new(8.0).with_gravity(Vec2::new(0.0, 1.0 / 1024.0));
+
+        physics.step(&mut world);
+        assert_eq!(world.get_sprite("box").velocity.dy, 1.0 / 128.0);
+        assert_eq!(world.get_sprite("box").pos.y, 100.0625);
+
+        let sprite = world.sprites.get_mut("box").unwrap();
+        sprite.velocity.dy = 0.0;
+        sprite.linear_damping = 0.125;
+        physics.step(&mut world);
+        assert_eq!(world.get_sprite("box").velocity.dy, 1.0 / 256.0);
+
+        let sprite = world.sprites.get_mut("box").unwrap();
+        sprite.velocity.dy = 0.0;
+        sprite.body = BodyKind::Kinematic;
+        physics.step(&mut world);
+        assert_eq!(world.get_sprite("box").velocity.dy, 0.0);
+    }
+
+    /// Verify that forces act for one update and impulses at once, both scaled by mass.
+    #[test]
+    fn test_forces_and_impulses() {
+        let mut world = world_with_box(Velocity { dx: 0.0, dy: 0.0 });
+        world.sprites.get_mut("box").unwrap().mass = 2.0;
+        let mut physics = Physics::new(8.0);
+
+        physics.apply_force("box", Vec2::new(1.0 / 1024.0, 0.0));
+        physics.apply_force("box", Vec2::new(1.0 / 1024.0, 0.0));
+        physics.update(&mut world, 4.0);
+        assert_eq!(world.get_sprite("box").velocity.dx, 0.0);
+        physics.update(&mut world, 4.0);
+        assert_eq!(world.get_sprite("box").velocity.dx, 1.0 / 128.0);
+        physics.update(&mut world, 8.0);
+        assert_eq!(world.get_sprite("box").velocity.dx, 1.0 / 128.0);
+
+        physics.apply_impulse(&mut world, "box", Vec2::new(0.0, -1.0));
+        assert_eq!(world.get_sprite("box").velocity.dy, -0.5);
+        world.sprites.get_mut("box").unwrap().body = BodyKind::Static;
+        physics.apply_impulse(&mut world, "box", Vec2::new(0.0, -1.0));
+        assert_eq!(world.get_sprite("box").velocity.dy, -0.5);
+    }
+
+    /// Verify that a mass that is not positive and finite leaves forces and impulses
+    /// without effect instead of producing infinite or NaN velocities.
+    #[test]
+    fn test_invalid_mass() {
+        for mass in [0.0, -1.0, f32::INFINITY, f32::NAN] {
+            let mut world = world_with_box(Velocity { dx: 0.0, dy: 0.0 });
+            world.sprites.get_mut("box").unwrap().mass = mass;
+            let mut physics = Physics::new(8.0);
+
+            physics.apply_force("box", Vec2::new(1.0, 0.0));
+            physics.update(&mut world, 8.0);
+            physics.apply_impulse(&mut world, "box", Vec2::new(0.0, -1.0));
+            let velocity = world.get_sprite("box").velocity;
+            assert_eq!((velocity.dx, velocity.dy), (0.0, 0.0), "mass {mass}");
+        }
+    }
+
+    /// Verify that a damping that is negative or not finite is ignored instead of
+    /// flipping the velocity or making it infinite or NaN.
+    #[test]
+    fn test_invalid_damping() {
+        for damping in [-1.0, f32::INFINITY, f32::NAN] {
+            let mut world = world_with_box(Velocity { dx: 0.125, dy: 0.0 });
+            world.sprites.get_mut("box").unwrap().linear_damping = damping;
+            let mut physics = Physics::new(8.0);
+
+            physics.step(&mut world);
+            let sprite = world.get_sprite("box");
+            assert_eq!(
+                (sprite.velocity.dx, sprite.pos.x),
+                (0.125, 101.0),
+                "damping {damping}"
+            );
+        }
+    }
+
+    /// Verify that a falling sprite without restitution comes to rest on the bottom
+    /// border, and friction stops it sliding.
+    #[test]
+    fn test_comes_to_rest() {
+        let mut world = world_with_box(Velocity { dx: 0.2, dy: 0.0 });
+        let sprite = world.sprites.get_mut("box").unwrap();
+        sprite.restitution = 0.0;
+        sprite.friction = 0.5;
+        let mut physics = Physics::default().with_gravity(Vec2::new(0.0, 0.002));
+
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            events.extend(physics.update(&mut world, 16.0));
+        }
+        assert!(
+            events
+                .iter()
+                .any(|event| event.border() == Some(collision::Border::Bottom))
+        );
+        let sprite = world.get_sprite("box");
+        assert_eq!(sprite.pos.y, world.window.height - sprite.size.height);
+        assert_eq!(sprite.velocity.dx, 0.0);
+    }
+
+    /// Verify that a step size of zero is rejected.
+    #[test]
+    #[should_panic(expected = "step size must be positive")]
+    fn test_zero_step_panics() {
+        Physics::new(0.0);
+    }
+}
@@ -0,0 +1,494 @@
+//! Input recording and replay.
+//!
+//! A [`Recorder`] stores the [`InputState`] and the time delta of every frame. Fed
+//! back in the same order, they make a deterministic game run exactly the same
+//! again, which turns a bug report into a file that reproduces it.
+//!
+//! Games read their frame input through a [`FrameSource`] instead of the engine:
+//!
+//! ```ignore
+//! let frame = frames.next_frame(engine, measured_dt);
+//! input.update(&frame.input);
+//! update(world, frame.dt);
+//! ```
+//!
+//! # File format
+//!
+//! Replay files are binary and little endian: the magic `GEREPLAY`, a `u16` format
+//! version, the `u64` seed of the game's [`Rng`](crate::rng::Rng) and the `u32` number
+//! of frames. Each frame is a flags byte, the `f32` time delta, and only the parts of
+//! the input that changed since the previous frame: buttons (`u16` count, then device
+//! byte and `u16` code each), cursor (two `f64`) and gamepad axes (six `f32`). A frame
+//! without input changes takes five bytes.
+
+use crate::input::{Button, GAMEPAD_AXES, InputSource, InputState};
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"GEREPLAY";
+const VERSION: u16 = 1;
+
+const BUTTONS_CHANGED: u8 = 1;
+const CURSOR_CHANGED: u8 = 2;
+const AXES_CHANGED: u8 = 4;
+
+/// Errors that can occur while reading or writing a replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The file could not be read or written.
+    Io(String),
+    /// The data is not a replay of a supported version.
+    Format(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(msg) => write!(f, "could not access replay: {msg}"),
+            ReplayError::Format(msg) => write!(f, "invalid replay: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Input and time delta of one frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frame {
+    pub dt: f32,
+    pub input: InputState,
+}
+
+/// Records frames in the replay file format.
+pub struct Recorder {
+    seed: u64,
+    data: Vec<u8>,
+    frames: u32,
+    previous: InputState,
+}
+
+impl Recorder {
+    /// Creates a recorder without frames for a game whose random numbers come from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            data: Vec::new(),
+            frames: 0,
+            previous: InputState::default(),
+        }
+    }
+
+    /// Appends a frame.
+    ///
+    /// Buttons with a code the file format cannot hold, such as GLFW's unknown key
+    /// `-1`, are left out rather than replayed as another button, and so is any
+    /// button after the first 65535.
+    pub fn record(&mut self, dt: f32, input: &InputState) {
+        let mut flags = 0;
+        if input.buttons != self.previous.buttons {
+            flags |= BUTTONS_CHANGED;
+        }
+        if input.cursor != self.previous.cursor {
+            flags |= CURSOR_CHANGED;
+        }
+        if input.gamepad_axes != self.previous.gamepad_axes {
+            flags |= AXES_CHANGED;
+        }
+
+        self.data.push(flags);
+        self.data.extend(dt.to_le_bytes());
+        if flags & BUTTONS_CHANGED != 0 {
+            let buttons: Vec<(u8, u16)> = input
+                .buttons
+                .iter()
+                .filter_map(|button| {
+                    let (device, code) = match *button {
+                        Button::Key(code) => (0, code),
+                        Button::Mouse(code) => (1, code),
+                        Button::Gamepad(code) => (2, code),
+                    };
+                    u16::try_from(code).ok().map(|code| (device, code))
+                })
+                .take(usize::from(u16::MAX))
+                .collect();
+            self.data.extend((buttons.len() as u16).to_le_bytes());
+            for (device, code) in buttons {
+                self.data.push(device);
+                self.data.extend(code.to_le_bytes());
+            }
+        }
+        if flags & CURSOR_CHANGED != 0 {
+            self.data.extend(input.cursor.0.to_le_bytes());
+            self.data.extend(input.cursor.1.to_le_bytes());
+        }
+        if flags & AXES_CHANGED != 0 {
+            for axis in input.gamepad_axes {
+                self.data.extend(axis.to_le_bytes());
+            }
+        }
+
+        self.frames += 1;
+        self.previous.clone_from(input);
+    }
+
+    /// Returns the number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    /// Returns the replay file contents.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 14 + self.data.len());
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(self.seed.to_le_bytes());
+        bytes.extend(self.frames.to_le_bytes());
+        bytes.extend(&self.data);
+        bytes
+    }
+
+    /// Writes the replay file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes())
+            .map_err(|err| ReplayError::Io(format!("{}: {err}", path.display())))
+    }
+}
+
+/// Reads little endian values from a replay.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let Some((head, rest)) = self.bytes.split_first_chunk::<N>() else {
+            return Err(ReplayError::Format("unexpected end of data".to_string()));
+        };
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, ReplayError> {
+        self.take().map(f32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Result<f64, ReplayError> {
+        self.take().map(f64::from_le_bytes)
+    }
+}
+
+/// Recorded frames, played back in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    seed: u64,
+    frames: VecDeque<Frame>,
+}
+
+impl Replay {
+    /// Parses replay file contents.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(ReplayError::Format)` for data without the magic, of another version,
+    ///   cut off, or with trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes };
+        if &reader.take::<8>()? != MAGIC {
+            return Err(ReplayError::Format("not a replay file".to_string()));
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(ReplayError::Format(format!(
+                "unsupported version {version}"
+            )));
+        }
+        let seed = reader.u64()?;
+
+        let count = reader.u32()?;
+        let mut frames = VecDeque::new();
+        let mut input = InputState::default();
+        for _ in 0..count {
+            let flags = reader.u8()?;
+            let dt = reader.f32()?;
+            if flags & BUTTONS_CHANGED != 0 {
+                let len = reader.u16()?;
+                input.buttons = (0..len)
+                    .map(|_| {
+                        let device = reader.u8()?;
+                        let code = i32::from(reader.u16()?);
+                        match device {
+                            0 => Ok(Button::Key(code)),
+                            1 => Ok(Button::Mouse(code)),
+                            2 => Ok(Button::Gamepad(code)),
+                            _ => Err(ReplayError::Format(format!("unknown device {device}"))),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                // Lookups in `InputState` search the buttons, so they must be sorted
+                // even if the file is not.
+                input.buttons.sort_unstable();
+                input.buttons.dedup();
+            }
+            if flags & CURSOR_CHANGED != 0 {
+                input.cursor = (reader.f64()?, reader.f64()?);
+            }
+            if flags & AXES_CHANGED != 0 {
+                let mut axes = [0.0; GAMEPAD_AXES];
+                for axis in &mut axes {
+                    *axis = reader.f32()?;
+                }
+                input.gamepad_axes = axes;
+            }
+            frames.push_back(Frame {
+                dt,
+                input: input.clone(),
+            });
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(ReplayError::Format("trailing data".to_string()));
+        }
+        Ok(Self { seed, frames })
+    }
+
+    /// Reads a replay file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|err| ReplayError::Io(format!("{}: {err}", path.display())))?;
+        Self::from_bytes(&bytes)
+            .map_err(|err| ReplayError::Format(format!("{}: {err}", path.display())))
+    }
+
+    /// Returns the seed the recorded game used.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of frames left.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        self.frames.pop_front()
+    }
+}
+
+/// Where the input and time delta of each frame come from.
+pub enum FrameSource {
+    /// Live input and the measured time delta.
+    Live,
+    /// Live input and the measured time delta, recorded.
+    Recording(Recorder),
+    /// Recorded frames. After the last one the source continues live.
+    Replaying(Replay),
+}
+
+impl FrameSource {
+    /// Returns the input and time delta of the next frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The live input, usually the [`Engine`](crate::Engine). A replay does
+    ///   not read it.
+    /// * `dt` - The measured time delta of the frame.
+    pub fn next_frame(&mut self, source: &impl InputSource, dt: f32) -> Frame {
+        if let FrameSource::Replaying(replay) = self {
+            match replay.next() {
+                Some(frame) => return frame,
+                None => *self = FrameSource::Live,
+            }
+        }
+        let frame = Frame {
+            dt,
+            input: InputState::read(source),
+        };
+        if let FrameSource::Recording(recorder) = self {
+            recorder.record(frame.dt, &frame.input);
+        }
+        frame
+    }
+
+    /// Returns `true` while recorded frames are played back.
+    pub fn is_replaying(&self) -> bool {
+        matches!(self, FrameSource::Replaying(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A live source whose keys and cursor the test sets.
+    #[derive(Default)]
+    struct FakeSource {
+        keys: Vec<i32>,
+        cursor: (f64, f64),
+        stick: f32,
+    }
+
+    impl InputSource for FakeSource {
+        fn key(&self, key: i32) -> bool {
+            self.keys.contains(&key)
+        }
+
+        fn mouse_button(&self, _button: i32) -> bool {
+            false
+        }
+
+        fn cursor(&self) -> (f64, f64) {
+            self.cursor
+        }
+
+        fn gamepad_button(&self, button: i32) -> bool {
+            button == 7 && self.stick != 0.0
+        }
+
+        fn gamepad_axis(&self, axis: i32) -> f32 {
+            if axis == 1 { self.stick } else { 0.0 }
+        }
+    }
+
+    /// Records `frames` frames with changing input and returns the recorder and the
+    /// frames it saw.
+    fn record(frames: usize) -> (Recorder, Vec<Frame>) {
+        let mut source = FrameSource::Recording(Recorder::new(99));
+        let mut live = FakeSource::default();
+        let mut seen = Vec::new();
+        for i in 0..frames {
+            live.keys = if i % 3 == 0 { vec![265] } else { vec![32, 265] };
+            live.cursor = ((i / 10) as f64, 0.5);
+            live.stick = if i % 7 == 0 { 0.25 } else { 0.0 };
+            seen.push(source.next_frame(&live, 16.0 + i as f32 / 10.0));
+        }
+        match source {
+            FrameSource::Recording(recorder) => (recorder, seen),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Verify that a replay returns exactly the recorded frames and then goes live.
+    #[test]
+    fn test_round_trip() {
+        let (recorder, seen) = record(50);
+        assert_eq!(recorder.len(), 50);
+
+        let replay = Replay::from_bytes(&recorder.to_bytes()).unwrap();
+        assert_eq!(replay.seed(), 99);
+        let mut source = FrameSource::Replaying(replay);
+        let ignored = FakeSource {
+            keys: vec![80],
+            ..Default::default()
+        };
+        for frame in &seen {
+            assert_eq!(&source.next_frame(&ignored, 1.0), frame);
+        }
+
+        assert!(source.is_replaying());
+        let live = source.next_frame(&ignored, 1.0);
+        assert!(!source.is_replaying());
+        assert_eq!(live.input.buttons, [Button::Key(80)]);
+    }
+
+    /// Verify that buttons with codes that do not fit the format are left out.
+    #[test]
+    fn test_unrecordable_buttons() {
+        let mut recorder = Recorder::new(0);
+        let input = InputState {
+            buttons: vec![Button::Key(-1), Button::Key(32), Button::Mouse(70000)],
+            ..Default::default()
+        };
+        recorder.record(16.0, &input);
+
+        let frame = Replay::from_bytes(&recorder.to_bytes())
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(frame.input.buttons, [Button::Key(32)]);
+    }
+
+    /// Verify that frames without input changes take five bytes.
+    #[test]
+    fn test_compact() {
+        let mut recorder = Recorder::new(0);
+        let input = InputState::default();
+        for _ in 0..1000 {
+            recorder.record(16.0, &input);
+        }
+        assert_eq!(recorder.to_bytes().len(), 22 + 1000 * 5);
+    }
+
+    /// Verify that buttons stored out of order or twice are still found.
+    #[test]
+    fn test_unsorted_buttons() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(0u64.to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        bytes.push(BUTTONS_CHANGED);
+        bytes.extend(16f32.to_le_bytes());
+        bytes.extend(3u16.to_le_bytes());
+        for (device, code) in [(2u8, 7u16), (0, 265), (2, 7)] {
+            bytes.push(device);
+            bytes.extend(code.to_le_bytes());
+        }
+
+        let frame = Replay::from_bytes(&bytes).unwrap().next().unwrap();
+        assert_eq!(frame.input.buttons, [Button::Key(265), Button::Gamepad(7)]);
+        assert!(frame.input.key(265));
+        assert!(frame.input.gamepad_button(7));
+    }
+
+    /// Verify that broken data is rejected.
+    #[test]
+    fn test_invalid_data() {
+        let bytes = record(5).0.to_bytes();
+        assert!(matches!(
+            Replay::from_bytes(b"NOREPLAY\x02\x00\x00\x00\x00\x00"),
+            Err(ReplayError::Format(_))
+        ));
+        assert!(matches!(
+            Replay::from_bytes(&[&bytes[..8], &[2, 0], &bytes[10..]].concat()),
+            Err(ReplayError::Format(message)) if message == "unsupported version 2"
+        ));
+        assert!(matches!(
+            Replay::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ReplayError::Format(_))
+        ));
+        assert!(matches!(
+            Replay::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            Err(ReplayError::Format(_))
+        ));
+    }
+}
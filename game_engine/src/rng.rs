@@ -0,0 +1,99 @@
+//! Seeded random numbers.
+//!
+//! [`Rng`] is a small SplitMix64 generator: the same seed always gives the same
+//! numbers on every platform, so a game run can be repeated exactly. Its whole
+//! state is one `u64`, which save files can store.
+
+use std::ops::Range;
+
+/// Deterministic random number generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the current state; `Rng::new(rng.state())` continues where `rng` is.
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a number in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a number in `range`.
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + self.next_f32() * (range.end - range.start)
+    }
+
+    /// Returns a number in `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn below(&mut self, n: u32) -> u32 {
+        assert!(n > 0, "below(0) has no result");
+        // Multiply-shift maps the full range evenly enough for games and keeps one draw.
+        ((u64::from(self.next_u32()) * u64::from(n)) >> 32) as u32
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a seed always produces the same sequence, and the state resumes it.
+    #[test]
+    fn test_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Rng::new(43).next_u64(), first[0]);
+
+        let mut resumed = Rng::new(a.state());
+        assert_eq!(resumed.next_u64(), a.next_u64());
+
+        // SplitMix64 reference value, so the sequence never changes between releases.
+        assert_eq!(Rng::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    /// Verify that the helpers stay in their ranges.
+    #[test]
+    fn test_ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let f = rng.next_f32();
+            assert!((0.0..1.0).contains(&f));
+            assert!((-2.0..3.0).contains(&rng.range_f32(-2.0..3.0)));
+            assert!(rng.below(6) < 6);
+        }
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
@@ -0,0 +1,561 @@
+//! Versioned save files.
+//!
+//! A save file is JSON with the schema version of the game data next to the data:
+//!
+//! ```json
+//! { "version": 2, "data": { "world": { ... }, "score": [3, 1] } }
+//! ```
+//!
+//! Games describe how to upgrade old files with [`Migrations`]: one step per version
+//! that edits the JSON in place, so saves from older releases stay loadable.
+//!
+//! The engine-side sprites are raw pointers and cannot be saved. [`SpriteState`] and
+//! [`WorldState`] hold the plain sprite data; restoring them creates new engine sprites.
+
+use crate::collider::Collider;
+use crate::sprite::{
+    ALL_LAYERS, BodyKind, Color, DEFAULT_LAYER, Pos, Size, Sprite, SpriteFields, Velocity,
+};
+use crate::world::World;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors that can occur while saving or loading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveError {
+    /// The file could not be read or written.
+    Io(String),
+    /// The file is not a save file or its data does not match the game structs.
+    Format(String),
+    /// The file was written by a newer version of the game.
+    TooNew { found: u32, supported: u32 },
+    /// No migration step upgrades the file from this version.
+    MissingMigration(u32),
+    /// A migration step failed.
+    Migration { from: u32, message: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(msg) => write!(f, "could not access save file: {msg}"),
+            SaveError::Format(msg) => write!(f, "invalid save file: {msg}"),
+            SaveError::TooNew { found, supported } => write!(
+                f,
+                "save file version {found} is newer than the supported version {supported}"
+            ),
+            SaveError::MissingMigration(version) => {
+                write!(f, "no migration from save file version {version}")
+            }
+            SaveError::Migration { from, message } => {
+                write!(
+                    f,
+                    "migration from save file version {from} failed: {message}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// A migration step: edits the data of one version into the next version.
+type Step = Box<dyn Fn(&mut Value) -> Result<(), String>>;
+
+/// The current schema version of a game's save data and the steps to reach it.
+pub struct Migrations {
+    version: u32,
+    steps: BTreeMap<u32, Step>,
+}
+
+impl Migrations {
+    /// Creates migrations for data at schema `version`, without upgrade steps yet.
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the current schema version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Adds the step that upgrades data of version `from` to version `from + 1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::save::Migrations;
+    ///
+    /// // Version 2 renamed `points` to `score`.
+    /// let migrations = Migrations::new(2).step(1, |data| {
+    ///     let points = data["points"].take();
+    ///     data["score"] = points;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn step(
+        mut self,
+        from: u32,
+        step: impl Fn(&mut Value) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.steps.insert(from, Box::new(step));
+        self
+    }
+
+    /// Upgrades `data` from version `from` to the current version.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(SaveError::TooNew)` if `from` is newer than the current version.
+    /// * `Err(SaveError::MissingMigration)` if a step is missing.
+    /// * `Err(SaveError::Migration)` if a step fails.
+    pub fn migrate(&self, from: u32, data: &mut Value) -> Result<(), SaveError> {
+        if from > self.version {
+            return Err(SaveError::TooNew {
+                found: from,
+                supported: self.version,
+            });
+        }
+        for version in from..self.version {
+            let step = self
+                .steps
+                .get(&version)
+                .ok_or(SaveError::MissingMigration(version))?;
+            step(data).map_err(|message| SaveError::Migration {
+                from: version,
+                message,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// On-disk layout of a save file.
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    version: u32,
+    data: T,
+}
+
+/// Serializes `data` as a save file of the current version of `migrations`.
+pub fn to_json<T: Serialize>(data: &T, migrations: &Migrations) -> Result<String, SaveError> {
+    let envelope = Envelope {
+        version: migrations.version(),
+        data,
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|err| SaveError::Format(err.to_string()))
+}
+
+/// Parses a save file, migrating old versions first.
+///
+/// # Returns
+///
+/// * `Err(SaveError::Format)` if the JSON is no save file or the data does not fit `T`.
+/// * The errors of [`Migrations::migrate`].
+pub fn from_json<T: DeserializeOwned>(json: &str, migrations: &Migrations) -> Result<T, SaveError> {
+    let Envelope { version, mut data } = serde_json::from_str::<Envelope<Value>>(json)
+        .map_err(|err| SaveError::Format(err.to_string()))?;
+    migrations.migrate(version, &mut data)?;
+    serde_json::from_value(data).map_err(|err| SaveError::Format(err.to_string()))
+}
+
+/// Writes a save file.
+///
+/// The data is written to a temporary file next to `path` first and then renamed,
+/// so a crash while saving does not destroy the previous save.
+pub fn save<T: Serialize>(
+    path: impl AsRef<Path>,
+    data: &T,
+    migrations: &Migrations,
+) -> Result<(), SaveError> {
+    let path = path.as_ref();
+    let io_error = |err: std::io::Error| SaveError::Io(format!("{}: {err}", path.display()));
+    let json = to_json(data, migrations)?;
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).map_err(io_error)?;
+    std::fs::rename(&tmp, path).map_err(io_error)
+}
+
+/// Reads a save file, migrating old versions first.
+///
+/// # Returns
+///
+/// * `Err(SaveError::Io)` if the file cannot be read.
+/// * The errors of [`from_json`].
+pub fn load<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    migrations: &Migrations,
+) -> Result<T, SaveError> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .map_err(|err| SaveError::Io(format!("{}: {err}", path.display())))?;
+    from_json(&json, migrations)
+}
+
+/// Directory for the files a game writes for its player, such as saves.
+///
+/// This is `<data dir>/<game>`, with the data dir taken from `XDG_DATA_HOME`,
+/// `APPDATA` on Windows or `~/.local/share`. Without any of these variables the
+/// files go to the working directory.
+pub fn data_dir(game: &str) -> PathBuf {
+    data_dir_from(game, |name| std::env::var_os(name))
+}
+
+/// [`data_dir`] with the environment variables read from `var`.
+fn data_dir_from(game: &str, var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    let var = |name: &str| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = var("XDG_DATA_HOME")
+        .or_else(|| if cfg!(windows) { var("APPDATA") } else { None })
+        .or_else(|| var("HOME").map(|home| home.join(".local/share")));
+    match base {
+        Some(base) => base.join(game),
+        None => PathBuf::new(),
+    }
+}
+
+/// Plain data of a [`Sprite`], without the engine-side sprite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteState {
+    pub pos: Pos,
+    pub size: Size,
+    pub velocity: Velocity,
+    pub color: Color,
+    /// Missing in saves from before body kinds, which load as dynamic.
+    #[serde(default)]
+    pub body: BodyKind,
+    #[serde(default = "elastic")]
+    pub restitution: f32,
+    /// Missing in saves from before masses, which load with a mass of `1.0` and
+    /// neither friction nor damping.
+    #[serde(default = "unit_mass")]
+    pub mass: f32,
+    #[serde(default)]
+    pub friction: f32,
+    #[serde(default)]
+    pub linear_damping: f32,
+    /// Missing in saves from before collision layers, which load on the default
+    /// layer, colliding with all layers.
+    #[serde(default = "default_layer")]
+    pub layer: u32,
+    #[serde(default = "all_layers")]
+    pub mask: u32,
+    #[serde(default)]
+    pub trigger: bool,
+    /// Missing in saves from before colliders, which load with the sprite rectangle.
+    #[serde(default)]
+    pub collider: Collider,
+}
+
+fn elastic() -> f32 {
+    1.0
+}
+
+fn unit_mass() -> f32 {
+    1.0
+}
+
+fn default_layer() -> u32 {
+    DEFAULT_LAYER
+}
+
+fn all_layers() -> u32 {
+    ALL_LAYERS
+}
+
+impl From<&Sprite> for SpriteState {
+    fn from(sprite: &Sprite) -> Self {
+        Self {
+            pos: sprite.pos,
+            size: sprite.size,
+            velocity: sprite.velocity,
+            color: sprite.color,
+            body: sprite.body,
+            restitution: sprite.restitution,
+            mass: sprite.mass,
+            friction: sprite.friction,
+            linear_damping: sprite.linear_damping,
+            layer: sprite.layer,
+            mask: sprite.mask,
+            trigger: sprite.trigger,
+            collider: sprite.collider.clone(),
+        }
+    }
+}
+
+impl From<SpriteState> for Sprite {
+    /// Creates a new engine-side sprite with the saved data.
+    fn from(state: SpriteState) -> Self {
+        let mut sprite = Sprite::new(state.pos, state.velocity, state.color, state.size);
+        sprite.body = state.body;
+        sprite.restitution = state.restitution;
+        sprite.mass = state.mass;
+        sprite.friction = state.friction;
+        sprite.linear_damping = state.linear_damping;
+        sprite.layer = state.layer;
+        sprite.mask = state.mask;
+        sprite.trigger = state.trigger;
+        sprite.collider = state.collider;
+        sprite
+    }
+}
+
+/// Plain data of a [`World`]: the window size and the named sprites.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldState {
+    pub window: Size,
+    /// Sorted by name, so the same world always saves to the same file.
+    pub sprites: BTreeMap<String, SpriteState>,
+}
+
+impl From<&World> for WorldState {
+    fn from(world: &World) -> Self {
+        Self {
+            window: world.window,
+            sprites: world
+                .sprites
+                .iter()
+                .map(|(name, sprite)| (name.clone(), SpriteState::from(sprite)))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<WorldState> for World {
+    type Error = SaveError;
+
+    /// Rebuilds the world with new engine-side sprites.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(SaveError::Format)` naming the first sprite that a level could not
+    ///   hold either, e.g. one with a size or mass that is not positive, or an
+    ///   invalid collider.
+    fn try_from(state: WorldState) -> Result<Self, SaveError> {
+        if let Some(problem) = state
+            .sprites
+            .iter()
+            .find_map(|(name, sprite)| sprite_problem(name, sprite))
+        {
+            return Err(SaveError::Format(problem));
+        }
+        let mut world = World::empty();
+        world.window = state.window;
+        world.sprites = state
+            .sprites
+            .into_iter()
+            .map(|(name, sprite)| (name, Sprite::from(sprite)))
+            .collect();
+        Ok(world)
+    }
+}
+
+/// Returns what makes a saved sprite unusable, or `None` if it is valid.
+fn sprite_problem(name: &str, sprite: &SpriteState) -> Option<String> {
+    let fields = SpriteFields {
+        size: sprite.size,
+        color: sprite.color,
+        mass: sprite.mass,
+        restitution: sprite.restitution,
+        friction: sprite.friction,
+        linear_damping: sprite.linear_damping,
+        collider: &sprite.collider,
+    };
+    fields
+        .problem()
+        .map(|problem| format!("sprite `{name}` {problem}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec2;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct GameSave {
+        world: WorldState,
+        score: [u32; 2],
+    }
+
+    fn world() -> World {
+        let mut world = World::empty();
+        world.add_sprite(
+            "ball",
+            Pos { x: 1.0, y: 2.0 },
+            Velocity { dx: 0.5, dy: -0.5 },
+            Size {
+                width: 3.0,
+                height: 4.0,
+            },
+            Color {
+                r: 10,
+                g: 20,
+                b: 30,
+            },
+        );
+        world.sprites.get_mut("ball").unwrap().collider = Collider::Polygon {
+            points: vec![
+                Vec2::new(0.0, -2.0),
+                Vec2::new(1.5, 2.0),
+                Vec2::new(-1.5, 2.0),
+            ],
+        };
+        world
+    }
+
+    /// Verify that a world survives a save file round trip with new sprites.
+    #[test]
+    fn test_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("game_engine_save_{}/game.json", std::process::id()));
+        let migrations = Migrations::new(1);
+        let saved = GameSave {
+            world: WorldState::from(&world()),
+            score: [3, 1],
+        };
+
+        save(&path, &saved, &migrations).unwrap();
+        let loaded: GameSave = load(&path, &migrations).unwrap();
+        assert_eq!(loaded, saved);
+
+        let restored = World::try_from(loaded.world).unwrap();
+        let ball = restored.get_sprite("ball");
+        assert_eq!(ball.velocity, Velocity { dx: 0.5, dy: -0.5 });
+        assert_eq!(
+            ball.color,
+            Color {
+                r: 10,
+                g: 20,
+                b: 30
+            }
+        );
+        assert_eq!(ball.collider, world().sprites["ball"].collider);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    /// Verify that a world with an invalid sprite is not restored.
+    #[test]
+    fn test_invalid_world() {
+        let mut state = WorldState::from(&world());
+        state.sprites.get_mut("ball").unwrap().mass = 0.0;
+        assert_eq!(
+            World::try_from(state).err(),
+            Some(SaveError::Format(
+                "sprite `ball` needs a positive mass".to_string()
+            ))
+        );
+
+        let mut state = WorldState::from(&world());
+        state.sprites.get_mut("ball").unwrap().friction = f32::NAN;
+        assert_eq!(
+            World::try_from(state).err(),
+            Some(SaveError::Format(
+                "sprite `ball` needs a non-negative friction".to_string()
+            ))
+        );
+
+        let mut state = WorldState::from(&world());
+        state.sprites.get_mut("ball").unwrap().size.width = 0.0;
+        assert_eq!(
+            World::try_from(state).err(),
+            Some(SaveError::Format(
+                "sprite `ball` needs a positive size".to_string()
+            ))
+        );
+
+        let mut state = WorldState::from(&world());
+        state.sprites.get_mut("ball").unwrap().color.g = 300;
+        assert_eq!(
+            World::try_from(state).err(),
+            Some(SaveError::Format(
+                "sprite `ball` has a color component outside 0 to 255".to_string()
+            ))
+        );
+
+        let mut state = WorldState::from(&world());
+        state.sprites.get_mut("ball").unwrap().collider = Collider::Polygon { points: vec![] };
+        assert!(matches!(
+            World::try_from(state),
+            Err(SaveError::Format(message)) if message.contains("convex polygon")
+        ));
+    }
+
+    /// Verify that old versions are migrated step by step.
+    #[test]
+    fn test_migrations() {
+        // Version 1 had a single `points` number, version 2 a score pair, version 3 the world.
+        let old = r#"{ "version": 1, "data": { "points": 4 } }"#;
+        let world = serde_json::to_value(WorldState::from(&World::empty())).unwrap();
+        let migrations = Migrations::new(3)
+            .step(1, |data| {
+                let points = data["points"].take();
+                *data = serde_json::json!({ "score": [points, 0] });
+                Ok(())
+            })
+            .step(2, move |data| {
+                data["world"] = world.clone();
+                Ok(())
+            });
+
+        let loaded: GameSave = from_json(old, &migrations).unwrap();
+        assert_eq!(loaded.score, [4, 0]);
+        assert!(loaded.world.sprites.is_empty());
+    }
+
+    /// Verify that newer, unmigratable and broken files are rejected.
+    #[test]
+    fn test_errors() {
+        let migrations = Migrations::new(2);
+        let result = from_json::<GameSave>(r#"{ "version": 5, "data": {} }"#, &migrations);
+        assert_eq!(
+            result,
+            Err(SaveError::TooNew {
+                found: 5,
+                supported: 2
+            })
+        );
+
+        let result = from_json::<GameSave>(r#"{ "version": 1, "data": {} }"#, &migrations);
+        assert_eq!(result, Err(SaveError::MissingMigration(1)));
+
+        let failing = Migrations::new(2).step(1, |_| Err("no points".to_string()));
+        let result = from_json::<GameSave>(r#"{ "version": 1, "data": {} }"#, &failing);
+        assert!(matches!(result, Err(SaveError::Migration { from: 1, .. })));
+
+        let result = from_json::<GameSave>(r#"{ "version": 2, "data": {} }"#, &migrations);
+        assert!(matches!(result, Err(SaveError::Format(_))));
+    }
+
+    /// Verify that the data dir follows the XDG variables and falls back to the
+    /// working directory.
+    #[test]
+    fn test_data_dir() {
+        fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+            move |name| {
+                let value = vars.iter().find(|(var, _)| *var == name)?.1;
+                Some(OsString::from(value))
+            }
+        }
+        let home = ("HOME", "/home/ann");
+        let dir = data_dir_from("pong", env(&[("XDG_DATA_HOME", "/data"), home]));
+        assert_eq!(dir, Path::new("/data/pong"));
+        let dir = data_dir_from("pong", env(&[("XDG_DATA_HOME", ""), home]));
+        assert_eq!(dir, Path::new("/home/ann/.local/share/pong"));
+        assert_eq!(data_dir_from("pong", env(&[])), Path::new(""));
+    }
+}
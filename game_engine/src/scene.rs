@@ -0,0 +1,425 @@
+//! Scene stack for title screens, gameplay, pause overlays and game-over screens.
+//!
+//! A game is split into [`Scene`]s kept on a [`SceneStack`]. Only the top scene
+//! handles input and updates; scenes below it are frozen. An overlay scene, like
+//! a pause screen, lets the scenes below keep rendering.
+//!
+//! Scenes are generic over two types:
+//!
+//! * `S` - state shared by all scenes, e.g. the audio mixer or the high score.
+//! * `E` - what rendering goes through, usually the [`crate::Engine`].
+//!
+//! Input reaches scenes through the shared state, e.g. an [`crate::input::Input`]
+//! updated once per frame, so the stack can run without a window in tests and
+//! replays.
+//!
+//! Scenes change the stack by returning a [`Transition`], optionally with a fade:
+//! the screen fades to black, the stack changes, and the screen fades back in.
+//! The C library draws no transparency, so the stack only reports the fade level
+//! and the game decides how to draw it.
+
+use crate::hash::StateHasher;
+use std::hash::Hasher;
+use std::time::Duration;
+
+/// One screen or state of a game.
+///
+/// Every method has a default that does nothing, so scenes implement only what they need.
+pub trait Scene<S, E> {
+    /// Called when the scene is pushed onto the stack.
+    fn on_enter(&mut self, _state: &mut S) {}
+
+    /// Called when the scene is popped or replaced.
+    fn on_exit(&mut self, _state: &mut S) {}
+
+    /// Reads input while the scene is on top of the stack.
+    fn handle_input(&mut self, _state: &mut S) -> Transition<S, E> {
+        Transition::None
+    }
+
+    /// Advances the scene while it is on top of the stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The shared game state.
+    /// * `dt` - The time delta since the last update (in milliseconds).
+    fn update(&mut self, _state: &mut S, _dt: f32) -> Transition<S, E> {
+        Transition::None
+    }
+
+    /// Draws the scene. Also called for frozen scenes below an overlay.
+    fn render(&self, _state: &S, _output: &E) {}
+
+    /// Returns `true` if the scenes below stay visible under this one.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Feeds the simulation state of the scene into `hasher`, for determinism tests.
+    /// Scenes without such state, like menus, add nothing.
+    fn hash_state(&self, _hasher: &mut StateHasher) {}
+}
+
+/// A change of the scene stack, returned by [`Scene::handle_input`] and [`Scene::update`].
+pub enum Transition<S, E> {
+    /// Keep the stack as it is.
+    None,
+    /// Freeze the current scene and put a new one on top of it.
+    Push(Box<dyn Scene<S, E>>),
+    /// Remove the top scene and continue with the one below.
+    Pop,
+    /// Remove the top scene and put a new one in its place.
+    Replace(Box<dyn Scene<S, E>>),
+    /// Fade out over the first half of the duration, change the stack, and fade in.
+    Fade(Duration, Box<Transition<S, E>>),
+}
+
+impl<S, E> Transition<S, E> {
+    /// Returns a transition that pushes `scene`.
+    pub fn push(scene: impl Scene<S, E> + 'static) -> Self {
+        Transition::Push(Box::new(scene))
+    }
+
+    /// Returns a transition that replaces the top scene with `scene`.
+    pub fn replace(scene: impl Scene<S, E> + 'static) -> Self {
+        Transition::Replace(Box::new(scene))
+    }
+
+    /// Wraps the transition in a fade of the given total duration.
+    pub fn with_fade(self, duration: Duration) -> Self {
+        Transition::Fade(duration, Box::new(self))
+    }
+}
+
+/// A running fade with the stack change it waits for.
+struct Fade<S, E> {
+    /// Total fade duration in milliseconds.
+    duration: f32,
+    elapsed: f32,
+    /// Applied once the screen is fully black.
+    pending: Option<Transition<S, E>>,
+}
+
+/// Stack of scenes; the last scene is on top.
+pub struct SceneStack<S, E> {
+    scenes: Vec<Box<dyn Scene<S, E>>>,
+    fade: Option<Fade<S, E>>,
+}
+
+impl<S, E> Default for SceneStack<S, E> {
+    fn default() -> Self {
+        Self {
+            scenes: Vec::new(),
+            fade: None,
+        }
+    }
+}
+
+impl<S, E> SceneStack<S, E> {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of scenes on the stack.
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// Returns `true` if the last scene was popped, which usually ends the game.
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Returns `true` while a fade is running; input and updates are paused meanwhile.
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Returns how far the screen is faded to black, from 0.0 (not at all) to 1.0.
+    pub fn fade_level(&self) -> f32 {
+        match &self.fade {
+            Some(fade) if fade.duration > 0.0 => {
+                let half = fade.duration / 2.0;
+                if fade.elapsed < half {
+                    fade.elapsed / half
+                } else {
+                    ((fade.duration - fade.elapsed) / half).max(0.0)
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Pushes a scene right away and calls its [`Scene::on_enter`].
+    pub fn push(&mut self, scene: Box<dyn Scene<S, E>>, state: &mut S) {
+        self.apply(Transition::Push(scene), state);
+    }
+
+    /// Pops the top scene right away and calls its [`Scene::on_exit`].
+    pub fn pop(&mut self, state: &mut S) {
+        self.apply(Transition::Pop, state);
+    }
+
+    /// Replaces the top scene right away.
+    pub fn replace(&mut self, scene: Box<dyn Scene<S, E>>, state: &mut S) {
+        self.apply(Transition::Replace(scene), state);
+    }
+
+    /// Applies a transition. A fade starts here and changes the stack in [`SceneStack::update`].
+    ///
+    /// A transition requested during a running fade is dropped.
+    pub fn apply(&mut self, transition: Transition<S, E>, state: &mut S) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(mut scene) => {
+                scene.on_enter(state);
+                self.scenes.push(scene);
+            }
+            Transition::Pop => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.on_exit(state);
+                }
+            }
+            Transition::Replace(scene) => {
+                self.apply(Transition::Pop, state);
+                self.apply(Transition::Push(scene), state);
+            }
+            Transition::Fade(duration, transition) => {
+                if self.fade.is_some() {
+                    return;
+                }
+                let duration = duration.as_secs_f32() * 1000.0;
+                if duration <= 0.0 {
+                    self.apply(*transition, state);
+                } else {
+                    self.fade = Some(Fade {
+                        duration,
+                        elapsed: 0.0,
+                        pending: Some(*transition),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Lets the top scene read input and applies the transition it returns.
+    pub fn handle_input(&mut self, state: &mut S) {
+        if self.fade.is_some() {
+            return;
+        }
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.handle_input(state);
+            self.apply(transition, state);
+        }
+    }
+
+    /// Advances a running fade, or else updates the top scene and applies its transition.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The shared game state.
+    /// * `dt` - The time delta since the last update (in milliseconds).
+    pub fn update(&mut self, state: &mut S, dt: f32) {
+        if let Some(fade) = &mut self.fade {
+            fade.elapsed += dt;
+            let pending = if fade.elapsed >= fade.duration / 2.0 {
+                fade.pending.take()
+            } else {
+                None
+            };
+            if fade.elapsed >= fade.duration {
+                self.fade = None;
+            }
+            if let Some(transition) = pending {
+                self.apply(transition, state);
+            }
+            return;
+        }
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.update(state, dt);
+            self.apply(transition, state);
+        }
+    }
+
+    /// Renders the top scene, and below it every scene visible through overlays, bottom first.
+    pub fn render(&self, state: &S, output: &E) {
+        let first_visible = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in &self.scenes[first_visible..] {
+            scene.render(state, output);
+        }
+    }
+
+    /// Returns a hash of the number of scenes and the state of each, bottom first.
+    /// Two runs with the same seed and input must end with the same hash.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write_usize(self.scenes.len());
+        for scene in &self.scenes {
+            scene.hash_state(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shared test state: a log of the scene callbacks.
+    type Log = Vec<String>;
+
+    /// A scene that logs its callbacks and returns a prepared transition once.
+    struct Logger {
+        name: &'static str,
+        overlay: bool,
+        next: Option<Transition<Log, ()>>,
+    }
+
+    fn scene(name: &'static str) -> Logger {
+        Logger {
+            name,
+            overlay: false,
+            next: None,
+        }
+    }
+
+    impl Scene<Log, ()> for Logger {
+        fn on_enter(&mut self, log: &mut Log) {
+            log.push(format!("enter {}", self.name));
+        }
+
+        fn on_exit(&mut self, log: &mut Log) {
+            log.push(format!("exit {}", self.name));
+        }
+
+        fn handle_input(&mut self, log: &mut Log) -> Transition<Log, ()> {
+            log.push(format!("input {}", self.name));
+            Transition::None
+        }
+
+        fn update(&mut self, log: &mut Log, _: f32) -> Transition<Log, ()> {
+            log.push(format!("update {}", self.name));
+            self.next.take().unwrap_or(Transition::None)
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+    }
+
+    /// Records the scenes a render call draws, in order.
+    struct Painter(&'static str, bool);
+
+    impl Scene<std::cell::RefCell<Log>, ()> for Painter {
+        fn render(&self, log: &std::cell::RefCell<Log>, _: &()) {
+            log.borrow_mut().push(self.0.to_string());
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.1
+        }
+    }
+
+    /// Verify that push, pop and replace call the enter and exit hooks in order.
+    #[test]
+    fn test_push_pop_replace() {
+        let mut log = Log::new();
+        let mut stack = SceneStack::new();
+
+        stack.push(Box::new(scene("title")), &mut log);
+        stack.replace(Box::new(scene("match")), &mut log);
+        stack.push(Box::new(scene("pause")), &mut log);
+        stack.pop(&mut log);
+        stack.pop(&mut log);
+
+        assert_eq!(
+            log,
+            [
+                "enter title",
+                "exit title",
+                "enter match",
+                "enter pause",
+                "exit pause",
+                "exit match"
+            ]
+        );
+        assert!(stack.is_empty());
+    }
+
+    /// Verify that only the top scene handles input and updates.
+    #[test]
+    fn test_only_top_scene_runs() {
+        let mut log = Log::new();
+        let mut stack = SceneStack::new();
+        let pause = Logger {
+            next: Some(Transition::Pop),
+            ..scene("pause")
+        };
+        stack.push(Box::new(scene("match")), &mut log);
+        stack.push(Box::new(pause), &mut log);
+        log.clear();
+
+        stack.handle_input(&mut log);
+        stack.update(&mut log, 16.0);
+        stack.update(&mut log, 16.0);
+
+        assert_eq!(
+            log,
+            ["input pause", "update pause", "exit pause", "update match"]
+        );
+    }
+
+    /// Verify that overlays render over the scenes below them, bottom first.
+    #[test]
+    fn test_overlay_rendering() {
+        let log = std::cell::RefCell::new(Log::new());
+        let mut state = std::cell::RefCell::new(Log::new());
+        let mut stack = SceneStack::new();
+        stack.push(Box::new(Painter("title", false)), &mut state);
+        stack.push(Box::new(Painter("match", false)), &mut state);
+        stack.push(Box::new(Painter("pause", true)), &mut state);
+        stack.push(Box::new(Painter("hint", true)), &mut state);
+
+        stack.render(&log, &());
+        assert_eq!(*log.borrow(), ["match", "pause", "hint"]);
+
+        stack.pop(&mut state);
+        stack.pop(&mut state);
+        log.borrow_mut().clear();
+        stack.render(&log, &());
+        assert_eq!(*log.borrow(), ["match"]);
+    }
+
+    /// Verify that a fading transition changes the stack at full black and blocks input.
+    #[test]
+    fn test_fade() {
+        let mut log = Log::new();
+        let mut stack = SceneStack::new();
+        stack.push(Box::new(scene("title")), &mut log);
+        stack.apply(
+            Transition::replace(scene("match")).with_fade(Duration::from_millis(400)),
+            &mut log,
+        );
+        log.clear();
+
+        stack.update(&mut log, 100.0);
+        assert!((stack.fade_level() - 0.5).abs() < 1e-6);
+        stack.handle_input(&mut log);
+        assert!(log.is_empty());
+
+        stack.update(&mut log, 100.0);
+        assert_eq!(log, ["exit title", "enter match"]);
+        assert!((stack.fade_level() - 1.0).abs() < 1e-6);
+
+        stack.update(&mut log, 250.0);
+        assert!(!stack.is_fading());
+        assert_eq!(stack.fade_level(), 0.0);
+        stack.update(&mut log, 16.0);
+        assert_eq!(log.last().unwrap(), "update match");
+    }
+}
@@ -0,0 +1,317 @@
+//! Sprite definitions and logic.
+//!
+//! This module defines the core data structures for representing sprites (game objects),
+//! including their position, size, velocity, and color. It also provides methods for
+//! creating and manipulating sprites, and synchronizing their state with the game engine.
+
+#[cfg(backend)]
+use crate::ffi;
+use crate::collider::Collider;
+use crate::math::{Aabb, Vec2};
+
+/// Represents an RGB color used to render sprites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: i32,
+    pub g: i32,
+    pub b: i32,
+}
+
+/// Represents a position in 2D game world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pos {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Represents a velocity vector in the game world,
+/// indicating how much the position changes per millisecond.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Velocity {
+    pub dx: f32,
+    pub dy: f32,
+}
+
+/// Represents the size (width and height) of a sprite in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// How a sprite takes part in collisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BodyKind {
+    /// Never moves; others bounce off it.
+    Static,
+    /// Moves by its velocity or by game code, e.g. a paddle, but is not pushed by
+    /// collisions; others bounce off it.
+    Kinematic,
+    /// Moves by its velocity and is pushed apart and bounced by collisions.
+    #[default]
+    Dynamic,
+}
+
+impl BodyKind {
+    /// Returns `true` for dynamic bodies, the only ones collisions move.
+    pub fn is_dynamic(self) -> bool {
+        self == BodyKind::Dynamic
+    }
+}
+
+/// Collision layer of sprites that do not set one.
+pub const DEFAULT_LAYER: u32 = 1;
+
+/// Layer bit of the window borders: a sprite touches the borders if its
+/// [`Sprite::mask`] has this bit.
+pub const BORDER_LAYER: u32 = 1 << 31;
+
+/// Mask of a sprite that collides with everything, the default.
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// The fields of a sprite that the level and save loaders check before creating it.
+#[cfg(feature = "serde")]
+pub(crate) struct SpriteFields<'a> {
+    pub size: Size,
+    pub color: Color,
+    pub mass: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    pub linear_damping: f32,
+    pub collider: &'a Collider,
+}
+
+#[cfg(feature = "serde")]
+impl SpriteFields<'_> {
+    /// Returns what makes a sprite with these fields unusable, e.g. "needs a positive
+    /// mass", or `None` if they are valid.
+    pub(crate) fn problem(&self) -> Option<String> {
+        if !(self.size.width > 0.0 && self.size.height > 0.0) {
+            return Some("needs a positive size".to_string());
+        }
+        if !(self.mass > 0.0 && self.mass.is_finite()) {
+            return Some("needs a positive mass".to_string());
+        }
+        for (field, value) in [
+            ("restitution", self.restitution),
+            ("friction", self.friction),
+            ("linear damping", self.linear_damping),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Some(format!("needs a non-negative {field}"));
+            }
+        }
+        if !self.collider.is_valid() {
+            return Some(
+                "needs a collider with a positive, finite size or a convex polygon".to_string(),
+            );
+        }
+        let color = self.color;
+        if [color.r, color.g, color.b]
+            .iter()
+            .any(|c| !(0..=255).contains(c))
+        {
+            return Some("has a color component outside 0 to 255".to_string());
+        }
+        None
+    }
+}
+
+/// Represents a game sprite, which is a renderable object in the world.
+///
+/// Without a backend feature there is no engine-side sprite, and the sprite is plain data.
+pub struct Sprite {
+    /// Pointer to the underlying C `Sprite` managed by the engine.
+    #[cfg(backend)]
+    c_sprite: *mut ffi::Sprite,
+
+    /// Current position of the sprite in world space.
+    pub pos: Pos,
+
+    pub size: Size,
+
+    /// Movement velocity of the sprite, in pixels per millisecond, used by movement,
+    /// collision response and physics.
+    pub velocity: Velocity,
+
+    /// Color the engine renders the sprite with, set when the sprite is created.
+    pub color: Color,
+
+    /// How the sprite responds to collisions, dynamic by default.
+    pub body: BodyKind,
+
+    /// Share of the speed along the contact normal kept after a bounce, `1.0` (the
+    /// default) for a perfectly elastic one. Of two touching sprites the larger value
+    /// counts. Must not be negative.
+    pub restitution: f32,
+
+    /// Mass of a dynamic sprite, `1.0` by default; must be positive. In a collision
+    /// between two dynamic sprites the lighter one is pushed and bounced more.
+    pub mass: f32,
+
+    /// Friction coefficient, `0.0` (the default) for frictionless sliding. Of two
+    /// touching sprites the geometric mean counts; at a border the sprite's own value.
+    /// Must not be negative.
+    pub friction: f32,
+
+    /// Share of the velocity a dynamic sprite loses per millisecond, applied by
+    /// [`crate::physics::Physics`]; `0.0` by default, and must not be negative.
+    pub linear_damping: f32,
+
+    /// Collision layers the sprite is on, one bit per layer; [`DEFAULT_LAYER`] by default.
+    pub layer: u32,
+
+    /// Layers the sprite collides with, [`ALL_LAYERS`] by default. Two sprites collide
+    /// only if each one is on a layer in the other's mask.
+    pub mask: u32,
+
+    /// A trigger (sensor) reports overlaps as collision events, but neither moves nor
+    /// bounces the sprites it overlaps, and passes through the window borders.
+    pub trigger: bool,
+
+    /// Shape the sprite collides with, independent of the rectangle it is drawn with;
+    /// [`Collider::Sprite`] (that rectangle) by default.
+    pub collider: Collider,
+}
+
+impl Sprite {
+    /// Creates a new sprite and registers it with the game engine.
+    ///
+    /// Allocates a sprite using the provided position, velocity, color, and size,
+    /// and returns a managed `Sprite` wrapper around the engine’s underlying `ffi::Sprite` pointer.
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - The initial position of the sprite.
+    /// * `velocity` - The movement velocity of the sprite.
+    /// * `color` - The sprite’s color, passed to the engine.
+    /// * `size` - The width and height of the sprite.
+    pub fn new(pos: Pos, velocity: Velocity, color: Color, size: Size) -> Self {
+        // `rust_create_sprite` allocates a C-side sprite
+        // and returns a raw pointer. This pointer is stored in
+        // `c_sprite` and freed when the sprite is dropped.
+        // Rendering happens later, once the window exists.
+        #[cfg(backend)]
+        let sprite_ptr = ffi::rust_create_sprite(
+            pos.x,
+            pos.y,
+            size.width as i32,
+            size.height as i32,
+            color.r,
+            color.g,
+            color.b
+        );
+        Self {
+            #[cfg(backend)]
+            c_sprite: sprite_ptr,
+            pos,
+            size,
+            velocity,
+            color,
+            body: BodyKind::Dynamic,
+            restitution: 1.0,
+            mass: 1.0,
+            friction: 0.0,
+            linear_damping: 0.0,
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+            trigger: false,
+            collider: Collider::Sprite,
+        }
+    }
+
+    /// Returns the inverse of the mass for dynamic sprites, and zero for static and
+    /// kinematic ones, which collisions cannot move.
+    ///
+    /// A mass that is not positive and finite also gives zero, so an invalid sprite
+    /// behaves like an immovable one instead of spreading infinities and NaN.
+    pub fn inverse_mass(&self) -> f32 {
+        if self.body.is_dynamic() && self.mass > 0.0 && self.mass.is_finite() {
+            1.0 / self.mass
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns `true` if the layers and masks of the two sprites let them collide.
+    pub fn interacts_with(&self, other: &Sprite) -> bool {
+        self.layer & other.mask != 0 && other.layer & self.mask != 0
+    }
+
+    /// Returns `true` if the sprite bounces off the window borders: it is dynamic, not
+    /// a trigger, and its mask has [`BORDER_LAYER`].
+    pub fn touches_borders(&self) -> bool {
+        self.body.is_dynamic() && !self.trigger && self.mask & BORDER_LAYER != 0
+    }
+
+    /// Returns the box the sprite covers.
+    pub fn bounds(&self) -> Aabb {
+        let min = Vec2::from(self.pos);
+        Aabb::new(min, min + Vec2::new(self.size.width, self.size.height))
+    }
+
+    /// Returns a raw pointer to the underlying C sprite.
+    ///
+    /// # Safety
+    ///
+    /// The sprite owns this pointer and frees it when dropped.
+    /// Do not attempt to free, clone, or otherwise manage its memory directly,
+    /// or use it after the sprite is gone.
+    #[cfg(backend)]
+    pub fn get_c_sprite(&self) -> *mut ffi::Sprite {
+        self.c_sprite
+    }
+
+ 
+    /// Updates the internal position of the sprite and synchronizes it with the engine-side
+    pub fn set_pos(&mut self, pos: &Pos) {
+        // Update the Rust-side position first...
+    
+        self.pos.x = pos.x;
+        self.pos.y = pos.y;
+
+        // ...then update the engine-side representation
+        self.update_pos();
+    }
+
+    /// Synchronizes the engine-side sprite position with the Rust-side position.
+    pub fn update_pos(&self) {
+        // SAFETY: `c_sprite` comes from `rust_create_sprite` and is only freed on drop.
+        #[cfg(backend)]
+        unsafe {
+            ffi::rust_update_sprite_position(self.c_sprite, self.pos.x, self.pos.y);
+        }
+    }
+
+    /// Renders the sprite at its engine-side position.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - The engine context of the game window.
+    #[cfg(backend)]
+    pub fn render(&self, engine: &crate::engine::Engine) {
+        // SAFETY: `c_sprite` comes from `rust_create_sprite` and is only freed on drop.
+        unsafe {
+            ffi::rust_render_sprite(engine, self.c_sprite);
+        }
+    }
+}
+
+#[cfg(backend)]
+impl Drop for Sprite {
+    /// Frees the engine-side sprite, so replacing the sprites of a world, e.g. by
+    /// loading a level or a save, does not leak the old ones.
+    fn drop(&mut self) {
+        // SAFETY: `c_sprite` comes from `rust_create_sprite`, and only this sprite,
+        // which is not `Clone`, owns it.
+        unsafe {
+            ffi::rust_free_sprite(self.c_sprite);
+        }
+    }
+}
@@ -0,0 +1,477 @@
+//! Game world logic.
+//!
+//! This module defines the [`World`] struct, which manages all sprites in the game
+//! as well as the window size. It provides methods for creating, adding, moving,
+//! and retrieving sprites by name.
+//!
+//! A `World` stores its sprites in a `HashMap<String, Sprite>`, where each sprite
+//! is identified by a unique string key. A [`SpatialHash`] over the collider bounds
+//! narrows collision checks to nearby sprites and answers region and point queries.
+//! Game code can [`World::subscribe`] to the collision events of the world.
+
+use crate::broad_phase::SpatialHash;
+use crate::collision::CollisionEvent;
+use crate::hash::StateHasher;
+use crate::math::{Aabb, Vec2};
+use crate::sprite::*;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Called with every collision event of a world.
+pub type CollisionHandler = Box<dyn FnMut(&CollisionEvent)>;
+
+/// Identifies a handler registered with [`World::subscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription(u64);
+
+/// Represents the game world, containing all sprites and the window size.
+pub struct World {
+    /// A collection of sprites in the world, keyed by name.
+    pub sprites: HashMap<String, Sprite>,
+    /// The dimensions of the game window.
+    pub window: Size,
+    /// Collider bounds by name, brought up to date by [`World::sync_broad_phase`].
+    broad_phase: SpatialHash<String>,
+    subscribers: Vec<(Subscription, CollisionHandler)>,
+    next_subscription: u64,
+}
+
+impl World {
+    /// Creates an empty world with no sprites and a default window size of
+    /// `1024x768` pixels.
+    ///
+    /// # Returns
+    ///
+    /// A new [`World`] instance with no sprites.
+    pub fn empty() -> Self {
+        Self {
+            sprites: HashMap::new(),
+            window: Size {
+                width: 1024.0,
+                height: 768.0,
+            },
+            broad_phase: SpatialHash::default(),
+            subscribers: Vec::new(),
+            next_subscription: 0,
+        }
+    }
+
+    /// Adds a new sprite to the world.
+    ///
+    /// If a sprite with the same name already exists, it will be replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The unique identifier for the sprite.
+    /// * `pos` - The initial position of the sprite.
+    /// * `velocity` - The velocity of the sprite.
+    /// * `size` - The dimensions of the sprite.
+    /// * `color` - The color of the sprite.
+    pub fn add_sprite(
+        &mut self,
+        name: &str,
+        pos: Pos,
+        velocity: Velocity,
+        size: Size,
+        color: Color,
+    ) {
+        self.sprites
+            .insert(String::from(name), Sprite::new(pos, velocity, color, size));
+    }
+
+    /// Updates the position of a sprite in the world.
+    ///
+    /// If the sprite with the given name does not exist, this method does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sprite to update.
+    /// * `pos` - The new position of the sprite.
+    pub fn set_sprite_pos(&mut self, name: &str, pos: Pos) {
+        if let Some(sprite) = self.sprites.get_mut(name) {
+            sprite.set_pos(&pos);
+        }
+    }
+
+    /// Sets the velocity of a sprite in the world.
+    ///
+    /// If the sprite with the given name does not exist, this method does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sprite to update.
+    /// * `velocity` - The new velocity of the sprite.
+    pub fn set_sprite_velocity(&mut self, name: &str, velocity: Velocity) {
+        if let Some(sprite) = self.sprites.get_mut(name) {
+            sprite.velocity = velocity;
+        }
+    }
+
+
+    /// Returns a reference to all sprites in the world.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the underlying [`HashMap`] of sprites.
+    pub fn get_sprites(&self) -> &HashMap<String, Sprite> {
+        &self.sprites
+    }
+
+    /// Returns a reference to a sprite by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sprite to retrieve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sprite does not exist in the world.
+    pub fn get_sprite(&self, name: &str) -> &Sprite {
+        &self.sprites[name]
+    }
+
+    /// Returns the names of all sprites that overlap or touch `area`, sorted.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - The region to search, in world space.
+    pub fn sprites_in(&mut self, area: Aabb) -> Vec<String> {
+        self.sync_broad_phase();
+        self.broad_phase.query(area)
+    }
+
+    /// Returns the names of all sprites that cover `point`, sorted.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to test, in world space.
+    pub fn sprites_at(&mut self, point: Vec2) -> Vec<String> {
+        self.sync_broad_phase();
+        self.broad_phase.query_point(point)
+    }
+
+    /// Brings the broad phase up to date with the sprite map and returns it.
+    ///
+    /// `sprites` is public and may change anywhere, so the bounds of every sprite are
+    /// compared with the stored ones, and only new and moved sprites are inserted.
+    /// Removed sprites are only searched for when the hash holds more keys than there
+    /// are sprites.
+    pub(crate) fn sync_broad_phase(&mut self) -> &SpatialHash<String> {
+        let sprites = &self.sprites;
+        for (name, sprite) in sprites {
+            let bounds = sprite.collider_bounds();
+            if self.broad_phase.bounds(name) != Some(bounds) {
+                self.broad_phase.insert(name.clone(), bounds);
+            }
+        }
+        if self.broad_phase.len() > sprites.len() {
+            self.broad_phase.retain(|name| sprites.contains_key(name));
+        }
+        &self.broad_phase
+    }
+
+    /// Registers a handler for the collision events found by
+    /// [`crate::movement::move_objects`] and [`crate::collision::collision`].
+    ///
+    /// Handlers run in the order they were registered, after the collisions of a call
+    /// have been resolved.
+    ///
+    /// # Returns
+    ///
+    /// A handle to pass to [`World::unsubscribe`].
+    pub fn subscribe(&mut self, handler: impl FnMut(&CollisionEvent) + 'static) -> Subscription {
+        let subscription = Subscription(self.next_subscription);
+        self.next_subscription += 1;
+        self.subscribers.push((subscription, Box::new(handler)));
+        subscription
+    }
+
+    /// Removes a handler registered with [`World::subscribe`].
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the handler was already removed.
+    pub fn unsubscribe(&mut self, subscription: Subscription) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|(id, _)| *id != subscription);
+        self.subscribers.len() != before
+    }
+
+    /// Passes `events` to every subscribed handler.
+    pub(crate) fn publish(&mut self, events: &[CollisionEvent]) {
+        for (_, handler) in &mut self.subscribers {
+            for event in events {
+                handler(event);
+            }
+        }
+    }
+
+    /// Feeds the window size and every sprite's name, position, size, velocity and
+    /// color into `hasher`, sorted by name so the map order does not matter.
+    pub fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_f32(self.window.width);
+        hasher.write_f32(self.window.height);
+        let mut names: Vec<&String> = self.sprites.keys().collect();
+        names.sort();
+        hasher.write_usize(names.len());
+        for name in names {
+            let sprite = &self.sprites[name];
+            name.hash(hasher);
+            for value in [
+                sprite.pos.x,
+                sprite.pos.y,
+                sprite.size.width,
+                sprite.size.height,
+                sprite.velocity.dx,
+                sprite.velocity.dy,
+            ] {
+                hasher.write_f32(value);
+            }
+            for channel in [sprite.color.r, sprite.color.g, sprite.color.b] {
+                hasher.write_i32(channel);
+            }
+        }
+    }
+
+    /// Returns the hash of [`World::hash_state`], to compare two runs of a game.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        self.hash_state(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::{Color, Pos, Size, Velocity};
+
+    /// Verify that a new world starts with no sprites.
+    #[test]
+    fn test_empty() {
+        let world = World::empty();
+        assert!(world.sprites.is_empty());
+    }
+
+    /// Verify that a sprite can be added and its properties are stored correctly.
+    #[test]
+    fn test_add_sprite() {
+        let mut world = World::empty();
+        let name = "player1";
+        let pos = Pos { x: 1.0, y: 2.0 };
+        let size = Size {
+            width: 10.0,
+            height: 20.0,
+        };
+        let color = Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        let velocity = Velocity { dx: 0.0, dy: 0.0 };
+
+        world.add_sprite(name, pos, velocity, size, color);
+
+        assert!(world.sprites.contains_key(name));
+        let sprite = world.sprites.get(name).unwrap();
+        assert_eq!(sprite.pos.x, 1.0);
+        assert_eq!(sprite.pos.y, 2.0);
+        assert_eq!(sprite.color.r, 255);
+        assert_eq!(sprite.color.g, 128);
+        assert_eq!(sprite.color.b, 0);
+    }
+
+
+    /// Verify that `get_sprites` returns the correct collection of sprites.
+    #[test]
+    fn test_get_sprites() {
+        let mut world = World::empty();
+        let velocity = Velocity { dx: 0.0, dy: 0.0 };
+        world.add_sprite(
+            "foo",
+            Pos { x: 0.0, y: 0.0 },
+            velocity,
+            Size {
+                width: 1.0,
+                height: 1.0,
+            },
+            Color { r: 0, g: 0, b: 0 },
+        );
+        let sprites = world.get_sprites();
+        assert_eq!(sprites.len(), 1);
+        assert!(sprites.contains_key("foo"));
+    }
+
+        /// Verify that `set_sprite_pos` updates the sprite's position.
+    #[test]
+    fn test_set_sprite_pos() {
+        let mut world = World::empty();
+        world.add_sprite(
+            "ball",
+            Pos { x: 5.0, y: 5.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 2.0, height: 2.0 },
+            Color { r: 255, g: 255, b: 255 },
+        );
+
+        world.set_sprite_pos("ball", Pos { x: 10.0, y: 15.0 });
+
+        let sprite = world.get_sprite("ball");
+        assert_eq!(sprite.pos.x, 10.0);
+        assert_eq!(sprite.pos.y, 15.0);
+    }
+
+    /// Verify that `set_sprite_pos` does nothing if the sprite does not exist.
+    #[test]
+    fn test_set_sprite_pos_nonexistent() {
+        let mut world = World::empty();
+        world.set_sprite_pos("ghost", Pos { x: 10.0, y: 10.0 });
+        // Still empty
+        assert!(world.sprites.is_empty());
+    }
+
+    /// Verify that `get_sprite` retrieves the correct sprite.
+    #[test]
+    fn test_get_sprite() {
+        let mut world = World::empty();
+        world.add_sprite(
+            "paddle",
+            Pos { x: 3.0, y: 4.0 },
+            Velocity { dx: 1.0, dy: 1.0 },
+            Size { width: 5.0, height: 10.0 },
+            Color { r: 0, g: 0, b: 255 },
+        );
+
+        let sprite = world.get_sprite("paddle");
+        assert_eq!(sprite.pos.x, 3.0);
+        assert_eq!(sprite.size.width, 5.0);
+        assert_eq!(sprite.color.b, 255);
+    }
+
+    /// Verify that `get_sprite` panics if the sprite does not exist.
+    #[test]
+    #[should_panic]
+    fn test_get_sprite_nonexistent_panics() {
+        let world = World::empty();
+        world.get_sprite("not_there");
+    }
+
+    /// Verify that adding a sprite with the same name replaces the old one.
+    #[test]
+    fn test_add_sprite_overwrites() {
+        let mut world = World::empty();
+        world.add_sprite(
+            "ball",
+            Pos { x: 1.0, y: 1.0 },
+            Velocity { dx: 0.0, dy: 0.0 },
+            Size { width: 1.0, height: 1.0 },
+            Color { r: 255, g: 0, b: 0 },
+        );
+        world.add_sprite(
+            "ball",
+            Pos { x: 2.0, y: 2.0 },
+            Velocity { dx: 1.0, dy: 1.0 },
+            Size { width: 2.0, height: 2.0 },
+            Color { r: 0, g: 255, b: 0 },
+        );
+
+        let sprite = world.get_sprite("ball");
+        assert_eq!(sprite.pos.x, 2.0);
+        assert_eq!(sprite.color.g, 255);
+        assert_eq!(sprite.size.width, 2.0);
+    }
+
+    /// Verify that the default window size is 1024x768.
+    #[test]
+    fn test_default_window_size() {
+        let world = World::empty();
+        assert_eq!(world.window.width, 1024.0);
+        assert_eq!(world.window.height, 768.0);
+    }
+
+    /// Verify that the state hash ignores insertion order and sees every position change.
+    #[test]
+    fn test_state_hash() {
+        let add = |world: &mut World, name: &str, x: f32| {
+            world.add_sprite(
+                name,
+                Pos { x, y: 0.0 },
+                Velocity { dx: 0.0, dy: 0.0 },
+                Size { width: 1.0, height: 1.0 },
+                Color { r: 255, g: 0, b: 0 },
+            );
+        };
+        let mut a = World::empty();
+        add(&mut a, "ball", 1.0);
+        add(&mut a, "paddle", 2.0);
+        let mut b = World::empty();
+        add(&mut b, "paddle", 2.0);
+        add(&mut b, "ball", 1.0);
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        b.set_sprite_pos("ball", Pos { x: 1.0, y: 0.5 });
+        assert_ne!(a.state_hash(), b.state_hash());
+    }
+
+    /// Verify that region and point queries follow moved and removed sprites.
+    #[test]
+    fn test_sprite_queries() {
+        let mut world = World::empty();
+        for (name, x) in [("left", 0.0), ("right", 500.0)] {
+            world.add_sprite(
+                name,
+                Pos { x, y: 0.0 },
+                Velocity { dx: 0.0, dy: 0.0 },
+                Size { width: 10.0, height: 10.0 },
+                Color { r: 255, g: 0, b: 0 },
+            );
+        }
+        let area = Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+        assert_eq!(world.sprites_in(area), vec!["left"]);
+        assert_eq!(world.sprites_at(Vec2::new(505.0, 5.0)), vec!["right"]);
+
+        world.set_sprite_pos("right", Pos { x: 50.0, y: 50.0 });
+        world.sprites.remove("left");
+        assert_eq!(world.sprites_in(area), vec!["right"]);
+        assert!(world.sprites_at(Vec2::new(505.0, 5.0)).is_empty());
+
+        // A sprite renamed to a removed name replaces its entry, the old name is gone.
+        let mut sprite = world.sprites.remove("right").unwrap();
+        sprite.pos = Pos { x: 500.0, y: 0.0 };
+        world.sprites.insert("left".to_string(), sprite);
+        assert!(world.sprites_in(area).is_empty());
+        assert_eq!(world.sprites_at(Vec2::new(505.0, 5.0)), vec!["left"]);
+    }
+
+    /// Verify that subscribers see the collision events until they unsubscribe.
+    #[test]
+    fn test_subscribe() {
+        use crate::collision::{self, Border};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut world = World::empty();
+        world.add_sprite(
+            "ball",
+            Pos { x: -5.0, y: 100.0 },
+            Velocity { dx: -1.0, dy: 0.0 },
+            Size { width: 10.0, height: 10.0 },
+            Color { r: 255, g: 0, b: 0 },
+        );
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let subscription = world.subscribe({
+            let seen = Rc::clone(&seen);
+            move |event| seen.borrow_mut().push((event.sprite.clone(), event.border()))
+        });
+
+        collision::collision(&mut world, 0.0);
+        assert_eq!(*seen.borrow(), vec![("ball".to_string(), Some(Border::Left))]);
+
+        assert!(world.unsubscribe(subscription));
+        assert!(!world.unsubscribe(subscription));
+        world.set_sprite_pos("ball", Pos { x: -5.0, y: 100.0 });
+        world.set_sprite_velocity("ball", Velocity { dx: -1.0, dy: 0.0 });
+        collision::collision(&mut world, 0.0);
+        assert_eq!(seen.borrow().len(), 1);
+    }
+}
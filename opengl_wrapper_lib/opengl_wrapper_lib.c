@@ -41,6 +41,11 @@ Sprite* create_sprite(float x, float y, int width, int height, int r, int g, int
     return sprite;
 }
 
+// Function to free a sprite created by create_sprite
+void free_sprite(Sprite *sprite) {
+    free(sprite);
+}
+
 // Function to render a sprite
 void render_sprite(Sprite *sprite) {
     // Convert sprite position and size to window coordinates
@@ -85,6 +90,34 @@ int get_key(GLFWwindow* window, int key) {
     return glfwGetKey(window, key);
 }
 
+// Function to get mouse button state
+int get_mouse_button(GLFWwindow* window, int button) {
+    return glfwGetMouseButton(window, button);
+}
+
+// Function to get the cursor position in window coordinates
+void get_cursor_pos(GLFWwindow* window, double* x, double* y) {
+    glfwGetCursorPos(window, x, y);
+}
+
+// Function to get a button state of the first gamepad, released if none is connected
+int get_gamepad_button(int button) {
+    GLFWgamepadstate state;
+    if (button < 0 || button > GLFW_GAMEPAD_BUTTON_LAST || !glfwGetGamepadState(GLFW_JOYSTICK_1, &state)) {
+        return GLFW_RELEASE;
+    }
+    return state.buttons[button];
+}
+
+// Function to get an axis of the first gamepad, 0 if none is connected
+float get_gamepad_axis(int axis) {
+    GLFWgamepadstate state;
+    if (axis < 0 || axis > GLFW_GAMEPAD_AXIS_LAST || !glfwGetGamepadState(GLFW_JOYSTICK_1, &state)) {
+        return 0.0f;
+    }
+    return state.axes[axis];
+}
+
 // Function to get the window pointer
 GLFWwindow* get_window() {
     return window;
@@ -17,6 +17,9 @@ void create_game_window(const char *title, int width, int height);
 // Function to create a sprite
 Sprite* create_sprite(float x, float y, int width, int height, int r, int g, int b);
 
+// Function to free a sprite created by create_sprite
+void free_sprite(Sprite *sprite);
+
 // Function to render a sprite
 void render_sprite(Sprite *sprite);
 
@@ -35,6 +38,18 @@ int window_should_close();
 // Function to get key state
 int get_key(GLFWwindow* window, int key);
 
+// Function to get mouse button state
+int get_mouse_button(GLFWwindow* window, int button);
+
+// Function to get the cursor position in window coordinates
+void get_cursor_pos(GLFWwindow* window, double* x, double* y);
+
+// Function to get a button state of the first gamepad, released if none is connected
+int get_gamepad_button(int button);
+
+// Function to get an axis of the first gamepad, 0 if none is connected
+float get_gamepad_axis(int axis);
+
 // Function to get the window pointer
 GLFWwindow* get_window();
 
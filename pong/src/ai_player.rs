@@ -3,8 +3,8 @@
 //! This module implements the logic for controlling the AI paddle ("player2").
 //! The AI paddle attempts to follow the ball's vertical position with a fixed speed.
 
-use crate::sprite::Pos;
-use crate::world::World;
+use game_engine::sprite::Pos;
+use game_engine::world::World;
 
 /// Calculates and applies the next action for the AI player ("player2").
 ///
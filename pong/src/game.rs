@@ -1,149 +1,392 @@
 //! Main game logic for Pong.
 //!
-//! This module defines the `Game` struct, which manages the game world, timing,
-//! sound effects, and the main game loop. It handles initialization, input processing,
-//! AI actions, movement, collision detection, rendering, and cleanup.
+//! This module defines the `Game` struct, which drives the scene stack (title, match,
+//! pause and game over, see [`crate::scenes`]) and owns the state shared by the scenes:
+//! input actions, random numbers, assets, audio and sound effects. Frames can be
+//! recorded to a replay file and played back, see [`Game::record`] and [`Game::replay`].
+//!
+//! Time comes from a [`Clock`] and randomness from a seeded [`Rng`], so with the same
+//! seed, clock and input the game always reaches the same state; [`Game::state_hash`]
+//! lets tests compare it.
 
-use crate::ai_player;
 use crate::input;
-use crate::movement;
-use crate::movement::CollisionType;
-use crate::sound::SoundEffect;
-use crate::sprite::{Color, Pos, Size, Velocity};
-use crate::view;
-use crate::world::World;
+use crate::scenes::Title;
+use crate::view::Curtain;
+use game_engine::assets::{AssetManager, Handle};
+use game_engine::audio::{Audio, Listener, SfxPreset, Sound};
+use game_engine::clock::{Clock, RealClock};
+use game_engine::hash::StateHasher;
+use game_engine::input::{Bindings, Input};
+use game_engine::replay::{Frame, FrameSource, Recorder, Replay};
+use game_engine::rng::Rng;
+use game_engine::save;
+use game_engine::scene::SceneStack;
+use game_engine::sprite::Size;
 use game_engine::*;
-use log::info;
-use std::time::Instant;
+use log::{error, info};
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory with the game assets.
+const ASSET_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+/// Controls of the player in the data dir of the player, created with the defaults on
+/// the first start.
+fn controls_file() -> PathBuf {
+    save::data_dir("pong").join("controls.json")
+}
+
+/// Background music of a match, streamed if the file exists.
+pub const MATCH_MUSIC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/music/match.ogg");
 
-/// The main game structure, responsible for managing the game state and loop.
+/// A sound effect rendered from a preset in `assets/sfx`.
 ///
-/// Holds the game world, timing information, and sound effects.
+/// The preset loads in the background and is rendered again whenever its file is
+/// hot reloaded. Until it is loaded, and if it cannot be loaded, the default preset
+/// is played; a load error is logged once.
+pub struct Sfx {
+    preset: Handle<SfxPreset>,
+    /// Preset version the sound was rendered from.
+    version: u32,
+    sound: Sound,
+}
+
+impl Sfx {
+    /// Starts loading `assets/sfx/<name>.json`.
+    pub fn load(assets: &AssetManager, name: &str) -> Self {
+        Self {
+            preset: assets.load(format!("sfx/{name}.json")),
+            version: 0,
+            sound: SfxPreset::default().render(),
+        }
+    }
+
+    /// Returns the sound of the latest loaded preset.
+    pub fn sound(&mut self) -> &Sound {
+        let version = self.preset.version();
+        if version != self.version {
+            self.version = version;
+            match (self.preset.get(), self.preset.error()) {
+                (_, Some(err)) => error!("{err}"),
+                (Some(preset), None) => self.sound = preset.render(),
+                (None, None) => {}
+            }
+        }
+        &self.sound
+    }
+}
+
+/// State shared by all scenes.
+pub struct Shared {
+    /// Size of the game window.
+    pub window: Size,
+    pub input: Input,
+    /// Source of every random decision, so a seed repeats a session.
+    pub rng: Rng,
+    pub assets: AssetManager,
+    pub audio: Audio,
+    pub border_sound: Sfx,
+    pub paddle_sound: Sfx,
+}
+
+/// The main game structure, responsible for the scene stack and the game loop.
 pub struct Game {
-    world: World,
-    last_time: Instant,
-    sound_effect: SoundEffect,
+    stack: SceneStack<Shared, Engine>,
+    shared: Shared,
+    curtain: Curtain,
+    clock: Box<dyn Clock>,
+    /// Seed of `shared.rng`, stored in recordings.
+    seed: u64,
+    frames: FrameSource,
+    /// File the recorded frames are written to on quit.
+    recording: Option<PathBuf>,
 }
 
 impl Game {
-    /// Creates a new `Game` instance with an empty world, initializes timing, and sound effects.
+    /// Creates a new `Game` instance with an empty scene stack, timed by the wall clock
+    /// and seeded from the current time. Reads the controls from `controls.json` and
+    /// starts loading the sound effects from `assets/sfx`.
     ///
     /// # Returns
     ///
     /// A new `Game` object ready for initialization.
+    ///
+    /// Without an audio device the game runs silently.
     pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        info!("Seed {seed}");
+        let mut game = Self::with_clock(Box::new(RealClock::new(2.0)), seed);
+        game.shared.audio = Audio::new();
+        let bindings = Bindings::load_or_init(controls_file(), input::default_bindings())
+            .unwrap_or_else(|err| {
+                error!("{err}, using the default controls");
+                input::default_bindings()
+            });
+        game.shared.input = Input::new(bindings);
+        game
+    }
+
+    /// Creates a `Game` with the default controls and offline audio that takes its time
+    /// from `clock` and its random numbers from `seed`, for runs without a player.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Source of the time delta of each frame.
+    /// * `seed` - Seed of the game's random numbers.
+    pub fn with_clock(clock: Box<dyn Clock>, seed: u64) -> Self {
+        let window = Size {
+            width: 1024.0,
+            height: 768.0,
+        };
+        let assets = AssetManager::new(ASSET_DIR);
         Self {
-            world: World::empty(),
-            last_time: Instant::now(),
-            sound_effect: SoundEffect::new(),
+            stack: SceneStack::new(),
+            shared: Shared {
+                window,
+                input: Input::new(input::default_bindings()),
+                rng: Rng::new(seed),
+                audio: Audio::offline(),
+                border_sound: Sfx::load(&assets, "border_hit"),
+                paddle_sound: Sfx::load(&assets, "paddle_hit"),
+                assets,
+            },
+            curtain: Curtain::new(window),
+            clock,
+            seed,
+            frames: FrameSource::Live,
+            recording: None,
         }
     }
 
-    /// Initializes the game world and adds the player and ball sprites.
-    ///
-    /// Sets up the initial positions, sizes, velocities, and colors for all sprites.
+    /// Records the input and time delta of every frame, to be written to `path` on quit.
+    pub fn record(&mut self, path: impl Into<PathBuf>) {
+        self.frames = FrameSource::Recording(Recorder::new(self.seed));
+        self.recording = Some(path.into());
+    }
+
+    /// Plays the frames of a recorded session instead of reading the keyboard and the
+    /// clock, with the seed of the recording. Since the game is deterministic, the session
+    /// runs exactly as recorded; afterwards the game continues live.
+    pub fn replay(&mut self, replay: Replay) {
+        info!("Replaying {} frames", replay.len());
+        self.seed = replay.seed();
+        self.shared.rng = Rng::new(self.seed);
+        self.frames = FrameSource::Replaying(replay);
+    }
+
+    /// Initializes the game and opens the title screen.
     pub fn init(&mut self) {
         info!("Init game threads");
 
-        self.last_time = Instant::now();
+        self.clock.reset();
+        self.shared
+            .audio
+            .set_listener(Listener::centered(self.shared.window));
+        self.stack
+            .push(Box::new(Title::new(self.shared.window)), &mut self.shared);
+    }
 
-        // ✅ Updated: use Pos, Size, Color
-        self.world.add_sprite(
-            "player1",
-            Pos { x: 20.0, y: 100.0 },
-            Velocity { dx: 0.0, dy: 0.0 },
-            Size {
-                width: 30.0,
-                height: 200.0,
-            },
-            Color {
-                r: 255,
-                g: 255,
-                b: 255,
-            },
-        );
-        self.world.add_sprite(
-            "player2",
-            Pos {
-                x: 1024.0 - 50.0,
-                y: 200.0,
-            },
-            Velocity { dx: 0.0, dy: 0.0 },
-            Size {
-                width: 30.0,
-                height: 200.0,
-            },
-            Color {
-                r: 255,
-                g: 255,
-                b: 255,
-            },
-        );
+    /// Advances the game by one frame: updates the input actions, lets the top scene
+    /// handle input and updates the scenes. Does not render, so it runs without a window.
+    pub fn step(&mut self, frame: &Frame) {
+        self.shared.input.update(&frame.input);
+        self.stack.handle_input(&mut self.shared);
+        self.stack.update(&mut self.shared, frame.dt);
+    }
 
-        self.world.add_sprite(
-            "ball",
-            Pos {
-                x: 1024.0 / 2.0 - 30.0,
-                y: 50.0,
-            },
-            Velocity { dx: 0.2, dy: 0.2 },
-            Size {
-                width: 30.0,
-                height: 30.0,
-            },
-            Color {
-                r: 255,
-                g: 255,
-                b: 255,
-            },
-        );
+    /// Returns a hash of the scenes' state and the random number generator, equal for two
+    /// runs that played out the same.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.stack.state_hash());
+        hasher.write_u64(self.shared.rng.state());
+        hasher.finish()
     }
 
-    /// Calculates the time delta (dt) since the last frame in milliseconds.
+    /// Runs one iteration of the main game loop.
     ///
-    /// Updates the internal timer and returns the elapsed time as a `f32`.
+    /// Clears the screen, reads the input and time delta of the frame (live or from a
+    /// replay), advances the game with [`Game::step`], and renders the visible scenes with
+    /// the transition curtain on top.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The elapsed time in milliseconds since the last frame, as a `f32`.
-    fn calc_dt(&mut self) -> f32 {
-        let dt = self.last_time.elapsed().as_millis();
+    /// * `engine` - The engine context of the game window.
+    pub fn game_loop(&mut self, engine: &Engine) {
+        rust_clear_screen(engine);
 
-        self.last_time = Instant::now();
+        let dt = self.clock.tick();
+        let replaying = self.frames.is_replaying();
+        let frame = self.frames.next_frame(engine, dt);
+        if replaying && !self.frames.is_replaying() {
+            info!(
+                "Replay finished with state hash {:016x}, continuing live",
+                self.state_hash()
+            );
+        }
+        self.step(&frame);
 
-        if dt > 2 { dt as f32 } else { 2.0 }
+        self.stack.render(&self.shared, engine);
+        self.curtain.render(engine, self.stack.fade_level());
     }
 
-    /// Runs one iteration of the main game loop.
+    /// Cleans up the game and writes a recording, if one was started. The state hash
+    /// of a recording is logged, to compare with the end of its replay.
+    ///
+    /// # Returns
     ///
-    /// Clears the screen, processes player input and AI actions, updates movement,
-    /// checks for collisions (playing a sound if detected), and renders the world.
-    pub fn game_loop(&mut self) {
-        rust_clear_screen();
+    /// * `true` if cleanup was successful.
+    pub fn quit(self) -> bool {
+        if let (FrameSource::Recording(recorder), Some(path)) = (&self.frames, &self.recording) {
+            match recorder.save(path) {
+                Ok(()) => info!(
+                    "Recorded {} frames to {}, state hash {:016x}",
+                    recorder.len(),
+                    path.display(),
+                    self.state_hash()
+                ),
+                Err(err) => error!("{err}"),
+            }
+        }
+        true
+    }
+}
 
-        let dt = self.calc_dt();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::{COURT, MatchSave, Play, save_migrations};
+    use game_engine::clock::FixedClock;
+    use game_engine::input::{Button, InputState};
+    use game_engine::level::Level;
+    use game_engine::save::{self, Migrations};
+    use game_engine::sprite::{ALL_LAYERS, BodyKind, DEFAULT_LAYER};
 
-        input::process(&mut self.world, dt);
-        ai_player::calc_action(&mut self.world, dt);
+    /// Scripted frames: start a match, then keep the paddle mostly at the bottom, so the
+    /// player misses and the ball is served again.
+    fn script(frames: usize) -> Vec<Frame> {
+        (0..frames)
+            .map(|i| {
+                let key = match i {
+                    0 => ffi::GLFW_KEY_SPACE,
+                    _ if i % 500 < 20 => ffi::GLFW_KEY_UP,
+                    _ => ffi::GLFW_KEY_DOWN,
+                };
+                Frame {
+                    dt: 16.0,
+                    input: InputState {
+                        buttons: vec![Button::Key(key)],
+                        ..Default::default()
+                    },
+                }
+            })
+            .collect()
+    }
 
-        movement::move_objects(&mut self.world, dt);
-        let collision = movement::collision(&mut self.world, dt);
+    /// Runs `frames` on a new game with a fixed clock and returns its state hash.
+    fn run(seed: u64, frames: impl IntoIterator<Item = Frame>) -> u64 {
+        let mut game = Game::with_clock(Box::new(FixedClock::new(16.0)), seed);
+        game.init();
+        for frame in frames {
+            game.step(&frame);
+        }
+        game.state_hash()
+    }
 
-        match collision {
-            Some(CollisionType::WithBorder) => self.sound_effect.beep(220.0),
-            Some(CollisionType::WithSprite) => self.sound_effect.beep(120.0),
-            None => (),
+    /// Verify that the shipped sound effect presets parse.
+    #[test]
+    fn test_sfx_presets_load() {
+        let assets = AssetManager::new(ASSET_DIR);
+        for name in ["border_hit", "paddle_hit"] {
+            let preset = assets.load::<SfxPreset>(format!("sfx/{name}.json"));
+            let preset = preset.wait().unwrap();
+            assert!(preset.render().frames() > 0, "{name} is silent");
         }
+    }
+
+    /// Verify that the shipped court level is valid and has what a match needs.
+    #[test]
+    fn test_court_level_loads() {
+        let assets = AssetManager::new(ASSET_DIR);
+        let court = assets.load::<Level>(COURT).wait().unwrap();
+        let window = Size {
+            width: 1024.0,
+            height: 768.0,
+        };
 
-        view::render(&self.world);
+        assert!(Play::new(window, &court).is_ok());
+        assert_eq!(
+            court.entity("player2").unwrap().position(window).x,
+            1024.0 - 50.0
+        );
     }
 
-    /// Cleans up the game and performs any necessary shutdown procedures.
-    ///
-    /// # Returns
-    ///
-    /// * `true` if cleanup was successful.
-    pub fn quit(self) -> bool {
-        true
+    /// Verify that the same seed and input always reach the same state, and that a
+    /// recorded session replays to it.
+    #[test]
+    fn test_deterministic_runs() {
+        let frames = script(1500);
+        let hash = run(7, frames.clone());
+        assert_eq!(run(7, frames.clone()), hash);
+        assert_ne!(run(7, script(1)), hash);
+
+        let mut recorder = Recorder::new(7);
+        for frame in &frames {
+            recorder.record(frame.dt, &frame.input);
+        }
+        let replay = Replay::from_bytes(&recorder.to_bytes()).unwrap();
+        assert_eq!(run(replay.seed(), replay), hash);
+    }
+
+    /// Verify that saves from before body kinds and layers load with kinematic paddles
+    /// that only collide with the ball.
+    #[test]
+    fn test_version_1_save_loads() {
+        let assets = AssetManager::new(ASSET_DIR);
+        let court = assets.load::<Level>(COURT).wait().unwrap();
+        let window = Size {
+            width: 1024.0,
+            height: 768.0,
+        };
+        let mut saved = Play::new(window, &court).unwrap().suspend(&Rng::new(7));
+        for sprite in saved.world.sprites.values_mut() {
+            sprite.body = BodyKind::Dynamic;
+            sprite.layer = DEFAULT_LAYER;
+            sprite.mask = ALL_LAYERS;
+        }
+
+        let json = save::to_json(&saved, &Migrations::new(1)).unwrap();
+        let loaded: MatchSave = save::from_json(&json, &save_migrations()).unwrap();
+        assert_eq!(loaded.world.sprites["player1"].body, BodyKind::Kinematic);
+        assert_eq!(loaded.world.sprites["player2"].body, BodyKind::Kinematic);
+        assert_eq!(loaded.world.sprites["ball"].body, BodyKind::Dynamic);
+        assert_eq!(loaded.world.sprites["player1"].mask, 1);
+        assert_eq!(loaded.world.sprites["player2"].layer, 2);
+        assert_eq!(loaded.world.sprites["ball"].mask, ALL_LAYERS);
+        assert_eq!(loaded.rng, Rng::new(0));
+    }
+
+    /// Verify that a suspended match resumes with the same court, score and serves.
+    #[test]
+    fn test_match_suspends_and_resumes() {
+        let assets = AssetManager::new(ASSET_DIR);
+        let court = assets.load::<Level>(COURT).wait().unwrap();
+        let window = Size {
+            width: 1024.0,
+            height: 768.0,
+        };
+        let mut rng = Rng::new(7);
+        rng.next_u64();
+        let saved = Play::new(window, &court).unwrap().suspend(&rng);
+
+        let json = save::to_json(&saved, &save_migrations()).unwrap();
+        let loaded: MatchSave = save::from_json(&json, &save_migrations()).unwrap();
+        let mut restored = Rng::new(0);
+        let play = Play::resume(loaded, &mut restored).unwrap();
+        assert_eq!(restored, rng);
+        assert_eq!(play.suspend(&restored), saved);
     }
 }
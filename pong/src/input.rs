@@ -1,28 +1,82 @@
-use crate::sprite::Pos;
-use crate::world::World;
+use game_engine::input::{AxisBinding, Bindings, Button, GamepadAxis};
+use game_engine::sprite::Pos;
+use game_engine::world::World;
 use game_engine::*;
 
-/// Processes keyboard input and moves the player sprite accordingly.
+/// Default controls, written to the controls file on the first start.
+///
+/// Actions: `start` and `resume` on the title and game-over screens, `pause` and
+/// `suspend` during a match. The `paddle` axis moves the player paddle, negative up.
+pub fn default_bindings() -> Bindings {
+    Bindings::new()
+        .with_action(
+            "start",
+            [
+                Button::Key(ffi::GLFW_KEY_SPACE),
+                Button::Key(ffi::GLFW_KEY_ENTER),
+                Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_A),
+            ],
+        )
+        .with_action(
+            "resume",
+            [
+                Button::Key(ffi::GLFW_KEY_ENTER),
+                Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_Y),
+            ],
+        )
+        .with_action(
+            "pause",
+            [
+                Button::Key(ffi::GLFW_KEY_P),
+                Button::Key(ffi::GLFW_KEY_ESCAPE),
+                Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_START),
+            ],
+        )
+        .with_action(
+            "suspend",
+            [
+                Button::Key(ffi::GLFW_KEY_S),
+                Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_BACK),
+            ],
+        )
+        .with_axis(
+            "paddle",
+            [
+                AxisBinding::Buttons {
+                    negative: Button::Key(ffi::GLFW_KEY_UP),
+                    positive: Button::Key(ffi::GLFW_KEY_DOWN),
+                },
+                AxisBinding::Buttons {
+                    negative: Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_DPAD_UP),
+                    positive: Button::Gamepad(ffi::GLFW_GAMEPAD_BUTTON_DPAD_DOWN),
+                },
+                AxisBinding::gamepad(GamepadAxis::LeftY),
+            ],
+        )
+}
+
+/// Moves the player sprite in the given direction.
 ///
 /// # Arguments
 ///
 /// * `world` - A mutable reference to the game world.
+/// * `direction` - The `paddle` axis, from `-1.0` (up) to `1.0` (down).
 /// * `dt` - The delta time since the last frame, used to scale movement speed.
-pub fn process(world: &mut World, dt: f32) {
+pub fn move_player(world: &mut World, direction: f32, dt: f32) {
     let speed = 0.3;
-    let dist = speed * dt;
+    let dist = speed * dt * direction;
     let window_height = world.window.height;
 
-    on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_UP, {
-        let player = world.get_sprite("player1");
-        let mut new_y = player.pos.y - dist;
-        new_y = new_y.clamp(0.0, window_height - player.size.height);
-        world.set_sprite_pos("player1", Pos { x: player.pos.x, y: new_y });
-    });
-    on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_DOWN, {
+    if direction != 0.0 {
         let player = world.get_sprite("player1");
         let mut new_y = player.pos.y + dist;
         new_y = new_y.clamp(0.0, window_height - player.size.height);
-        world.set_sprite_pos("player1", Pos { x: player.pos.x, y: new_y });
-    });
+        world.set_sprite_pos(
+            "player1",
+            Pos {
+                x: player.pos.x,
+                y: new_y,
+            },
+        );
+    }
 }
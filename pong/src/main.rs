@@ -1,21 +1,22 @@
 //! Entry point for the Rust test game.
 //!
 //! Initializes logging, creates the game instance, and starts the game loop.
+//!
+//! `pong --record FILE` records the session to a replay file, `pong --replay FILE`
+//! plays it back.
 
 mod ai_player;
 mod game;
 mod input;
 mod logging;
-mod movement;
-mod sprite;
-mod sound;
+mod scenes;
 mod view;
-mod world;
 
 use game::*;
 use game_engine::*;
 
-use log::info;
+use game_engine::replay::Replay;
+use log::{error, info, warn};
 
 /// Main function that sets up and runs the game.
 fn main() {
@@ -24,7 +25,19 @@ fn main() {
 
     let mut game = Game::new();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => {}
+        [flag, path] if flag == "--record" => game.record(path),
+        [flag, path] if flag == "--replay" => match Replay::load(path) {
+            Ok(replay) => game.replay(replay),
+            Err(err) => error!("{err}"),
+        },
+        _ => warn!("Usage: pong [--record FILE | --replay FILE]"),
+    }
+
     start_window_and_game_loop!(
+        engine,
         "Pong",
         1024,
         768,
@@ -32,7 +45,7 @@ fn main() {
             game.init();
         },
         {
-            game.game_loop();
+            game.game_loop(&engine);
         },
         {
             game.quit();
@@ -0,0 +1,62 @@
+//! Scenes of Pong: title screen, match, pause overlay and game over.
+//!
+//! The title starts a match, a match ends in the game-over screen once a player
+//! reaches [`WINNING_SCORE`], and game over returns to the title. Scene changes
+//! fade through the [`crate::view::Curtain`]. A suspended match is kept in
+//! the [`save_file`] until the title resumes it.
+
+mod game_over;
+mod pause;
+mod play;
+mod title;
+
+pub use game_over::GameOver;
+pub use pause::Pause;
+pub use play::{MatchSave, Play};
+pub use title::Title;
+
+use game_engine::save::{self, Migrations};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Points needed to win a match.
+pub const WINNING_SCORE: u32 = 5;
+
+/// Level file with the paddles and the ball, relative to the asset directory.
+pub const COURT: &str = "levels/court.json";
+
+/// Duration of the fade between scenes.
+const FADE: Duration = Duration::from_millis(600);
+
+/// File a suspended match is saved to, in the data dir of the player.
+pub fn save_file() -> PathBuf {
+    save::data_dir("pong").join("saves/match.json")
+}
+
+/// Schema version of [`MatchSave`], with the steps that upgrade older save files.
+pub fn save_migrations() -> Migrations {
+    // Version 2 made the paddles kinematic, so the ball bounces off them without
+    // pushing them.
+    Migrations::new(4)
+        .step(1, |data| {
+            for paddle in ["player1", "player2"] {
+                data["world"]["sprites"][paddle]["body"] = "kinematic".into();
+            }
+            Ok(())
+        })
+        // Version 3 put the paddles on layer 2, colliding only with the ball on the
+        // default layer 1.
+        .step(2, |data| {
+            for paddle in ["player1", "player2"] {
+                data["world"]["sprites"][paddle]["layer"] = 2.into();
+                data["world"]["sprites"][paddle]["mask"] = 1.into();
+            }
+            Ok(())
+        })
+        // Version 4 saved the generator of the serves. Older matches continue with
+        // one seeded with 0.
+        .step(3, |data| {
+            data["rng"]["state"] = 0.into();
+            Ok(())
+        })
+}
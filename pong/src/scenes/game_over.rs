@@ -0,0 +1,48 @@
+use super::{FADE, Title, WINNING_SCORE};
+use crate::game::Shared;
+use crate::view::{self, ScoreBoard};
+use game_engine::scene::{Scene, Transition};
+use game_engine::sprite::{Size, Sprite};
+use game_engine::*;
+
+/// Game-over screen: the final score and the winner's paddle on its side of the court.
+/// The `start` action returns to the title.
+pub struct GameOver {
+    score: [u32; 2],
+    score_board: ScoreBoard,
+    winner: Sprite,
+}
+
+impl GameOver {
+    /// # Arguments
+    ///
+    /// * `window` - Size of the game window.
+    /// * `winner` - Index of the winning player, 0 for the human and 1 for the AI.
+    /// * `score` - Final score of both players.
+    pub fn new(window: Size, winner: usize, score: [u32; 2]) -> Self {
+        let x = if winner == 0 {
+            window.width / 4.0
+        } else {
+            window.width * 3.0 / 4.0
+        };
+        Self {
+            score,
+            score_board: ScoreBoard::new(window, WINNING_SCORE),
+            winner: view::white_block(x - 30.0, window.height / 2.0 - 200.0, 60.0, 400.0),
+        }
+    }
+}
+
+impl Scene<Shared, Engine> for GameOver {
+    fn handle_input(&mut self, shared: &mut Shared) -> Transition<Shared, Engine> {
+        if shared.input.just_pressed("start") {
+            return Transition::replace(Title::new(shared.window)).with_fade(FADE);
+        }
+        Transition::None
+    }
+
+    fn render(&self, _shared: &Shared, engine: &Engine) {
+        self.score_board.render(engine, self.score);
+        view::render_all(engine, [&self.winner]);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::game::Shared;
+use crate::view;
+use game_engine::audio::Bus;
+use game_engine::scene::{Scene, Transition};
+use game_engine::sprite::{Size, Sprite};
+use game_engine::*;
+
+/// Music volume while paused.
+const DUCKED_VOLUME: f32 = 0.3;
+
+/// Pause overlay: the frozen match stays visible under a frame and a pause sign.
+/// The `pause` action resumes.
+pub struct Pause {
+    overlay: Vec<Sprite>,
+}
+
+impl Pause {
+    pub fn new(window: Size) -> Self {
+        let (width, height) = (window.width, window.height);
+        let (center_x, center_y) = (width / 2.0, height / 2.0);
+        let border = 8.0;
+        Self {
+            overlay: vec![
+                view::white_block(0.0, 0.0, width, border),
+                view::white_block(0.0, height - border, width, border),
+                view::white_block(0.0, 0.0, border, height),
+                view::white_block(width - border, 0.0, border, height),
+                view::white_block(center_x - 35.0, center_y - 50.0, 25.0, 100.0),
+                view::white_block(center_x + 10.0, center_y - 50.0, 25.0, 100.0),
+            ],
+        }
+    }
+}
+
+impl Scene<Shared, Engine> for Pause {
+    fn on_enter(&mut self, shared: &mut Shared) {
+        shared.audio.set_bus_volume(Bus::Music, DUCKED_VOLUME);
+    }
+
+    fn on_exit(&mut self, shared: &mut Shared) {
+        shared.audio.set_bus_volume(Bus::Music, 1.0);
+    }
+
+    fn handle_input(&mut self, shared: &mut Shared) -> Transition<Shared, Engine> {
+        if shared.input.just_pressed("pause") {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn render(&self, _shared: &Shared, engine: &Engine) {
+        view::render_all(engine, &self.overlay);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
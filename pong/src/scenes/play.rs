@@ -0,0 +1,217 @@
+use super::{FADE, GameOver, Pause, Title, WINNING_SCORE, save_file, save_migrations};
+use crate::ai_player;
+use crate::game::{MATCH_MUSIC, Shared};
+use crate::input;
+use crate::view::{self, ScoreBoard};
+use game_engine::audio::{Bus, Music};
+use game_engine::collision::{Border, CollisionEvent, CollisionType};
+use game_engine::hash::StateHasher;
+use game_engine::level::Level;
+use game_engine::rng::Rng;
+use game_engine::save::{self, SaveError, WorldState};
+use game_engine::scene::{Scene, Transition};
+use game_engine::sprite::{Pos, Size, Velocity};
+use game_engine::world::World;
+use game_engine::*;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// A running match. The human player scores when the ball hits the right border,
+/// the AI player when it hits the left one. The `pause` action pauses, `suspend`
+/// saves the match to the [`save_file`] and returns to the title.
+pub struct Play {
+    world: World,
+    /// Paddle direction read by `handle_input` and applied in `update`.
+    direction: f32,
+    score: [u32; 2],
+    /// Start position of the ball, it is served from there in a random diagonal
+    /// direction after every point.
+    serve: Pos,
+    score_board: ScoreBoard,
+}
+
+/// Saved state of a suspended match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchSave {
+    pub world: WorldState,
+    pub score: [u32; 2],
+    pub serve: Pos,
+    /// Generator of the serve directions, so the match continues with the same serves.
+    pub rng: Rng,
+}
+
+/// Sprites a court level must define.
+const REQUIRED_SPRITES: [&str; 3] = ["player1", "player2", "ball"];
+
+impl Play {
+    /// Creates a match with the paddles and the ball at their positions in `court`.
+    ///
+    /// # Returns
+    ///
+    /// * `Err` naming the missing sprite if the level lacks a paddle or the ball.
+    pub fn new(window: Size, court: &Level) -> Result<Self, String> {
+        if let Some(name) = REQUIRED_SPRITES
+            .iter()
+            .find(|name| court.entity(name).is_none())
+        {
+            return Err(format!("court level has no `{name}`"));
+        }
+
+        let mut world = World::empty();
+        world.window = window;
+        court.spawn_into(&mut world);
+        let serve = world.get_sprite("ball").pos;
+
+        Ok(Self {
+            world,
+            direction: 0.0,
+            score: [0, 0],
+            serve,
+            score_board: ScoreBoard::new(window, WINNING_SCORE),
+        })
+    }
+
+    /// Continues a suspended match, restoring its generator into `rng`.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(SaveError::Format)` if a saved sprite is invalid; `rng` is unchanged then.
+    pub fn resume(saved: MatchSave, rng: &mut Rng) -> Result<Self, SaveError> {
+        let window = saved.world.window;
+        let world = World::try_from(saved.world)?;
+        *rng = saved.rng;
+        Ok(Self {
+            world,
+            direction: 0.0,
+            score: saved.score,
+            serve: saved.serve,
+            score_board: ScoreBoard::new(window, WINNING_SCORE),
+        })
+    }
+
+    /// Returns the state needed to resume the match later, with the generator `rng`
+    /// of the serves.
+    pub fn suspend(&self, rng: &Rng) -> MatchSave {
+        MatchSave {
+            world: WorldState::from(&self.world),
+            score: self.score,
+            serve: self.serve,
+            rng: rng.clone(),
+        }
+    }
+
+    /// Awards a point if the ball touched the left or right border, and serves again.
+    ///
+    /// The ball bounces off a border it touches during the frame, so the collision
+    /// events tell whether it was a side border, not the ball position.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The collisions of this frame.
+    /// * `rng` - Picks the direction of the serve.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the player who won the match with this point, or `None`.
+    fn check_score(&mut self, events: &[CollisionEvent], rng: &mut Rng) -> Option<usize> {
+        let scorer = events
+            .iter()
+            .filter(|event| event.sprite == "ball")
+            .find_map(|event| match event.border() {
+                Some(Border::Left) => Some(1),
+                Some(Border::Right) => Some(0),
+                _ => None,
+            })?;
+
+        self.score[scorer] += 1;
+        info!("Score {}:{}", self.score[0], self.score[1]);
+        self.world.set_sprite_pos("ball", self.serve);
+        let Velocity { dx, dy } = self.world.get_sprite("ball").velocity;
+        let mut serve = |speed: f32| {
+            if rng.chance(0.5) {
+                speed.abs()
+            } else {
+                -speed.abs()
+            }
+        };
+        let velocity = Velocity {
+            dx: serve(dx),
+            dy: serve(dy),
+        };
+        self.world.set_sprite_velocity("ball", velocity);
+
+        (self.score[scorer] >= WINNING_SCORE).then_some(scorer)
+    }
+}
+
+impl Scene<Shared, Engine> for Play {
+    fn on_enter(&mut self, shared: &mut Shared) {
+        if let Err(err) = shared
+            .audio
+            .play_music(&Music::new(MATCH_MUSIC), Duration::from_secs(1))
+        {
+            info!("Playing without music: {err}");
+        }
+    }
+
+    fn on_exit(&mut self, shared: &mut Shared) {
+        shared.audio.stop_music(FADE);
+    }
+
+    fn handle_input(&mut self, shared: &mut Shared) -> Transition<Shared, Engine> {
+        if shared.input.just_pressed("pause") {
+            self.direction = 0.0;
+            return Transition::push(Pause::new(shared.window));
+        }
+        if shared.input.just_pressed("suspend") {
+            // If saving fails the match goes on, rather than losing it.
+            match save::save(save_file(), &self.suspend(&shared.rng), &save_migrations()) {
+                Ok(()) => {
+                    info!("Match suspended");
+                    return Transition::replace(Title::new(shared.window)).with_fade(FADE);
+                }
+                Err(err) => error!("{err}"),
+            }
+        }
+        self.direction = shared.input.axis("paddle");
+        Transition::None
+    }
+
+    /// Moves the paddles and the ball, plays a sound from the contact point of every
+    /// collision, and counts points.
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition<Shared, Engine> {
+        input::move_player(&mut self.world, self.direction, dt);
+        ai_player::calc_action(&mut self.world, dt);
+
+        let mut events = movement::move_objects(&mut self.world, dt);
+        events.extend(collision::collision(&mut self.world, dt));
+
+        for event in &events {
+            let sound = match event.kind() {
+                CollisionType::WithBorder => shared.border_sound.sound(),
+                CollisionType::WithSprite => shared.paddle_sound.sound(),
+            };
+            shared.audio.play_at(sound, Bus::Sfx, event.point);
+        }
+
+        match self.check_score(&events, &mut shared.rng) {
+            Some(winner) => Transition::replace(GameOver::new(shared.window, winner, self.score))
+                .with_fade(FADE),
+            None => Transition::None,
+        }
+    }
+
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        self.world.hash_state(hasher);
+        self.score.hash(hasher);
+        hasher.write_f32(self.serve.x);
+        hasher.write_f32(self.serve.y);
+    }
+
+    fn render(&self, _shared: &Shared, engine: &Engine) {
+        view::render(engine, &self.world);
+        self.score_board.render(engine, self.score);
+    }
+}
@@ -0,0 +1,94 @@
+use super::{COURT, FADE, MatchSave, Play, save_file, save_migrations};
+use crate::game::Shared;
+use crate::view;
+use game_engine::assets::{AssetError, Handle};
+use game_engine::level::Level;
+use game_engine::save;
+use game_engine::scene::{Scene, Transition};
+use game_engine::sprite::{Size, Sprite};
+use game_engine::*;
+use log::{error, info};
+
+/// Blink period of the start hint in milliseconds.
+const BLINK: f32 = 1000.0;
+
+/// Title screen: the court at rest and a blinking start hint. The `resume` action
+/// continues a match suspended to the [`save_file`] if there is one; otherwise, and with
+/// `start`, a new match starts on the court from [`COURT`].
+pub struct Title {
+    court: Option<Handle<Level>>,
+    /// Paddles and ball in their resting position.
+    pose: Vec<Sprite>,
+    hint: Sprite,
+    time: f32,
+}
+
+impl Title {
+    pub fn new(window: Size) -> Self {
+        let center_x = window.width / 2.0;
+        let center_y = window.height / 2.0;
+        Self {
+            court: None,
+            pose: vec![
+                view::white_block(20.0, center_y - 100.0, 30.0, 200.0),
+                view::white_block(window.width - 50.0, center_y - 100.0, 30.0, 200.0),
+                view::white_block(center_x - 15.0, center_y - 15.0, 30.0, 30.0),
+            ],
+            hint: view::white_block(center_x - 60.0, window.height - 120.0, 120.0, 12.0),
+            time: 0.0,
+        }
+    }
+}
+
+impl Scene<Shared, Engine> for Title {
+    /// Starts loading the court while the title is shown.
+    fn on_enter(&mut self, shared: &mut Shared) {
+        self.court = Some(shared.assets.load(COURT));
+    }
+
+    fn handle_input(&mut self, shared: &mut Shared) -> Transition<Shared, Engine> {
+        let file = save_file();
+        if shared.input.just_pressed("resume") && file.exists() {
+            // The save is used up by resuming; an unreadable one is dropped as well,
+            // so it does not block the title forever.
+            let saved = save::load::<MatchSave>(&file, &save_migrations());
+            if let Err(err) = std::fs::remove_file(&file) {
+                error!("Could not remove {}: {err}", file.display());
+            }
+            match saved.and_then(|saved| Play::resume(saved, &mut shared.rng)) {
+                Ok(play) => {
+                    info!("Match resumed");
+                    return Transition::replace(play).with_fade(FADE);
+                }
+                Err(err) => error!("{err}"),
+            }
+        }
+        if shared.input.just_pressed("start") {
+            // A broken court file is logged and the title stays, so it can be fixed
+            // and hot reloaded without restarting.
+            let court = self
+                .court
+                .get_or_insert_with(|| shared.assets.load(COURT))
+                .wait();
+            match court
+                .and_then(|court| Play::new(shared.window, &court).map_err(AssetError::Parse))
+            {
+                Ok(play) => return Transition::replace(play).with_fade(FADE),
+                Err(err) => error!("{err}"),
+            }
+        }
+        Transition::None
+    }
+
+    fn update(&mut self, _shared: &mut Shared, dt: f32) -> Transition<Shared, Engine> {
+        self.time = (self.time + dt) % BLINK;
+        Transition::None
+    }
+
+    fn render(&self, _shared: &Shared, engine: &Engine) {
+        view::render_all(engine, &self.pose);
+        if self.time < BLINK / 2.0 {
+            view::render_all(engine, [&self.hint]);
+        }
+    }
+}
@@ -1,13 +1,111 @@
-use crate::world::World;
+use game_engine::sprite::{Color, Pos, Size, Sprite, Velocity};
+use game_engine::world::World;
 use game_engine::*;
 
+const WHITE: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+};
+const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+/// Creates a resting sprite for drawing static shapes.
+pub fn block(x: f32, y: f32, width: f32, height: f32, color: Color) -> Sprite {
+    Sprite::new(
+        Pos { x, y },
+        Velocity { dx: 0.0, dy: 0.0 },
+        color,
+        Size { width, height },
+    )
+}
+
+/// Creates a white resting sprite.
+pub fn white_block(x: f32, y: f32, width: f32, height: f32) -> Sprite {
+    block(x, y, width, height, WHITE)
+}
+
 /// Renders the player sprite and all other sprites in the world.
 ///
 /// # Arguments
 ///
+/// * `engine` - The engine context of the game window.
 /// * `world` - A reference to the game world containing sprites to render.
-pub fn render(world: &World) {
+pub fn render(engine: &Engine, world: &World) {
     for sprite_ref in world.get_sprites().values() {
-        rust_render_sprite(sprite_ref.get_c_sprite());
+        sprite_ref.render(engine);
+    }
+}
+
+/// Renders sprites in the given order.
+pub fn render_all<'a>(engine: &Engine, sprites: impl IntoIterator<Item = &'a Sprite>) {
+    for sprite in sprites {
+        sprite.render(engine);
+    }
+}
+
+/// Score of both players, drawn as a row of squares at the top of each half.
+pub struct ScoreBoard {
+    left: Vec<Sprite>,
+    right: Vec<Sprite>,
+}
+
+impl ScoreBoard {
+    /// Creates the squares for scores up to `max_score`.
+    pub fn new(window: Size, max_score: u32) -> Self {
+        let square = 16.0;
+        let step = 2.0 * square;
+        let center = window.width / 2.0;
+        Self {
+            left: (0..max_score)
+                .map(|i| white_block(center - step * (i + 1) as f32, square, square, square))
+                .collect(),
+            right: (0..max_score)
+                .map(|i| white_block(center + square + step * i as f32, square, square, square))
+                .collect(),
+        }
+    }
+
+    /// Renders one square per point of each player.
+    pub fn render(&self, engine: &Engine, score: [u32; 2]) {
+        render_all(engine, self.left.iter().take(score[0] as usize));
+        render_all(engine, self.right.iter().take(score[1] as usize));
+    }
+}
+
+/// Black bars that close from the top and bottom over the screen.
+///
+/// Stands in for a fade to black, which the C library cannot draw without transparency.
+pub struct Curtain {
+    top: Sprite,
+    bottom: Sprite,
+    window: Size,
+}
+
+impl Curtain {
+    pub fn new(window: Size) -> Self {
+        let half = window.height / 2.0;
+        Self {
+            top: block(0.0, -half, window.width, half, BLACK),
+            bottom: block(0.0, window.height, window.width, half, BLACK),
+            window,
+        }
+    }
+
+    /// Renders the curtain closed by `level`, from 0.0 (open) to 1.0 (closed).
+    pub fn render(&mut self, engine: &Engine, level: f32) {
+        if level <= 0.0 {
+            return;
+        }
+        let half = self.window.height / 2.0;
+        let covered = level.min(1.0) * half;
+        self.top.set_pos(&Pos {
+            x: 0.0,
+            y: covered - half,
+        });
+        self.bottom.set_pos(&Pos {
+            x: 0.0,
+            y: self.window.height - covered,
+        });
+        render_all(engine, [&self.top, &self.bottom]);
     }
 }
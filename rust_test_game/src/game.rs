@@ -1,37 +1,82 @@
 use crate::input;
 use crate::sprite_creator;
 use crate::sprite_data::SpriteData;
-use crate::sprite::{Pos, Size, Color};
 use crate::view;
-use crate::world::World;
+use crate::world;
 use crossbeam_channel::unbounded;
+use game_engine::audio::{Audio, Bus, Sound};
+use game_engine::clock::{Clock, RealClock};
+use game_engine::ecs::{Schedule, World};
+use game_engine::input::{Bindings, Input};
+use game_engine::level::Level;
+use game_engine::save::{self, Migrations};
+use game_engine::sprite::{Color, Pos, Size};
 use game_engine::*;
 use log::{error, info, warn};
+use std::path::PathBuf;
 use std::thread::{self, JoinHandle};
-use std::time::Instant;
+use std::time::Duration;
 
-/// Main game structure holding the world, timing, communication channels, and thread handles.
+/// Start layout with the player sprite.
+const LEVEL: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/level.json");
+
+/// Controls of the player in the data dir of the player, created with the defaults on
+/// the first start.
+fn controls_file() -> PathBuf {
+    save::data_dir("rust_test_game").join("controls.json")
+}
+
+/// Session save with the sprites of the last run, written on quit to the data dir of
+/// the player.
+fn save_file() -> PathBuf {
+    save::data_dir("rust_test_game").join("saves/session.json")
+}
+
+/// Schema version of [`world::SessionSave`], with the steps that upgrade older saves.
+fn save_migrations() -> Migrations {
+    Migrations::new(1)
+}
+
+/// Size of the game window.
+const WINDOW: Size = Size {
+    width: 1024.0,
+    height: 768.0,
+};
+
+/// Main game structure holding the world and its systems, input, timing, communication
+/// channels, thread handles and audio.
 pub struct Game {
     world: World,
-    last_time: Instant,
+    schedule: Schedule,
+    input: Input,
+    clock: RealClock,
     rx: Option<crossbeam_channel::Receiver<SpriteData>>,
     tx: Option<crossbeam_channel::Sender<()>>,
     handles: Vec<JoinHandle<()>>,
+    audio: Audio,
+    spawn_sound: Sound,
+    pickup_sound: Sound,
 }
 
 impl Game {
     /// Creates a new `Game` instance with an empty world and initializes timing and channels.
     pub fn new() -> Self {
         Self {
-            world: World::empty(),
-            last_time: Instant::now(),
+            world: World::new(),
+            schedule: Schedule::new(),
+            input: Input::new(Bindings::new()),
+            clock: RealClock::new(2.0),
             rx: None,
             tx: None,
             handles: Vec::new(),
+            audio: Audio::new(),
+            spawn_sound: Sound::tone(660.0, Duration::from_millis(80), 0.15),
+            pickup_sound: Sound::tone(990.0, Duration::from_millis(60), 0.15),
         }
     }
 
-    /// Initializes the game, sets up threads for sprite creation, and sets the initial player sprite.
+    /// Initializes the game, sets up threads for sprite creation, reads the controls, and
+    /// spawns the sprites of the last session, or the level on the first run.
     pub fn init(&mut self) {
         info!("Init game threads");
 
@@ -65,26 +110,50 @@ impl Game {
         });
 
         self.handles.push(handle);
-        self.last_time = Instant::now();
-
-        // ✅ Updated: use Pos, Size, Color
-        self.world.set_player_sprite(
-            Pos { x: 100.0, y: 100.0 },
-            Size { width: 100, height: 100 },
-            Color { r: 255, g: 0, b: 0 },
-        );
+
+        let bindings = Bindings::load_or_init(controls_file(), input::default_bindings())
+            .unwrap_or_else(|err| {
+                error!("{err}, using the default controls");
+                input::default_bindings()
+            });
+        self.input = Input::new(bindings);
+        self.clock.reset();
+
+        self.schedule.add_system("movement", movement::move_system);
+        self.schedule.add_system("triggers", collision::trigger_system);
+
+        let file = save_file();
+        if file.exists() {
+            match save::load(&file, &save_migrations()) {
+                Ok(session) => {
+                    world::restore_session(&mut self.world, &session);
+                    info!("Restored {} sprites", self.world.len());
+                    return;
+                }
+                Err(err) => error!("{err}, starting from the level"),
+            }
+        }
+        match Level::load(LEVEL) {
+            Ok(level) => world::spawn_level(&mut self.world, &level, WINDOW),
+            Err(err) => error!("{err}"),
+        }
     }
 
-    /// Receives new sprites from the background thread and adds them to the world.
+    /// Receives new sprites from the background thread, adds them to the world as
+    /// collectibles and plays a spawn sound.
     fn receive_new_sprites(&mut self) {
         match &self.rx {
             Some(rx) => {
                 if let Ok(received) = rx.try_recv() {
-                    self.world.add_sprite(
-                        Pos { x: received.x, y: received.y },
+                    world::spawn_collectible(
+                        &mut self.world,
+                        Pos {
+                            x: received.x,
+                            y: received.y,
+                        },
                         Size {
-                            width: received.width,
-                            height: received.height,
+                            width: received.width as f32,
+                            height: received.height as f32,
                         },
                         Color {
                             r: received.r,
@@ -92,6 +161,7 @@ impl Game {
                             b: received.b,
                         },
                     );
+                    self.audio.play(&self.spawn_sound, Bus::Sfx);
                 }
             }
             None => {
@@ -100,34 +170,46 @@ impl Game {
         }
     }
 
-    /// Calculates the delta time (dt) since the last frame in milliseconds.
-    fn calc_dt(&mut self) -> f32 {
-        let dt = self.last_time.elapsed().as_millis();
-
-        self.last_time = Instant::now();
+    /// Main game loop: clears the screen, processes input, receives new sprites, runs the
+    /// systems, picks up the collectibles the player touches and renders the world.
+    ///
+    /// Only the main thread holds the `Engine`; the download thread never touches OpenGL.
+    pub fn game_loop(&mut self, engine: &Engine) {
+        rust_clear_screen(engine);
 
-        if dt > 2 { dt as f32 } else { 2.0 }
-    }
+        let dt = self.clock.tick();
 
-    /// Main game loop: clears the screen, processes input, receives new sprites, and renders the world.
-    pub fn game_loop(&mut self) {
-        rust_clear_screen();
+        self.input.update(engine);
+        input::process(&self.input, &mut self.world, dt);
 
-        let dt = self.calc_dt();
+        self.receive_new_sprites();
 
-        input::process(&mut self.world, dt);
+        self.schedule.run(&mut self.world, dt);
 
-        self.receive_new_sprites();
+        let picked = world::collect_pickups(&mut self.world);
+        if picked > 0 {
+            info!("Picked up {picked} sprites");
+            self.audio.play(&self.pickup_sound, Bus::Sfx);
+        }
 
-        view::render(&self.world);
+        view::render(engine, &self.world);
     }
 
-    /// Cleans up the game, sends termination signals to threads, and waits for them to finish.
+    /// Cleans up the game: saves the sprites for the next session, sends termination
+    /// signals to threads, and waits for them to finish.
     ///
     /// # Returns
     ///
     /// * `true` if cleanup was successful.
     pub fn quit(self) -> bool {
+        if let Err(err) = save::save(
+            save_file(),
+            &world::save_session(&self.world),
+            &save_migrations(),
+        ) {
+            error!("{err}");
+        }
+
         match &self.tx {
             Some(tx) => match &tx.send(()) {
                 Ok(_) => {
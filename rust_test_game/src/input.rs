@@ -1,26 +1,69 @@
+use crate::world::Player;
+use game_engine::ecs::World;
+use game_engine::input::{AxisBinding, Bindings, Button, GamepadAxis, Input};
+use game_engine::sprite::Sprite;
 use game_engine::*;
-use crate::world::World;
 
-/// Processes keyboard input and moves the player sprite accordingly.
+/// Default controls, written to the controls file on the first start: the `move_x`
+/// and `move_y` axes on the cursor keys, WASD, the gamepad d-pad and left stick.
+pub fn default_bindings() -> Bindings {
+    let keys = |negative, positive| AxisBinding::Buttons {
+        negative: Button::Key(negative),
+        positive: Button::Key(positive),
+    };
+    let dpad = |negative, positive| AxisBinding::Buttons {
+        negative: Button::Gamepad(negative),
+        positive: Button::Gamepad(positive),
+    };
+    Bindings::new()
+        .with_axis(
+            "move_x",
+            [
+                keys(ffi::GLFW_KEY_LEFT, ffi::GLFW_KEY_RIGHT),
+                keys(ffi::GLFW_KEY_A, ffi::GLFW_KEY_D),
+                dpad(
+                    ffi::GLFW_GAMEPAD_BUTTON_DPAD_LEFT,
+                    ffi::GLFW_GAMEPAD_BUTTON_DPAD_RIGHT,
+                ),
+                AxisBinding::gamepad(GamepadAxis::LeftX),
+            ],
+        )
+        .with_axis(
+            "move_y",
+            [
+                keys(ffi::GLFW_KEY_UP, ffi::GLFW_KEY_DOWN),
+                keys(ffi::GLFW_KEY_W, ffi::GLFW_KEY_S),
+                dpad(
+                    ffi::GLFW_GAMEPAD_BUTTON_DPAD_UP,
+                    ffi::GLFW_GAMEPAD_BUTTON_DPAD_DOWN,
+                ),
+                AxisBinding::gamepad(GamepadAxis::LeftY),
+            ],
+        )
+}
+
+/// Moves the player sprite along the `move_x` and `move_y` axes.
 ///
 /// # Arguments
 ///
+/// * `input` - The input state of the frame.
 /// * `world` - A mutable reference to the game world.
 /// * `dt` - The delta time since the last frame, used to scale movement speed.
-pub fn process(world: &mut World, dt: f32) {
-        let speed = 0.1;
-        let dist = speed * dt;
+pub fn process(input: &Input, world: &mut World, dt: f32) {
+    let speed = 0.1;
+    let dist = speed * dt;
+
+    let (dx, dy) = (input.axis("move_x"), input.axis("move_y"));
+    if dx != 0.0 || dy != 0.0 {
+        move_player(world, dx * dist, dy * dist);
+    }
+}
 
-        on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_RIGHT, {
-            world.move_player(dist, 0.0);
-        });
-        on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_LEFT, {
-            world.move_player(-dist, 0.0);
-        });
-        on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_UP, {
-            world.move_player(0.0, -dist);
-        });
-        on_key_press!(ffi::rust_get_window(), ffi::GLFW_KEY_DOWN, {
-            world.move_player(0.0, dist);
-        });
-    }
\ No newline at end of file
+/// Moves the sprites of all player entities by the given delta values.
+fn move_player(world: &mut World, dx: f32, dy: f32) {
+    world.query::<(&mut Sprite, &Player)>(|_, (sprite, _)| {
+        sprite.pos.x += dx;
+        sprite.pos.y += dy;
+        sprite.update_pos();
+    });
+}
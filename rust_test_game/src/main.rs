@@ -5,7 +5,6 @@
 mod input;
 mod game;
 mod logging;
-mod sprite;
 mod sprite_creator;
 mod sprite_data;
 mod view;
@@ -24,6 +23,7 @@ fn main() {
     let mut game = Game::new();
 
     start_window_and_game_loop!(
+        engine,
         "Test game 1",
         1024,
         768,
@@ -31,7 +31,7 @@ fn main() {
             game.init();
         },
         {
-            game.game_loop();
+            game.game_loop(&engine);
         },
         {
             game.quit();
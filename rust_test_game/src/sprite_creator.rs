@@ -1,5 +1,5 @@
 use crate::sprite_data::SpriteData;
-use reqwest;
+use game_engine::net;
 
 /// Fetches new sprite data from a remote API and parses it into a `SpriteData` struct.
 ///
@@ -8,25 +8,13 @@ use reqwest;
 /// * `Ok(SpriteData)` if the sprite data is successfully fetched and parsed.
 /// * `Err(String)` if there is an error during the fetch or parse process.
 pub fn get_new_sprite_data() -> Result<SpriteData, String> {
-    let body = reqwest::blocking::get(
+    let json_string = net::get_text(
         "https://get-random-sprite-data-dan-chiarlones-projects.vercel.app/api/handler",
-    );
+    )?;
 
-    match body {
-        Ok(text) => {
-            let pt = text.text();
-            match pt {
-                Ok(json_string) => {
-                    let sd = SpriteData::from_json_string(&json_string);
-                    match sd {
-                        Ok(sprite_data) => Ok(sprite_data),
-                        Err(_) => Err(String::from("Error parsing sprite data")),
-                    }
-                }
-                Err(_) => Err(String::from("Error parsing json string")),
-            }
-        }
-        Err(_) => Err(String::from("Error downloading json string")),
+    match SpriteData::from_json_string(&json_string) {
+        Ok(sprite_data) => Ok(sprite_data),
+        Err(_) => Err(String::from("Error parsing sprite data")),
     }
 }
 
@@ -1,14 +1,15 @@
-use crate::world::World;
+use game_engine::ecs::World;
+use game_engine::sprite::Sprite;
 use game_engine::*;
 
-/// Renders the player sprite and all other sprites in the world.
+/// Renders all sprite entities in the world.
 ///
 /// # Arguments
 ///
+/// * `engine` - The engine context of the game window.
 /// * `world` - A reference to the game world containing sprites to render.
-pub fn render(world: &World) {
-    rust_render_sprite(world.get_player_sprite().get_c_sprite());
-    for sprite_ref in world.get_sprites() {
-        rust_render_sprite(sprite_ref.get_c_sprite());
-    }
+pub fn render(engine: &Engine, world: &World) {
+    world.query::<&Sprite>(|_, sprite| {
+        sprite.render(engine);
+    });
 }
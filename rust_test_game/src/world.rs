@@ -1,51 +1,216 @@
-use crate::sprite::*;
-
-/// Represents the game world, containing the player sprite and other sprites.
-pub struct World {
-    player_sprite: Sprite,
-    sprites: Vec<Sprite>,
-}
-
-impl World {
-    /// Creates an empty world with a default player sprite and no other sprites.
-    pub fn empty() -> Self {
-        Self {
-            player_sprite: Sprite::new(
-                Pos { x: 0.0, y: 0.0 },
-                Velocity { dx: 0.0, dy: 0.0 },
-                Color { r: 0, g: 0, b: 0 },
-                Size {
-                    width: 0,
-                    height: 0,
-                },
-            ),
-            sprites: Vec::new(),
+//! Game world on top of the engine ECS.
+//!
+//! Every visible object is an entity with an engine [`Sprite`] component; the
+//! entity controlled by the keyboard additionally has the [`Player`] tag. Downloaded
+//! sprites are [`Collectible`] triggers the player picks up by touching them. The start
+//! layout comes from the level file `assets/level.json`; later sessions continue
+//! with the sprites of a [`SessionSave`].
+
+use game_engine::collision::TriggerEvents;
+use game_engine::ecs::{Entity, World};
+use game_engine::level::Level;
+use game_engine::save::SpriteState;
+use game_engine::sprite::{Color, Pos, Size, Sprite, Velocity};
+use serde::{Deserialize, Serialize};
+
+/// Tag component of the entity moved by the keyboard.
+pub struct Player;
+
+/// Tag component of a trigger sprite that disappears when the player touches it.
+pub struct Collectible;
+
+/// Spawns a resting sprite entity with the given position, size, and color.
+///
+/// # Returns
+///
+/// The new entity.
+pub fn spawn_sprite(world: &mut World, pos: Pos, size: Size, color: Color) -> Entity {
+    let entity = world.spawn();
+    world.insert(
+        entity,
+        Sprite::new(pos, Velocity { dx: 0.0, dy: 0.0 }, color, size),
+    );
+    entity
+}
+
+/// Spawns a collectible: a resting trigger sprite with the [`Collectible`] tag.
+///
+/// # Returns
+///
+/// The new entity.
+pub fn spawn_collectible(world: &mut World, pos: Pos, size: Size, color: Color) -> Entity {
+    let entity = spawn_sprite(world, pos, size, color);
+    make_collectible(world, entity);
+    entity
+}
+
+fn make_collectible(world: &mut World, entity: Entity) {
+    if let Some(sprite) = world.get_mut::<Sprite>(entity) {
+        sprite.trigger = true;
+    }
+    world.insert(entity, Collectible);
+}
+
+/// Spawns the entities of a level, tags the ones tagged `"player"` in the file with
+/// [`Player`] and makes the ones tagged `"collectible"` collectibles.
+pub fn spawn_level(world: &mut World, level: &Level, window: Size) {
+    for (def, entity) in level
+        .entities
+        .iter()
+        .zip(level.spawn_entities(world, window))
+    {
+        if def.has_tag("player") {
+            world.insert(entity, Player);
+        }
+        if def.has_tag("collectible") {
+            make_collectible(world, entity);
         }
     }
+}
 
-    /// Sets the player sprite's position, size, and color.
-    pub fn set_player_sprite(&mut self, pos: Pos, size: Size, color: Color) {
-        self.player_sprite = Sprite::new(pos, Velocity { dx: 0.0, dy: 0.0 }, color, size);
+/// Despawns the collectibles the player touched, as reported by the last run of the
+/// trigger system.
+///
+/// # Returns
+///
+/// The number of collectibles picked up.
+pub fn collect_pickups(world: &mut World) -> usize {
+    let Some(TriggerEvents(events)) = world.remove_resource::<TriggerEvents>() else {
+        return 0;
+    };
+    let mut picked = 0;
+    for event in events {
+        if world.has::<Collectible>(event.trigger)
+            && world.has::<Player>(event.other)
+            && world.despawn(event.trigger)
+        {
+            picked += 1;
+        }
     }
+    picked
+}
+
+/// Sprites of a session, including the downloaded ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSave {
+    pub sprites: Vec<SavedSprite>,
+}
+
+/// A saved sprite entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSprite {
+    #[serde(flatten)]
+    pub sprite: SpriteState,
+    /// Whether the entity has the [`Player`] tag.
+    #[serde(default)]
+    pub player: bool,
+    /// Whether the entity has the [`Collectible`] tag.
+    #[serde(default)]
+    pub collectible: bool,
+}
+
+/// Returns the sprite entities of `world` in spawn order.
+pub fn save_session(world: &World) -> SessionSave {
+    let mut sprites = Vec::new();
+    world.query::<(&Sprite, Option<&Player>, Option<&Collectible>)>(
+        |_, (sprite, player, collectible)| {
+            sprites.push(SavedSprite {
+                sprite: SpriteState::from(sprite),
+                player: player.is_some(),
+                collectible: collectible.is_some(),
+            });
+        },
+    );
+    SessionSave { sprites }
+}
 
-    /// Adds a new sprite to the world with the given position, size, and color.
-    pub fn add_sprite(&mut self, pos: Pos, size: Size, color: Color) {
-        self.sprites
-            .push(Sprite::new(pos, Velocity { dx: 0.0, dy: 0.0 }, color, size));
+/// Spawns the saved sprite entities into `world`.
+pub fn restore_session(world: &mut World, session: &SessionSave) {
+    for saved in &session.sprites {
+        let entity = world.spawn();
+        world.insert(entity, Sprite::from(saved.sprite.clone()));
+        if saved.player {
+            world.insert(entity, Player);
+        }
+        if saved.collectible {
+            world.insert(entity, Collectible);
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_engine::collision;
 
-    /// Returns a reference to the player sprite.
-    pub fn get_player_sprite(&self) -> &Sprite {
-        &self.player_sprite
+    /// Verify that the shipped level spawns exactly one player.
+    #[test]
+    fn test_level_spawns_player() {
+        let level = Level::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/level.json")).unwrap();
+        let mut world = World::new();
+        spawn_level(
+            &mut world,
+            &level,
+            Size {
+                width: 1024.0,
+                height: 768.0,
+            },
+        );
+
+        let mut players = 0;
+        world.query::<(&Sprite, &Player)>(|_, _| players += 1);
+        assert_eq!(players, 1);
     }
 
-    /// Returns a reference to the vector of all sprites in the world.
-    pub fn get_sprites(&self) -> &Vec<Sprite> {
-        &self.sprites
+    /// Verify that a restored session has the same sprites and player.
+    #[test]
+    fn test_session_round_trip() {
+        let mut world = World::new();
+        let player = spawn_sprite(
+            &mut world,
+            Pos { x: 1.0, y: 2.0 },
+            Size {
+                width: 10.0,
+                height: 10.0,
+            },
+            Color { r: 255, g: 0, b: 0 },
+        );
+        world.insert(player, Player);
+        spawn_sprite(
+            &mut world,
+            Pos { x: 30.0, y: 40.0 },
+            Size {
+                width: 5.0,
+                height: 8.0,
+            },
+            Color { r: 0, g: 0, b: 255 },
+        );
+        let session = save_session(&world);
+
+        let mut restored = World::new();
+        restore_session(&mut restored, &session);
+        assert_eq!(save_session(&restored), session);
+        assert_eq!(session.sprites.iter().filter(|s| s.player).count(), 1);
     }
 
-    /// Moves the player sprite by the given delta values.
-    pub fn move_player(&mut self, dx: f32, dy: f32) {
-        self.player_sprite.move_pos(dx, dy);
+    /// Verify that the player picks up a collectible it touches, and only that one.
+    #[test]
+    fn test_collect_pickups() {
+        let mut world = World::new();
+        let size = Size {
+            width: 10.0,
+            height: 10.0,
+        };
+        let color = Color { r: 0, g: 255, b: 0 };
+        let player = spawn_sprite(&mut world, Pos { x: 0.0, y: 0.0 }, size, color);
+        world.insert(player, Player);
+        let touched = spawn_collectible(&mut world, Pos { x: 5.0, y: 5.0 }, size, color);
+        let far = spawn_collectible(&mut world, Pos { x: 50.0, y: 0.0 }, size, color);
+
+        collision::trigger_system(&mut world, 16.0);
+        assert_eq!(collect_pickups(&mut world), 1);
+        assert!(!world.is_alive(touched));
+        assert!(world.is_alive(far) && world.is_alive(player));
+        assert_eq!(collect_pickups(&mut world), 0);
     }
 }